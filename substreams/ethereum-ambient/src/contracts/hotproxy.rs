@@ -5,7 +5,6 @@ use ethabi::{decode, ParamType};
 use hex_literal::hex;
 use substreams_ethereum::pb::eth::v2::Call;
 
-pub const AMBIENT_HOTPROXY_CONTRACT: [u8; 20] = hex!("37e00522Ce66507239d59b541940F99eA19fF81F");
 pub const USER_CMD_HOTPROXY_FN_SIG: [u8; 4] = hex!("f96dc788");
 
 pub const SWAP_ABI_HOTPROXY_INPUT: &[ParamType] = &[
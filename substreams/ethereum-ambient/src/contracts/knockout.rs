@@ -5,9 +5,11 @@ use ethabi::{decode, ParamType};
 use hex_literal::hex;
 use substreams_ethereum::pb::eth::v2::Call;
 
-pub const AMBIENT_KNOCKOUT_CONTRACT: [u8; 20] = hex!("7F5D75AdE75646919c923C98D53E9Cc7Be7ea794");
 pub const USER_CMD_KNOCKOUT_FN_SIG: [u8; 4] = hex!("f96dc788");
 
+const KNOCKOUT_MINT_CODE: u8 = 91;
+const KNOCKOUT_BURN_CODE: u8 = 92;
+
 // Represents the ABI of any cmd which is not mint or burn
 const KNOCKOUT_INTERNAL_OTHER_CMD_ABI: &[ParamType] = &[
     ParamType::Uint(8),
@@ -51,8 +53,8 @@ pub fn decode_knockout_call(
             .ok_or_else(|| anyhow!("Failed to Knockout userCmd input data.".to_string()))?;
 
         let code = input_data[31];
-        let is_mint = code == 91;
-        let is_burn = code == 92;
+        let is_mint = code == KNOCKOUT_MINT_CODE;
+        let is_burn = code == KNOCKOUT_BURN_CODE;
 
         let abi = if is_mint || is_burn {
             KNOCKOUT_INTERNAL_MINT_BURN_ABI
@@ -104,3 +106,65 @@ pub fn decode_knockout_call(
         bail!("Failed to decode inputs for knockout call.".to_string());
     }
 }
+
+/// Decodes the range a knockout mint adds liquidity to, as
+/// `(pool_hash, is_bid, lower_tick, upper_tick)`. Returns `None` for any other knockout command.
+///
+/// The knockout pivot is the lower tick of bid positions and the upper tick of ask positions.
+#[allow(clippy::type_complexity)]
+pub fn decode_knockout_mint(
+    call: &Call,
+) -> Result<Option<([u8; 32], bool, i32, i32)>, anyhow::Error> {
+    let external_cmd = decode(KNOCKOUT_EXTERNAL_ABI, &call.input[4..])
+        .map_err(|_| anyhow!("Failed to decode inputs for knockout call.".to_string()))?;
+    let input_data = external_cmd[0]
+        .to_owned()
+        .into_bytes()
+        .ok_or_else(|| anyhow!("Failed to Knockout userCmd input data.".to_string()))?;
+
+    if input_data[31] != KNOCKOUT_MINT_CODE {
+        return Ok(None);
+    }
+
+    let mint_inputs = decode(KNOCKOUT_INTERNAL_MINT_BURN_ABI, &input_data)
+        .map_err(|_| anyhow!("Failed to decode knockout mint inputs.".to_string()))?;
+    let base_token = mint_inputs[1]
+        .to_owned()
+        .into_address()
+        .ok_or_else(|| anyhow!("Failed to convert base token to address: {:?}", &mint_inputs[1]))?
+        .to_fixed_bytes()
+        .to_vec();
+    let quote_token = mint_inputs[2]
+        .to_owned()
+        .into_address()
+        .ok_or_else(|| anyhow!("Failed to convert quote token to address: {:?}", &mint_inputs[2]))?
+        .to_fixed_bytes()
+        .to_vec();
+    let mut pool_index_buf = [0u8; 32];
+    mint_inputs[3]
+        .to_owned()
+        .into_uint()
+        .ok_or_else(|| {
+            anyhow!("Failed to convert pool index to bytes for knockout call".to_string())
+        })?
+        .to_big_endian(&mut pool_index_buf);
+
+    // int24 ticks are sign extended to 256 bits, so their low 32 bits are a valid i32.
+    let lower_tick = mint_inputs[4]
+        .to_owned()
+        .into_int()
+        .ok_or_else(|| anyhow!("Failed to convert lower tick: {:?}", &mint_inputs[4]))?
+        .low_u32() as i32;
+    let upper_tick = mint_inputs[5]
+        .to_owned()
+        .into_int()
+        .ok_or_else(|| anyhow!("Failed to convert upper tick: {:?}", &mint_inputs[5]))?
+        .low_u32() as i32;
+    let is_bid = mint_inputs[6]
+        .to_owned()
+        .into_bool()
+        .ok_or_else(|| anyhow!("Failed to convert is bid: {:?}", &mint_inputs[6]))?;
+
+    let pool_hash = encode_pool_hash(base_token, quote_token, pool_index_buf.to_vec());
+    Ok(Some((pool_hash, is_bid, lower_tick, upper_tick)))
+}
@@ -12,7 +12,6 @@ use crate::{
     utils::{decode_flows_from_output, encode_pool_hash},
 };
 
-pub const AMBIENT_CONTRACT: [u8; 20] = hex!("aaaaaaaaa24eeeb8d57d431224f73832bc34f688");
 pub const USER_CMD_FN_SIG: [u8; 4] = hex!("a15112f9");
 
 const USER_CMD_EXTERNAL_ABI: &[ParamType] = &[
@@ -98,6 +97,7 @@ pub fn decode_direct_swap_call(
 pub fn decode_pool_init(
     call: &Call,
     tx_index: u64,
+    dex: &[u8],
 ) -> Result<Option<AmbientProtocolComponent>, anyhow::Error> {
     // Decode external call to UserCmd
     if let Ok(external_params) = decode(USER_CMD_EXTERNAL_ABI, &call.input[4..]) {
@@ -146,6 +146,7 @@ pub fn decode_pool_init(
                     tokens,
                     pool_index,
                     tx_index,
+                    dex: dex.to_vec(),
                 };
                 Ok(Some(new_component))
             } else {
@@ -164,7 +165,7 @@ impl From<AmbientProtocolComponent> for ProtocolComponent {
         ProtocolComponent {
             id: component.id,
             tokens: component.tokens,
-            contracts: vec![AMBIENT_CONTRACT.to_vec()],
+            contracts: vec![component.dex],
             static_att: vec![Attribute {
                 name: "pool_index".to_string(),
                 value: component.pool_index,
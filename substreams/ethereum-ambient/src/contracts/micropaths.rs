@@ -4,8 +4,6 @@ use ethabi::{decode, ParamType};
 use hex_literal::hex;
 use substreams_ethereum::pb::eth::v2::Call;
 
-pub const AMBIENT_MICROPATHS_CONTRACT: [u8; 20] = hex!("f241bEf0Ea64020655C70963ef81Fea333752367");
-
 pub const SWEEP_SWAP_FN_SIG: [u8; 4] = hex!("7b370fc2");
 
 pub const MINT_AMBIENT_FN_SIG: [u8; 4] = hex!("2ee11587");
@@ -121,6 +119,31 @@ pub fn decode_mint_range_call(
     }
 }
 
+/// Decodes the range of a mintRange call as `(pool_hash, low_tick, high_tick)`.
+pub fn decode_mint_range_ticks(call: &Call) -> Result<([u8; 32], i32, i32), anyhow::Error> {
+    let mint_range = decode(MINT_RANGE_ABI, &call.input[4..])
+        .map_err(|_| anyhow!("Failed to decode inputs for mintRange call.".to_string()))?;
+    let pool_hash: [u8; 32] = mint_range[9]
+        .to_owned()
+        .into_fixed_bytes()
+        .ok_or_else(|| anyhow!("Failed to convert pool hash to fixed bytes".to_string()))?
+        .try_into()
+        .map_err(|_| anyhow!("Pool hash should be 32 bytes long".to_string()))?;
+
+    // int24 ticks are sign extended to 256 bits, so their low 32 bits are a valid i32.
+    let low_tick = mint_range[6]
+        .to_owned()
+        .into_int()
+        .ok_or_else(|| anyhow!("Failed to convert low tick: {:?}", &mint_range[6]))?
+        .low_u32() as i32;
+    let high_tick = mint_range[7]
+        .to_owned()
+        .into_int()
+        .ok_or_else(|| anyhow!("Failed to convert high tick: {:?}", &mint_range[7]))?
+        .low_u32() as i32;
+    Ok((pool_hash, low_tick, high_tick))
+}
+
 pub fn decode_burn_ambient_call(
     call: &Call,
 ) -> Result<([u8; 32], ethabi::Int, ethabi::Int), anyhow::Error> {
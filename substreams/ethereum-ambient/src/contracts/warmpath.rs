@@ -5,8 +5,9 @@ use ethabi::{decode, ParamType};
 use hex_literal::hex;
 use substreams_ethereum::pb::eth::v2::Call;
 
-pub const AMBIENT_WARMPATH_CONTRACT: [u8; 20] = hex!("d268767BE4597151Ce2BB4a70A9E368ff26cB195");
 pub const USER_CMD_WARMPATH_FN_SIG: [u8; 4] = hex!("f96dc788");
+
+const RANGE_MINT_CODES: [u8; 3] = [1, 11, 12];
 const USER_CMD_EXTERNAL_ABI: &[ParamType] = &[
     ParamType::Bytes, // userCmd
 ];
@@ -33,7 +34,7 @@ pub fn decode_warm_path_user_cmd_call(
             .ok_or_else(|| anyhow!("Failed to hotproxy userCmd input data.".to_string()))?;
 
         let code = input_bytes[31];
-        let is_mint = [1, 11, 12, 3, 31, 32].contains(&code);
+        let is_mint = RANGE_MINT_CODES.contains(&code) || [3, 31, 32].contains(&code);
         let is_burn = [2, 21, 22, 4, 41, 42].contains(&code);
         let is_harvest = code == 5;
         if is_mint || is_burn || is_harvest {
@@ -85,3 +86,60 @@ pub fn decode_warm_path_user_cmd_call(
         bail!("Failed to decode WarmPath call external input.".to_string());
     }
 }
+
+/// Decodes the range of a WarmPath range mint as `(pool_hash, bid_tick, ask_tick)`. Returns
+/// `None` for any other WarmPath command.
+pub fn decode_warm_path_range_mint(
+    call: &Call,
+) -> Result<Option<([u8; 32], i32, i32)>, anyhow::Error> {
+    let external_cmd = decode(USER_CMD_EXTERNAL_ABI, &call.input[4..])
+        .map_err(|_| anyhow!("Failed to decode WarmPath call external input.".to_string()))?;
+    let input_bytes = external_cmd[0]
+        .to_owned()
+        .into_bytes()
+        .ok_or_else(|| anyhow!("Failed to WarmPath userCmd input data.".to_string()))?;
+
+    if !RANGE_MINT_CODES.contains(&input_bytes[31]) {
+        return Ok(None);
+    }
+
+    let liquidity_change_calldata = decode(LIQUIDITY_CHANGE_ABI, &input_bytes)
+        .map_err(|_| anyhow!("Failed to decode inputs for WarmPath userCmd call.".to_string()))?;
+    let base_token = liquidity_change_calldata[1]
+        .to_owned()
+        .into_address()
+        .ok_or_else(|| {
+            anyhow!("Failed to convert base token to address: {:?}", &liquidity_change_calldata[1])
+        })?
+        .to_fixed_bytes()
+        .to_vec();
+    let quote_token = liquidity_change_calldata[2]
+        .to_owned()
+        .into_address()
+        .ok_or_else(|| {
+            anyhow!("Failed to convert quote token to address: {:?}", &liquidity_change_calldata[2])
+        })?
+        .to_fixed_bytes()
+        .to_vec();
+    let mut pool_index_buf = [0u8; 32];
+    liquidity_change_calldata[3]
+        .to_owned()
+        .into_uint()
+        .ok_or_else(|| {
+            anyhow!("Failed to convert pool index to bytes for WarmPath userCmd call".to_string())
+        })?
+        .to_big_endian(&mut pool_index_buf);
+
+    // The int24 bid and ask ticks are the fifth and sixth words of the command, sign extended to
+    // 256 bits, so the low 32 bits of each word are a valid i32.
+    let tick = |word: usize| {
+        i32::from_be_bytes(
+            input_bytes[32 * word + 28..32 * (word + 1)]
+                .try_into()
+                .unwrap(),
+        )
+    };
+
+    let pool_hash = encode_pool_hash(base_token, quote_token, pool_index_buf.to_vec());
+    Ok(Some((pool_hash, tick(4), tick(5))))
+}
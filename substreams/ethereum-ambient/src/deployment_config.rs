@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use substreams_ethereum::pb::eth::v2::Call;
+
+/// Indexes of the proxy sidecars in the dex `proxyPaths_`, i.e. the `callpath` argument of
+/// `userCmd`. Defaults to the `CrocSlots` constants the mainnet deployment installed them at:
+/// `SWAP_PROXY_IDX`, `LP_PROXY_IDX`, `COLD_PROXY_IDX` and `KNOCKOUT_LP_PROXY_IDX`.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct Callpaths {
+    pub hot: u16,
+    pub warm: u16,
+    pub cold: u16,
+    pub knockout: u16,
+}
+
+impl Default for Callpaths {
+    fn default() -> Self {
+        Callpaths { hot: 1, warm: 2, cold: 3, knockout: 7 }
+    }
+}
+
+/// The Ambient (CrocSwap) deployment indexed by the package, e.g.
+/// `dex=aaaaaaaaa24eeeb8d57d431224f73832bc34f688&callpaths[knockout]=7`
+///
+/// Sidecars are not identified by their address, which changes whenever governance upgrades
+/// them, but by the callpath the dex delegates the command through.
+#[derive(Deserialize)]
+pub struct DeploymentConfig {
+    /// The `CrocSwapDex` contract, which holds the state of all pools.
+    #[serde(with = "hex::serde")]
+    pub dex: Vec<u8>,
+    #[serde(default)]
+    pub callpaths: Callpaths,
+}
+
+impl DeploymentConfig {
+    /// Returns the callpath of the dex command `call` was made for: the first argument of the
+    /// closest dex call among `call` and its ancestors. All the dex `userCmd` entrypoints
+    /// (`userCmd`, `userCmdRelayer` and `userCmdRouter`) take the callpath as first argument.
+    ///
+    /// Returns `None` if `call` wasn't made by the dex.
+    pub fn callpath_of(&self, call: &Call, calls_by_index: &HashMap<u32, &Call>) -> Option<u16> {
+        let mut current = call;
+        loop {
+            if current.address == self.dex {
+                return current
+                    .input
+                    .get(4..36)
+                    .filter(|word| word[..30].iter().all(|byte| *byte == 0))
+                    .map(|word| u16::from_be_bytes([word[30], word[31]]));
+            }
+            if current.depth == 0 {
+                return None;
+            }
+            current = calls_by_index.get(&current.parent_index)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_deployment() {
+        let config: DeploymentConfig = serde_qs::from_str(
+            "dex=aaaaAAAACB71BF2C8CaE522EA5fa455571A74106&callpaths[knockout]=8",
+        )
+        .unwrap();
+
+        assert_eq!(config.dex, hex::decode("aaaaAAAACB71BF2C8CaE522EA5fa455571A74106").unwrap());
+        assert_eq!(config.callpaths, Callpaths { knockout: 8, ..Default::default() });
+    }
+
+    #[test]
+    fn test_callpath_of() {
+        let config: DeploymentConfig =
+            serde_qs::from_str("dex=aaaaaaaaa24eeeb8d57d431224f73832bc34f688").unwrap();
+        let mut input = hex::decode("a15112f9").unwrap();
+        input.extend([0u8; 31]);
+        input.push(7);
+        let user_cmd = Call { index: 1, address: config.dex.clone(), input, ..Default::default() };
+        let sidecar = Call {
+            index: 2,
+            parent_index: 1,
+            depth: 1,
+            address: vec![1; 20],
+            ..Default::default()
+        };
+        let external = Call { index: 3, address: vec![2; 20], ..Default::default() };
+        let calls_by_index = [&user_cmd, &sidecar, &external]
+            .into_iter()
+            .map(|call| (call.index, call))
+            .collect();
+
+        assert_eq!(config.callpath_of(&sidecar, &calls_by_index), Some(7));
+        assert_eq!(config.callpath_of(&external, &calls_by_index), None);
+    }
+}
@@ -1,6 +1,8 @@
 mod contracts;
+mod deployment_config;
 mod pb;
 
 pub use modules::*;
 mod modules;
+mod pool_state;
 mod utils;
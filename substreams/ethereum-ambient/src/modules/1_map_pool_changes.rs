@@ -1,49 +1,49 @@
-use substreams_ethereum::pb::eth::{self};
+use std::collections::HashMap;
+
+use substreams_ethereum::pb::eth::{self, v2::CallType};
 
 use crate::{
     contracts::{
-        hotproxy::{
-            decode_direct_swap_hotproxy_call, AMBIENT_HOTPROXY_CONTRACT, USER_CMD_HOTPROXY_FN_SIG,
-        },
-        knockout::{decode_knockout_call, AMBIENT_KNOCKOUT_CONTRACT, USER_CMD_KNOCKOUT_FN_SIG},
-        main::{
-            decode_direct_swap_call, decode_pool_init, AMBIENT_CONTRACT, SWAP_FN_SIG,
-            USER_CMD_FN_SIG,
-        },
+        hotproxy::{decode_direct_swap_hotproxy_call, USER_CMD_HOTPROXY_FN_SIG},
+        knockout::{decode_knockout_call, decode_knockout_mint, USER_CMD_KNOCKOUT_FN_SIG},
+        main::{decode_direct_swap_call, decode_pool_init, SWAP_FN_SIG, USER_CMD_FN_SIG},
         micropaths::{
             decode_burn_ambient_call, decode_burn_range_call, decode_mint_ambient_call,
-            decode_mint_range_call, decode_sweep_swap_call, AMBIENT_MICROPATHS_CONTRACT,
+            decode_mint_range_call, decode_mint_range_ticks, decode_sweep_swap_call,
             BURN_AMBIENT_FN_SIG, BURN_RANGE_FN_SIG, MINT_AMBIENT_FN_SIG, MINT_RANGE_FN_SIG,
             SWEEP_SWAP_FN_SIG,
         },
         warmpath::{
-            decode_warm_path_user_cmd_call, AMBIENT_WARMPATH_CONTRACT, USER_CMD_WARMPATH_FN_SIG,
+            decode_warm_path_range_mint, decode_warm_path_user_cmd_call, USER_CMD_WARMPATH_FN_SIG,
         },
     },
-    pb::tycho::ambient::v1::{AmbientBalanceDelta, BlockPoolChanges},
+    deployment_config::DeploymentConfig,
+    pb::tycho::ambient::v1::{
+        AmbientBalanceDelta, AmbientKnockoutPivot, AmbientTickLevel, BlockPoolChanges,
+    },
     utils::from_u256_to_vec,
 };
 
 #[substreams::handlers::map]
-fn map_pool_changes(block: eth::v2::Block) -> Result<BlockPoolChanges, substreams::errors::Error> {
+fn map_pool_changes(
+    params: String,
+    block: eth::v2::Block,
+) -> Result<BlockPoolChanges, substreams::errors::Error> {
+    let config: DeploymentConfig = serde_qs::from_str(&params)?;
+    let callpaths = config.callpaths;
+
     let mut balance_deltas = Vec::new();
     let mut protocol_components = Vec::new();
+    let mut knockout_pivots = Vec::new();
+    let mut tick_levels = Vec::new();
 
     for block_tx in block.transactions() {
         let tx_index = block_tx.index as u64;
-        // extract storage changes
-        let mut storage_changes = block_tx
+        let calls_by_index = block_tx
             .calls
             .iter()
-            .filter(|call| !call.state_reverted)
-            .flat_map(|call| {
-                call.storage_changes
-                    .iter()
-                    .filter(|c| c.address == AMBIENT_CONTRACT)
-            })
-            .collect::<Vec<_>>();
-        storage_changes.sort_unstable_by_key(|change| change.ordinal);
-
+            .map(|call| (call.index, call))
+            .collect::<HashMap<_, _>>();
         let block_calls = block_tx
             .calls
             .iter()
@@ -55,42 +55,69 @@ fn map_pool_changes(block: eth::v2::Block) -> Result<BlockPoolChanges, substream
                 continue;
             }
             let selector: [u8; 4] = call.input[0..4].try_into().unwrap();
-            let address: [u8; 20] = call.address.clone().try_into().unwrap();
+            let is_dex = call.address == config.dex;
+            // Sidecars are delegate called by the dex on behalf of a user command, the callpath
+            // of which tells which sidecar is executing.
+            let callpath = config.callpath_of(call, &calls_by_index);
+            let is_sidecar = call.call_type == CallType::Delegate as i32 && callpath.is_some();
 
-            if call.address == AMBIENT_CONTRACT && selector == USER_CMD_FN_SIG {
+            if is_dex && selector == USER_CMD_FN_SIG && callpath == Some(callpaths.cold) {
                 // Extract pool creations
-                if let Some(protocol_component) = decode_pool_init(call, tx_index)? {
+                if let Some(protocol_component) = decode_pool_init(call, tx_index, &config.dex)? {
                     protocol_components.push(protocol_component);
                 }
             }
 
+            if is_sidecar &&
+                selector == USER_CMD_KNOCKOUT_FN_SIG &&
+                callpath == Some(callpaths.knockout)
+            {
+                // Extract knockout pivots receiving liquidity
+                if let Some((pool_hash, is_bid, lower_tick, upper_tick)) =
+                    decode_knockout_mint(call)?
+                {
+                    knockout_pivots.push(AmbientKnockoutPivot {
+                        pool_hash: pool_hash.to_vec(),
+                        is_bid,
+                        tick: if is_bid { lower_tick } else { upper_tick },
+                        tx_index,
+                    });
+                    tick_levels
+                        .extend(tick_level_pair(pool_hash, lower_tick, upper_tick, tx_index));
+                }
+            }
+
+            // Extract the ticks of range mints, which hold the liquidity levels of the range
+            let range_mint = match selector {
+                USER_CMD_WARMPATH_FN_SIG if is_sidecar && callpath == Some(callpaths.warm) => {
+                    decode_warm_path_range_mint(call)?
+                }
+                MINT_RANGE_FN_SIG if is_sidecar => Some(decode_mint_range_ticks(call)?),
+                _ => None,
+            };
+            if let Some((pool_hash, lower_tick, upper_tick)) = range_mint {
+                tick_levels.extend(tick_level_pair(pool_hash, lower_tick, upper_tick, tx_index));
+            }
+
             // Extract TVL changes
-            let result = match (address, selector) {
-                (AMBIENT_CONTRACT, SWAP_FN_SIG) => Some(decode_direct_swap_call(call)?),
-                (AMBIENT_HOTPROXY_CONTRACT, USER_CMD_HOTPROXY_FN_SIG) => {
+            let result = match selector {
+                SWAP_FN_SIG if is_dex => Some(decode_direct_swap_call(call)?),
+                USER_CMD_HOTPROXY_FN_SIG if is_sidecar && callpath == Some(callpaths.hot) => {
                     Some(decode_direct_swap_hotproxy_call(call)?)
                 }
-                (AMBIENT_MICROPATHS_CONTRACT, SWEEP_SWAP_FN_SIG) => {
-                    Some(decode_sweep_swap_call(call)?)
-                }
-                (AMBIENT_WARMPATH_CONTRACT, USER_CMD_WARMPATH_FN_SIG) => {
+                USER_CMD_WARMPATH_FN_SIG if is_sidecar && callpath == Some(callpaths.warm) => {
                     decode_warm_path_user_cmd_call(call)?
                 }
-                (AMBIENT_MICROPATHS_CONTRACT, MINT_RANGE_FN_SIG) => {
-                    Some(decode_mint_range_call(call)?)
-                }
-                (AMBIENT_MICROPATHS_CONTRACT, MINT_AMBIENT_FN_SIG) => {
-                    Some(decode_mint_ambient_call(call)?)
-                }
-                (AMBIENT_MICROPATHS_CONTRACT, BURN_RANGE_FN_SIG) => {
-                    Some(decode_burn_range_call(call)?)
-                }
-                (AMBIENT_MICROPATHS_CONTRACT, BURN_AMBIENT_FN_SIG) => {
-                    Some(decode_burn_ambient_call(call)?)
-                }
-                (AMBIENT_KNOCKOUT_CONTRACT, USER_CMD_KNOCKOUT_FN_SIG) => {
+                USER_CMD_KNOCKOUT_FN_SIG if is_sidecar && callpath == Some(callpaths.knockout) => {
                     Some(decode_knockout_call(call)?)
                 }
+                // MicroPaths are called by the LongPath sidecar for each step of a compound
+                // command
+                SWEEP_SWAP_FN_SIG if is_sidecar => Some(decode_sweep_swap_call(call)?),
+                MINT_RANGE_FN_SIG if is_sidecar => Some(decode_mint_range_call(call)?),
+                MINT_AMBIENT_FN_SIG if is_sidecar => Some(decode_mint_ambient_call(call)?),
+                BURN_RANGE_FN_SIG if is_sidecar => Some(decode_burn_range_call(call)?),
+                BURN_AMBIENT_FN_SIG if is_sidecar => Some(decode_burn_ambient_call(call)?),
                 _ => None,
             };
             let (pool_hash, base_flow, quote_flow) = match result {
@@ -101,19 +128,37 @@ fn map_pool_changes(block: eth::v2::Block) -> Result<BlockPoolChanges, substream
                 pool_hash: Vec::from(pool_hash),
                 token_type: "base".to_string(),
                 token_delta: from_u256_to_vec(base_flow),
-                ordinal: call.index as u64,
+                ordinal: call.end_ordinal,
                 tx_index,
             };
             let quote_balance_delta = AmbientBalanceDelta {
                 pool_hash: Vec::from(pool_hash),
                 token_type: "quote".to_string(),
                 token_delta: from_u256_to_vec(quote_flow),
-                ordinal: call.index as u64,
+                ordinal: call.end_ordinal,
                 tx_index,
             };
             balance_deltas.extend([base_balance_delta.clone(), quote_balance_delta.clone()]);
         }
     }
     balance_deltas.sort_by_key(|delta| (delta.ordinal, delta.token_type.clone()));
-    Ok(BlockPoolChanges { balance_deltas, new_components: protocol_components })
+    Ok(BlockPoolChanges {
+        balance_deltas,
+        new_components: protocol_components,
+        knockout_pivots,
+        tick_levels,
+    })
+}
+
+fn tick_level_pair(
+    pool_hash: [u8; 32],
+    lower_tick: i32,
+    upper_tick: i32,
+    tx_index: u64,
+) -> [AmbientTickLevel; 2] {
+    [lower_tick, upper_tick].map(|tick| AmbientTickLevel {
+        pool_hash: pool_hash.to_vec(),
+        tick,
+        tx_index,
+    })
 }
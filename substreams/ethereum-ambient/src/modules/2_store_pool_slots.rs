@@ -0,0 +1,35 @@
+use substreams::store::{StoreNew, StoreSet, StoreSetString};
+
+use crate::{pb::tycho::ambient::v1::BlockPoolChanges, pool_state::PoolSlot};
+
+/// Maps the storage slots of the Ambient dex contract holding pool state to the pool and state
+/// they hold: the `CurveState` of new pools, the pivots of knockout mints and the `BookLevel` of
+/// the ticks bounding range and knockout mints.
+#[substreams::handlers::store]
+pub fn store_pool_slots(changes: BlockPoolChanges, slots_store: StoreSetString) {
+    for component in changes.new_components {
+        let pool_hash: [u8; 32] = hex::decode(&component.id)
+            .expect("pool id should be a hex encoded hash")
+            .try_into()
+            .expect("pool id should be 32 bytes long");
+        for (slot, pool_slot) in PoolSlot::curve(&pool_hash) {
+            slots_store.set(0, format!("slot:{}", hex::encode(slot)), &pool_slot.to_string());
+        }
+    }
+    for pivot in changes.knockout_pivots {
+        let pool_hash: [u8; 32] = pivot
+            .pool_hash
+            .try_into()
+            .expect("pool hash should be 32 bytes long");
+        let (slot, pool_slot) = PoolSlot::knockout_pivot(&pool_hash, pivot.is_bid, pivot.tick);
+        slots_store.set(0, format!("slot:{}", hex::encode(slot)), &pool_slot.to_string());
+    }
+    for level in changes.tick_levels {
+        let pool_hash: [u8; 32] = level
+            .pool_hash
+            .try_into()
+            .expect("pool hash should be 32 bytes long");
+        let (slot, pool_slot) = PoolSlot::level(&pool_hash, level.tick);
+        slots_store.set(0, format!("slot:{}", hex::encode(slot)), &pool_slot.to_string());
+    }
+}
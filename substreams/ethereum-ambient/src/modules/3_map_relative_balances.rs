@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use substreams::store::{StoreGet, StoreGetProto};
+use substreams_ethereum::pb::eth;
+use tycho_substreams::prelude::*;
+
+use crate::pb::tycho::ambient::v1::BlockPoolChanges;
+
+/// Converts the base/quote flows decoded by `map_pool_changes` into `BalanceDelta`s of the pool
+/// tokens, so that they can be aggregated with the tycho-substreams balance helpers.
+///
+/// Flows of pools that are not in the store (e.g. initialised before the start block) are skipped.
+#[substreams::handlers::map]
+fn map_relative_balances(
+    block: eth::v2::Block,
+    block_pool_changes: BlockPoolChanges,
+    pool_store: StoreGetProto<ProtocolComponent>,
+) -> Result<BlockBalanceDeltas, substreams::errors::Error> {
+    let transactions = block
+        .transactions()
+        .map(|tx| (tx.index as u64, Transaction::from(tx)))
+        .collect::<HashMap<_, _>>();
+
+    let balance_deltas = block_pool_changes
+        .balance_deltas
+        .into_iter()
+        .filter_map(|balance_delta| {
+            let pool_hash_hex = hex::encode(&balance_delta.pool_hash);
+            let Some(pool) = pool_store.get_last(&pool_hash_hex) else {
+                substreams::log::info!("Skipping balance delta of unknown pool {pool_hash_hex}");
+                return None;
+            };
+            let token_index = if balance_delta.token_type == "quote" { 1 } else { 0 };
+
+            Some(BalanceDelta {
+                ord: balance_delta.ordinal,
+                tx: transactions
+                    .get(&balance_delta.tx_index)
+                    .cloned(),
+                token: pool.tokens[token_index].clone(),
+                delta: balance_delta.token_delta,
+                component_id: pool_hash_hex.into_bytes(),
+            })
+        })
+        .collect();
+
+    Ok(BlockBalanceDeltas { balance_deltas })
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use serde::Deserialize;
+use substreams::{
+    pb::substreams::StoreDeltas,
+    store::{StoreGet, StoreGetString},
+};
+use substreams_ethereum::pb::eth;
+use tycho_substreams::{
+    balances::aggregate_balances_changes, contract::extract_contract_changes_builder, prelude::*,
+};
+
+use crate::{pb::tycho::ambient::v1::BlockPoolChanges, pool_state::PoolSlot};
+
+#[derive(Deserialize)]
+struct Params {
+    /// The `CrocSwapDex` contract, see `DeploymentConfig`.
+    #[serde(with = "hex::serde")]
+    dex: Vec<u8>,
+    /// Also emit the curve state and the active knockout pivots of pools as entity attributes.
+    #[serde(default)]
+    native_attributes: bool,
+}
+
+/// Aggregates new pools, absolute pool balances and the storage changes of the Ambient dex
+/// contract by transaction.
+#[substreams::handlers::map]
+fn map_changes(
+    params: String,
+    block: eth::v2::Block,
+    block_pool_changes: BlockPoolChanges,
+    deltas: BlockBalanceDeltas,
+    balance_store: StoreDeltas,
+    pool_slots_store: StoreGetString,
+) -> Result<BlockChanges, substreams::errors::Error> {
+    let params: Params = serde_qs::from_str(&params)?;
+
+    // We merge contract changes by transaction (identified by transaction index) making it easy to
+    // sort them at the very end.
+    let mut transaction_changes: HashMap<_, TransactionChangesBuilder> = HashMap::new();
+
+    let transactions = block
+        .transactions()
+        .map(|tx| (tx.index as u64, Transaction::from(tx)))
+        .collect::<HashMap<_, _>>();
+
+    // Aggregate newly created components per tx
+    block_pool_changes
+        .new_components
+        .into_iter()
+        .for_each(|component| {
+            let tx = transactions
+                .get(&component.tx_index)
+                .expect("component created by a transaction of this block");
+            transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(tx))
+                .add_protocol_component(&component.into());
+        });
+
+    // Aggregate absolute balances per transaction.
+    aggregate_balances_changes(balance_store, deltas)
+        .into_iter()
+        .for_each(|(_, (tx, balances))| {
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx));
+            balances
+                .values()
+                .for_each(|token_bc_map| {
+                    token_bc_map
+                        .values()
+                        .for_each(|bc| builder.add_balance_change(bc))
+                });
+        });
+
+    if params.native_attributes {
+        for tx in block.transactions() {
+            let attributes = pool_attributes(tx, &params.dex, &pool_slots_store);
+            if attributes.is_empty() {
+                continue;
+            }
+            let builder = transaction_changes
+                .entry(tx.index.into())
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx.into()));
+            attributes
+                .iter()
+                .for_each(|entity_change| builder.add_entity_change(entity_change));
+        }
+    }
+
+    // All pools live in the Ambient dex contract, which is the only contract we need to index.
+    extract_contract_changes_builder(&block, |addr| addr == params.dex, &mut transaction_changes);
+
+    // Process all `transaction_changes` for final output in the `BlockChanges`,
+    // sorted by transaction index (the key).
+    Ok(BlockChanges {
+        block: Some((&block).into()),
+        changes: transaction_changes
+            .drain()
+            .sorted_unstable_by_key(|(index, _)| *index)
+            .filter_map(|(_, builder)| builder.build())
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Decodes the changes of the pool state slots tracked by `store_pool_slots` made by `tx` into
+/// entity changes, sorted by component.
+fn pool_attributes(
+    tx: &eth::v2::TransactionTrace,
+    dex: &[u8],
+    pool_slots_store: &StoreGetString,
+) -> Vec<EntityChanges> {
+    // The first old value and the last new value of each slot changed in the transaction
+    let mut slot_changes: HashMap<Vec<u8>, (Vec<u8>, Vec<u8>)> = HashMap::new();
+    tx.calls
+        .iter()
+        .filter(|call| !call.state_reverted)
+        .flat_map(|call| call.storage_changes.iter())
+        .filter(|change| change.address == dex)
+        .sorted_by_key(|change| change.ordinal)
+        .for_each(|change| {
+            slot_changes
+                .entry(change.key.clone())
+                .and_modify(|(_, new_value)| *new_value = change.new_value.clone())
+                .or_insert_with(|| (change.old_value.clone(), change.new_value.clone()));
+        });
+
+    let mut attributes: HashMap<String, Vec<Attribute>> = HashMap::new();
+    slot_changes
+        .into_iter()
+        .sorted_unstable_by(|(a, _), (b, _)| a.cmp(b))
+        .for_each(|(slot, (old_value, new_value))| {
+            let Some(pool_slot) = pool_slots_store
+                .get_last(format!("slot:{}", hex::encode(&slot)))
+                .map(|pool_slot| {
+                    pool_slot
+                        .parse::<PoolSlot>()
+                        .expect("invalid pool slot")
+                })
+            else {
+                return;
+            };
+            attributes
+                .entry(pool_slot.component_id().to_string())
+                .or_default()
+                .extend(pool_slot.attributes(&old_value, &new_value));
+        });
+
+    attributes
+        .into_iter()
+        .sorted_unstable_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(component_id, attributes)| EntityChanges { component_id, attributes })
+        .collect()
+}
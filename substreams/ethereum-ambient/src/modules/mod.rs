@@ -1,6 +1,8 @@
 pub use map_changes::map_changes;
 pub use map_pool_changes::map_pool_changes;
+pub use map_relative_balances::map_relative_balances;
 pub use store_pool_balances::store_pool_balances;
+pub use store_pool_slots::store_pool_slots;
 pub use store_pools::store_pools;
 
 #[path = "1_map_pool_changes.rs"]
@@ -9,8 +11,14 @@ mod map_pool_changes;
 #[path = "2_store_pools.rs"]
 mod store_pools;
 
-#[path = "2_store_pool_balances.rs"]
+#[path = "2_store_pool_slots.rs"]
+mod store_pool_slots;
+
+#[path = "3_map_relative_balances.rs"]
+mod map_relative_balances;
+
+#[path = "4_store_pool_balances.rs"]
 mod store_pool_balances;
 
-#[path = "3_map_changes.rs"]
+#[path = "5_map_changes.rs"]
 mod map_changes;
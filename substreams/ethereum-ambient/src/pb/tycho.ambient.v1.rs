@@ -34,6 +34,40 @@ pub struct AmbientProtocolComponent {
     /// Transaction index for the component creation
     #[prost(uint64, tag="4")]
     pub tx_index: u64,
+    /// Address of the dex contract holding the pool state
+    #[prost(bytes="vec", tag="5")]
+    pub dex: ::prost::alloc::vec::Vec<u8>,
+}
+/// A knockout pivot liquidity was minted to. Ambient specific.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AmbientKnockoutPivot {
+    /// The hash of the pool the pivot belongs to.
+    #[prost(bytes="vec", tag="1")]
+    pub pool_hash: ::prost::alloc::vec::Vec<u8>,
+    /// Whether the pivot is on the bid side of the pool.
+    #[prost(bool, tag="2")]
+    pub is_bid: bool,
+    /// The tick at which the pivot is knocked out.
+    #[prost(int32, tag="3")]
+    pub tick: i32,
+    /// Transaction index of the mint
+    #[prost(uint64, tag="4")]
+    pub tx_index: u64,
+}
+/// A tick concentrated liquidity was added at, as the lower or upper bound of a range.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AmbientTickLevel {
+    /// The hash of the pool the tick belongs to.
+    #[prost(bytes="vec", tag="1")]
+    pub pool_hash: ::prost::alloc::vec::Vec<u8>,
+    /// The tick holding the liquidity level.
+    #[prost(int32, tag="2")]
+    pub tick: i32,
+    /// Transaction index of the mint
+    #[prost(uint64, tag="3")]
+    pub tx_index: u64,
 }
 /// Ambient pool changes within a single block
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -45,5 +79,11 @@ pub struct BlockPoolChanges {
     /// Balance changes on this block
     #[prost(message, repeated, tag="2")]
     pub balance_deltas: ::prost::alloc::vec::Vec<AmbientBalanceDelta>,
+    /// Knockout pivots minted on this block
+    #[prost(message, repeated, tag="3")]
+    pub knockout_pivots: ::prost::alloc::vec::Vec<AmbientKnockoutPivot>,
+    /// Ticks receiving range liquidity on this block
+    #[prost(message, repeated, tag="4")]
+    pub tick_levels: ::prost::alloc::vec::Vec<AmbientTickLevel>,
 }
 // @@protoc_insertion_point(module)
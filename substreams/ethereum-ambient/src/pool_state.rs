@@ -0,0 +1,361 @@
+use anyhow::{anyhow, bail};
+use ethabi::{ethereum_types::U256, Token};
+use substreams::scalar::BigInt;
+use tiny_keccak::{Hasher, Keccak};
+use tycho_substreams::prelude::*;
+
+// Storage slots of the mappings declared in `CrocSlots.sol`
+const LVL_MAP_SLOT: u64 = 65538;
+const KO_PIVOT_SLOT: u64 = 65546;
+const CURVE_MAP_SLOT: u64 = 65551;
+
+/// A storage slot of the Ambient dex contract holding pool state we decode into attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PoolSlot {
+    /// One of the two words of the pool's `CurveState`.
+    Curve { pool_hash: String, word: u8 },
+    /// The `KnockoutPivot` of a knockout tick.
+    KnockoutPivot { pool_hash: String, is_bid: bool, tick: i32 },
+    /// The `BookLevel` of a tick, holding the concentrated liquidity starting or ending at it.
+    Level { pool_hash: String, tick: i32 },
+}
+
+impl PoolSlot {
+    /// Both words of the `CurveState` of a pool, as `(slot, PoolSlot)`.
+    pub fn curve(pool_hash: &[u8; 32]) -> [([u8; 32], PoolSlot); 2] {
+        let base = U256::from_big_endian(&mapping_slot(pool_hash, CURVE_MAP_SLOT));
+        [0u8, 1u8].map(|word| {
+            let mut slot = [0u8; 32];
+            (base + U256::from(word)).to_big_endian(&mut slot);
+            (slot, PoolSlot::Curve { pool_hash: hex::encode(pool_hash), word })
+        })
+    }
+
+    /// The `KnockoutPivot` of a knockout tick, as `(slot, PoolSlot)`. Pivots are keyed by
+    /// `keccak256(abi.encode(poolHash, isBid, tick))`.
+    pub fn knockout_pivot(pool_hash: &[u8; 32], is_bid: bool, tick: i32) -> ([u8; 32], PoolSlot) {
+        let pivot_key = keccak256(&ethabi::encode(&[
+            Token::FixedBytes(pool_hash.to_vec()),
+            Token::Bool(is_bid),
+            Token::Int(U256::from(tick as i64 as u64) | sign_extension(tick)),
+        ]));
+        (
+            mapping_slot(&pivot_key, KO_PIVOT_SLOT),
+            PoolSlot::KnockoutPivot { pool_hash: hex::encode(pool_hash), is_bid, tick },
+        )
+    }
+
+    /// The `BookLevel` of a tick, as `(slot, PoolSlot)`. Levels are keyed by
+    /// `keccak256(abi.encodePacked(poolHash, tick))`, the tick being an `int24`.
+    pub fn level(pool_hash: &[u8; 32], tick: i32) -> ([u8; 32], PoolSlot) {
+        let level_key = keccak256(&[pool_hash.as_slice(), &tick.to_be_bytes()[1..]].concat());
+        (
+            mapping_slot(&level_key, LVL_MAP_SLOT),
+            PoolSlot::Level { pool_hash: hex::encode(pool_hash), tick },
+        )
+    }
+
+    /// The id of the component the slot belongs to.
+    pub fn component_id(&self) -> &str {
+        match self {
+            PoolSlot::Curve { pool_hash, .. } => pool_hash,
+            PoolSlot::KnockoutPivot { pool_hash, .. } => pool_hash,
+            PoolSlot::Level { pool_hash, .. } => pool_hash,
+        }
+    }
+
+    /// Decodes a change of the slot into entity attributes:
+    ///  - `price_root` and `ambient_seeds` from the first `CurveState` word
+    ///  - `conc_liq`, `seed_deflator` and `conc_growth` from the second `CurveState` word
+    ///  - `knockout_lots:{bid|ask}:{tick}` and `knockout_range:{bid|ask}:{tick}` from a
+    ///    `KnockoutPivot`, which are deleted once the pivot is knocked out or fully burnt
+    ///  - `level_lots:{bid|ask}:{tick}` from a `BookLevel`, the lots of liquidity added when the
+    ///    price crosses the tick from below (`bid`) or removed (`ask`), deleted once zero. Changes
+    ///    of the fee odometer alone, which happen when the price crosses the tick, are skipped
+    pub fn attributes(&self, old_value: &[u8], new_value: &[u8]) -> Vec<Attribute> {
+        let new_value = word(new_value);
+        let uint = |range: std::ops::Range<usize>| {
+            BigInt::from_unsigned_bytes_be(&new_value[range]).to_signed_bytes_be()
+        };
+        let update = |name: &str, value: Vec<u8>| Attribute {
+            name: name.to_string(),
+            value,
+            change: ChangeType::Update.into(),
+        };
+
+        match self {
+            PoolSlot::Curve { word: 0, .. } => {
+                vec![update("price_root", uint(16..32)), update("ambient_seeds", uint(0..16))]
+            }
+            PoolSlot::Curve { .. } => vec![
+                update("conc_liq", uint(16..32)),
+                update("seed_deflator", uint(8..16)),
+                update("conc_growth", uint(0..8)),
+            ],
+            PoolSlot::KnockoutPivot { is_bid, tick, .. } => {
+                // struct KnockoutPivot { uint96 lots_; uint32 pivotTime_; uint16 rangeTicks_; }
+                let side = if *is_bid { "bid" } else { "ask" };
+                let old_lots = BigInt::from_unsigned_bytes_be(&word(old_value)[20..32]);
+                let new_lots = BigInt::from_unsigned_bytes_be(&new_value[20..32]);
+                let change = lots_change(&old_lots, &new_lots);
+                let value = |range| if new_lots.is_zero() { vec![] } else { uint(range) };
+                vec![
+                    Attribute {
+                        name: format!("knockout_lots:{side}:{tick}"),
+                        value: value(20..32),
+                        change: change.into(),
+                    },
+                    Attribute {
+                        name: format!("knockout_range:{side}:{tick}"),
+                        value: value(14..16),
+                        change: change.into(),
+                    },
+                ]
+            }
+            PoolSlot::Level { tick, .. } => {
+                // struct BookLevel { uint96 bidLots_; uint96 askLots_; uint64 feeOdometer_; }
+                let old_value = word(old_value);
+                [("bid", 20..32), ("ask", 8..20)]
+                    .into_iter()
+                    .filter_map(|(side, range)| {
+                        let old_lots = BigInt::from_unsigned_bytes_be(&old_value[range.clone()]);
+                        let new_lots = BigInt::from_unsigned_bytes_be(&new_value[range.clone()]);
+                        if old_lots == new_lots {
+                            return None;
+                        }
+                        Some(Attribute {
+                            name: format!("level_lots:{side}:{tick}"),
+                            value: if new_lots.is_zero() { vec![] } else { uint(range) },
+                            change: lots_change(&old_lots, &new_lots).into(),
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Lots are created when they become non zero and deleted when they drop back to zero.
+fn lots_change(old_lots: &BigInt, new_lots: &BigInt) -> ChangeType {
+    if new_lots.is_zero() {
+        ChangeType::Deletion
+    } else if old_lots.is_zero() {
+        ChangeType::Creation
+    } else {
+        ChangeType::Update
+    }
+}
+
+impl std::fmt::Display for PoolSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolSlot::Curve { pool_hash, word } => write!(f, "{pool_hash}:curve:{word}"),
+            PoolSlot::KnockoutPivot { pool_hash, is_bid, tick } => {
+                write!(f, "{pool_hash}:knockout:{}:{tick}", if *is_bid { "bid" } else { "ask" })
+            }
+            PoolSlot::Level { pool_hash, tick } => write!(f, "{pool_hash}:level:{tick}"),
+        }
+    }
+}
+
+impl std::str::FromStr for PoolSlot {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s
+            .split(':')
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [pool_hash, "curve", word] => {
+                Ok(PoolSlot::Curve { pool_hash: pool_hash.to_string(), word: word.parse()? })
+            }
+            [pool_hash, "knockout", side, tick] => Ok(PoolSlot::KnockoutPivot {
+                pool_hash: pool_hash.to_string(),
+                is_bid: match *side {
+                    "bid" => true,
+                    "ask" => false,
+                    _ => bail!("Invalid knockout side: {side}"),
+                },
+                tick: tick.parse()?,
+            }),
+            [pool_hash, "level", tick] => {
+                Ok(PoolSlot::Level { pool_hash: pool_hash.to_string(), tick: tick.parse()? })
+            }
+            _ => Err(anyhow!("Invalid pool slot: {s}")),
+        }
+    }
+}
+
+/// Storage slot of `key` in a `mapping(bytes32 => ...)` declared at `slot`.
+fn mapping_slot(key: &[u8; 32], slot: u64) -> [u8; 32] {
+    let mut preimage = [0u8; 64];
+    preimage[..32].copy_from_slice(key);
+    U256::from(slot).to_big_endian(&mut preimage[32..]);
+    keccak256(&preimage)
+}
+
+/// The high bits of the two's complement 256 bit representation of `value`.
+fn sign_extension(value: i32) -> U256 {
+    if value < 0 {
+        U256::MAX << 64
+    } else {
+        U256::zero()
+    }
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(data);
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    output
+}
+
+/// Left pads a storage value to a full word.
+fn word(value: &[u8]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    let len = value.len().min(32);
+    word[32 - len..].copy_from_slice(&value[value.len() - len..]);
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::utils::encode_pool_hash;
+
+    /// The ETH/USDC pool with pool index 420.
+    fn eth_usdc_pool() -> [u8; 32] {
+        let mut pool_index = [0u8; 32];
+        U256::from(420).to_big_endian(&mut pool_index);
+        encode_pool_hash(
+            vec![0; 20],
+            hex::decode("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap(),
+            pool_index.to_vec(),
+        )
+    }
+
+    fn slot(hex: &str) -> [u8; 32] {
+        hex::decode(hex)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    /// Packs `BookLevel { bidLots_, askLots_, feeOdometer_ }` into a storage word.
+    fn book_level(bid_lots: u64, ask_lots: u64, fee_odometer: u64) -> Vec<u8> {
+        let mut value = [0u8; 32];
+        value[..8].copy_from_slice(&fee_odometer.to_be_bytes());
+        value[12..20].copy_from_slice(&ask_lots.to_be_bytes());
+        value[24..].copy_from_slice(&bid_lots.to_be_bytes());
+        value.to_vec()
+    }
+
+    #[test]
+    fn test_curve_slots() {
+        let pool_hash = eth_usdc_pool();
+        assert_eq!(
+            hex::encode(pool_hash),
+            "d417ff54652c09bd9f31f216b1a2e5d1e28c1dce1ba840c40d16f2b4d09b5902"
+        );
+
+        let [(price_slot, _), (liquidity_slot, liquidity)] = PoolSlot::curve(&pool_hash);
+        assert_eq!(
+            price_slot,
+            slot("e609744b3a66dcd1237053ac626a385bee307e7f046b695afd0e05c0ff1cb637")
+        );
+        assert_eq!(
+            liquidity_slot,
+            slot("e609744b3a66dcd1237053ac626a385bee307e7f046b695afd0e05c0ff1cb638")
+        );
+        assert_eq!(liquidity, PoolSlot::Curve { pool_hash: hex::encode(pool_hash), word: 1 });
+    }
+
+    #[test]
+    fn test_knockout_pivot_slots() {
+        let pool_hash = eth_usdc_pool();
+
+        let (bid_slot, _) = PoolSlot::knockout_pivot(&pool_hash, true, -201300);
+        assert_eq!(
+            bid_slot,
+            slot("4e7c2e441c510154efe031d695d19895a650264646bde1a16a51d4717727c467")
+        );
+        let (ask_slot, _) = PoolSlot::knockout_pivot(&pool_hash, false, 201300);
+        assert_eq!(
+            ask_slot,
+            slot("6070a671a7c266dccbcaae2c458107c89890454cebfbbd591b1a369e6b5809b3")
+        );
+    }
+
+    #[test]
+    fn test_level_slot() {
+        let pool_hash = eth_usdc_pool();
+
+        let (level_slot, level) = PoolSlot::level(&pool_hash, -201300);
+        assert_eq!(
+            level_slot,
+            slot("7d30e115af206abc12af520dc97cb082f52e0421008c3cff05abf58d4e07ee08")
+        );
+        assert_eq!(
+            level
+                .to_string()
+                .parse::<PoolSlot>()
+                .unwrap(),
+            level
+        );
+    }
+
+    #[test]
+    fn test_level_attributes() {
+        let level = PoolSlot::Level { pool_hash: "aa".into(), tick: -5 };
+
+        let attributes = level.attributes(&[], &book_level(3, 0, 7));
+        assert_eq!(
+            attributes,
+            vec![Attribute {
+                name: "level_lots:bid:-5".into(),
+                value: vec![3],
+                change: ChangeType::Creation.into(),
+            }]
+        );
+
+        // Crossing the tick only moves the fee odometer
+        assert!(level
+            .attributes(&book_level(3, 2, 7), &book_level(3, 2, 9))
+            .is_empty());
+
+        let attributes = level.attributes(&book_level(3, 2, 7), &book_level(4, 0, 7));
+        assert_eq!(
+            attributes,
+            vec![
+                Attribute {
+                    name: "level_lots:bid:-5".into(),
+                    value: vec![4],
+                    change: ChangeType::Update.into(),
+                },
+                Attribute {
+                    name: "level_lots:ask:-5".into(),
+                    value: vec![],
+                    change: ChangeType::Deletion.into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_curve_attributes() {
+        // struct CurveState { uint128 priceRoot_; uint128 ambientSeeds_; ... }
+        let mut value = [0u8; 32];
+        value[15] = 2;
+        value[31] = 1;
+        let curve = PoolSlot::Curve { pool_hash: "aa".into(), word: 0 };
+
+        let attributes = curve.attributes(&[], &value);
+        assert_eq!(attributes[0].name, "price_root");
+        assert_eq!(attributes[0].value, vec![1]);
+        assert_eq!(attributes[1].name, "ambient_seeds");
+        assert_eq!(attributes[1].value, vec![2]);
+    }
+}
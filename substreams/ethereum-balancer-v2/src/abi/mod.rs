@@ -1,9 +1,12 @@
 #![allow(clippy::all)]
+pub mod balancer_pool;
 pub mod composable_stable_pool_factory;
+pub mod erc20;
 pub mod erc_linear_pool_factory;
 pub mod euler_linear_pool_factory;
 pub mod gearbox_linear_pool_factory;
 pub mod managed_pool_factory;
+pub mod meta_stable_pool;
 pub mod silo_linear_pool_factory;
 pub mod vault;
 pub mod weighted_pool_factory_v1;
@@ -1,10 +1,12 @@
 use crate::{abi, pool_factories};
 use anyhow::Result;
 use itertools::Itertools;
-use std::collections::HashMap;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use substreams::{
     hex,
     pb::substreams::StoreDeltas,
+    scalar::BigInt,
     store::{StoreAddBigInt, StoreGet, StoreGetString, StoreNew, StoreSet, StoreSetString},
 };
 use substreams_ethereum::{pb::eth, Event};
@@ -17,8 +19,22 @@ use tycho_substreams::{
 pub const VAULT_ADDRESS: &[u8] = &hex!("BA12222222228d8Ba445958a75a0704d566BF2C8");
 pub const ZERO_ADDRESS: &[u8] = &[0u8; 20];
 
+#[derive(Deserialize)]
+struct Params {
+    /// Index the BPT of pools that register it as one of their tokens (composable stable and
+    /// linear pools), including its balance and the pool's virtual supply. Downstream modules
+    /// follow the components created with it, so it's only set here.
+    #[serde(default)]
+    track_bpt: bool,
+}
+
 #[substreams::handlers::map]
-pub fn map_components(block: eth::v2::Block) -> Result<BlockTransactionProtocolComponents> {
+pub fn map_components(
+    params: String,
+    block: eth::v2::Block,
+) -> Result<BlockTransactionProtocolComponents> {
+    let params: Params = serde_qs::from_str(&params)?;
+
     // Gather contract changes by indexing `PoolCreated` events and analysing the `Create` call
     // We store these as a hashmap by tx hash since we need to agg by tx hash later
     Ok(BlockTransactionProtocolComponents {
@@ -33,6 +49,7 @@ pub fn map_components(block: eth::v2::Block) -> Result<BlockTransactionProtocolC
                             log,
                             call.call,
                             tx,
+                            params.track_bpt,
                         )
                     })
                     .collect::<Vec<_>>();
@@ -47,7 +64,9 @@ pub fn map_components(block: eth::v2::Block) -> Result<BlockTransactionProtocolC
     })
 }
 
-/// Simply stores the `ProtocolComponent`s with the pool address as the key and the pool id as value
+/// Simply stores the `ProtocolComponent`s with the pool address as the key and the pool id as
+/// value.  Pools whose BPT is tracked are also stored under a `bpt:` key, which is how the
+/// downstream  modules know about `track_bpt`.
 #[substreams::handlers::store]
 pub fn store_components(map: BlockTransactionProtocolComponents, store: StoreSetString) {
     map.tx_components
@@ -56,7 +75,13 @@ pub fn store_components(map: BlockTransactionProtocolComponents, store: StoreSet
             tx_pc
                 .components
                 .into_iter()
-                .for_each(|pc| store.set(0, format!("pool:{0}", &pc.id[..42]), &pc.id))
+                .for_each(|pc| {
+                    store.set(0, format!("pool:{0}", &pc.id[..42]), &pc.id);
+
+                    if tracks_bpt(&pc) {
+                        store.set(0, format!("bpt:{0}", &pc.id[..42]), &pc.id);
+                    }
+                })
         });
 }
 
@@ -67,6 +92,13 @@ pub fn map_relative_balances(
     block: eth::v2::Block,
     store: StoreGetString,
 ) -> Result<BlockBalanceDeltas, anyhow::Error> {
+    // BPT balances are only tracked for pools that include their BPT in their tokens
+    let tracks_bpt = |component_id: &str| {
+        store
+            .get_last(format!("bpt:{}", &component_id[..42]))
+            .is_some()
+    };
+
     let balance_deltas = block
         .logs()
         .filter(|log| log.address() == VAULT_ADDRESS)
@@ -83,9 +115,9 @@ pub fn map_relative_balances(
                     .get_last(format!("pool:{}", &component_id[..42]))
                     .is_some()
                 {
+                    let track_bpt = tracks_bpt(&component_id);
                     for (token, delta) in ev.tokens.iter().zip(ev.deltas.iter()) {
-                        // BPT tokens not supported - their balance handling is currently bugged
-                        if *token != bpt_token {
+                        if track_bpt || *token != bpt_token {
                             deltas.push(BalanceDelta {
                                 ord: vault_log.ordinal(),
                                 tx: Some(vault_log.receipt.transaction.into()),
@@ -104,8 +136,8 @@ pub fn map_relative_balances(
                     .get_last(format!("pool:{}", &component_id[..42]))
                     .is_some()
                 {
-                    // BPT tokens not supported - their balance handling is currently bugged
-                    if ev.token_in != bpt_token {
+                    let track_bpt = tracks_bpt(&component_id);
+                    if track_bpt || ev.token_in != bpt_token {
                         deltas.push(BalanceDelta {
                             ord: vault_log.ordinal(),
                             tx: Some(vault_log.receipt.transaction.into()),
@@ -114,8 +146,7 @@ pub fn map_relative_balances(
                             component_id: component_id.as_bytes().to_vec(),
                         });
                     }
-                    // BPT tokens not supported - their balance handling is currently bugged
-                    if ev.token_out != bpt_token {
+                    if track_bpt || ev.token_out != bpt_token {
                         deltas.push(BalanceDelta {
                             ord: vault_log.ordinal(),
                             tx: Some(vault_log.receipt.transaction.into()),
@@ -132,9 +163,8 @@ pub fn map_relative_balances(
                 let bpt_token = hex::decode(&component_id[2..42]).unwrap();
                 if store
                     .get_last(format!("pool:{}", &component_id[..42]))
-                    .is_some()
-                    // BPT tokens not supported - their balance handling is currently bugged
-                    && ev.token != bpt_token
+                    .is_some() &&
+                    (ev.token != bpt_token || tracks_bpt(&component_id))
                 {
                     deltas.push(BalanceDelta {
                         ord: vault_log.ordinal(),
@@ -160,6 +190,102 @@ pub fn store_balances(deltas: BlockBalanceDeltas, store: StoreAddBigInt) {
     tycho_substreams::balances::store_balance_changes(deltas, store);
 }
 
+/// The virtual supply of a pool with a phantom BPT is its total supply minus the BPT held by the
+///  Vault on behalf of the pool. It's tallied from BPT mints and burns as well as from the pool's
+///  BPT balance deltas in the Vault, for pools whose BPT is tracked.
+#[substreams::handlers::map]
+pub fn map_virtual_supply_deltas(
+    block: eth::v2::Block,
+    store: StoreGetString,
+) -> Result<BlockBalanceDeltas, anyhow::Error> {
+    let balance_deltas = block
+        .logs()
+        .flat_map(|log| {
+            let mut deltas = Vec::new();
+
+            let mut push_delta = |component_id: String, bpt_token: Vec<u8>, delta: BigInt| {
+                deltas.push(BalanceDelta {
+                    ord: log.ordinal(),
+                    tx: Some(log.receipt.transaction.into()),
+                    token: bpt_token,
+                    delta: delta.to_signed_bytes_be(),
+                    component_id: component_id.into_bytes(),
+                })
+            };
+
+            if log.address() == VAULT_ADDRESS {
+                if let Some(ev) = abi::vault::events::PoolBalanceChanged::match_and_decode(log.log)
+                {
+                    let component_id = format!("0x{}", hex::encode(ev.pool_id));
+                    let bpt_token = hex::decode(&component_id[2..42]).unwrap();
+
+                    if store
+                        .get_last(format!("bpt:{}", &component_id[..42]))
+                        .is_some()
+                    {
+                        for (token, delta) in ev.tokens.iter().zip(ev.deltas.iter()) {
+                            if *token == bpt_token {
+                                push_delta(component_id.clone(), bpt_token.clone(), delta.neg());
+                            }
+                        }
+                    }
+                } else if let Some(ev) = abi::vault::events::Swap::match_and_decode(log.log) {
+                    let component_id = format!("0x{}", hex::encode(ev.pool_id));
+                    let bpt_token = hex::decode(&component_id[2..42]).unwrap();
+
+                    if store
+                        .get_last(format!("bpt:{}", &component_id[..42]))
+                        .is_some()
+                    {
+                        // Joins and exits routed as swaps against the BPT
+                        if ev.token_in == bpt_token {
+                            push_delta(component_id, bpt_token, ev.amount_in.neg());
+                        } else if ev.token_out == bpt_token {
+                            push_delta(component_id, bpt_token, ev.amount_out);
+                        }
+                    }
+                }
+            } else if let Some(delta) = abi::erc20::events::Transfer::match_and_decode(log.log)
+                .and_then(|ev| supply_delta(&ev))
+            {
+                // Only mints and burns are looked up, plain transfers don't change any supply
+                if let Some(component_id) =
+                    store.get_last(format!("bpt:0x{}", hex::encode(log.address())))
+                {
+                    push_delta(component_id, log.address().to_vec(), delta);
+                }
+            }
+
+            deltas
+        })
+        .collect::<Vec<_>>();
+
+    Ok(BlockBalanceDeltas { balance_deltas })
+}
+
+/// The change of the total supply of a token by a transfer: positive for mints and negative for
+///  burns.
+fn supply_delta(ev: &abi::erc20::events::Transfer) -> Option<BigInt> {
+    if ev.from == ZERO_ADDRESS && ev.to != ZERO_ADDRESS {
+        Some(ev.value.clone())
+    } else if ev.to == ZERO_ADDRESS && ev.from != ZERO_ADDRESS {
+        Some(ev.value.neg())
+    } else {
+        None
+    }
+}
+
+/// Whether the component includes its own BPT in its tokens, i.e. was created with `track_bpt`.
+fn tracks_bpt(component: &ProtocolComponent) -> bool {
+    let bpt = hex::decode(&component.id[2..42]).unwrap();
+    component.tokens.contains(&bpt)
+}
+
+#[substreams::handlers::store]
+pub fn store_virtual_supplies(deltas: BlockBalanceDeltas, store: StoreAddBigInt) {
+    tycho_substreams::balances::store_balance_changes(deltas, store);
+}
+
 /// This is the main map that handles most of the indexing of this substream.
 /// Every contract change is grouped by transaction index via the `transaction_changes`
 ///  map. Each block of code will extend the `TransactionChanges` struct with the
@@ -173,6 +299,8 @@ pub fn map_protocol_changes(
     deltas: BlockBalanceDeltas,
     components_store: StoreGetString,
     balance_store: StoreDeltas, // Note, this map module is using the `deltas` mode for the store.
+    virtual_supply_deltas: BlockBalanceDeltas,
+    virtual_supply_store: StoreDeltas,
 ) -> Result<BlockChanges> {
     // We merge contract changes by transaction (identified by transaction index) making it easy to
     //  sort them at the very end.
@@ -191,6 +319,17 @@ pub fn map_protocol_changes(
             value: vec![1u8],
             change: ChangeType::Creation.into(),
         },
+        // Pools are deployed unpaused and out of recovery mode
+        Attribute {
+            name: "paused".to_string(),
+            value: vec![0u8],
+            change: ChangeType::Creation.into(),
+        },
+        Attribute {
+            name: "recovery_mode".to_string(),
+            value: vec![0u8],
+            change: ChangeType::Creation.into(),
+        },
     ];
     grouped_components
         .tx_components
@@ -236,10 +375,17 @@ pub fn map_protocol_changes(
                     }
 
                     builder.add_protocol_component(component);
-                    let entity_change = EntityChanges {
-                        component_id: component.id.clone(),
-                        attributes: default_attributes.clone(),
-                    };
+                    let mut attributes = default_attributes.clone();
+                    // The virtual supply starts at zero and is updated by its deltas
+                    if tracks_bpt(component) {
+                        attributes.push(Attribute {
+                            name: "virtual_supply".to_string(),
+                            value: BigInt::zero().to_signed_bytes_be(),
+                            change: ChangeType::Creation.into(),
+                        });
+                    }
+                    let entity_change =
+                        EntityChanges { component_id: component.id.clone(), attributes };
                     builder.add_entity_change(&entity_change)
                 });
         });
@@ -263,6 +409,73 @@ pub fn map_protocol_changes(
                 });
         });
 
+    // Attribute changes in the tx creating the pool overwrite its `Creation`, so they are kept as
+    //  `Creation` themselves.
+    let created_components = grouped_components
+        .tx_components
+        .iter()
+        .flat_map(|tx_component| {
+            let tx_index = tx_component.tx.as_ref().unwrap().index;
+            tx_component
+                .components
+                .iter()
+                .map(move |component| (tx_index, component.id.clone()))
+        })
+        .collect::<HashSet<_>>();
+
+    // Virtual supplies are tallied like balances, but exposed as a component attribute since
+    //  they aren't held by anyone.
+    aggregate_balances_changes(virtual_supply_store, virtual_supply_deltas)
+        .into_iter()
+        .for_each(|(_, (tx, balances))| {
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx));
+            balances
+                .into_values()
+                .flat_map(|token_bc_map| token_bc_map.into_values())
+                .for_each(|bc| {
+                    let component_id = String::from_utf8(bc.component_id).unwrap();
+                    let change = if created_components.contains(&(tx.index, component_id.clone())) {
+                        ChangeType::Creation
+                    } else {
+                        ChangeType::Update
+                    };
+                    builder.add_entity_change(&EntityChanges {
+                        component_id,
+                        attributes: vec![Attribute {
+                            name: "virtual_supply".to_string(),
+                            value: bc.balance,
+                            change: change.into(),
+                        }],
+                    })
+                });
+        });
+
+    // Pool parameters that change over a pool's life are emitted by the pools themselves, including
+    //  the initial values set in their constructors.
+    block.logs().for_each(|log| {
+        let Some(component_id) =
+            components_store.get_last(format!("pool:0x{}", hex::encode(log.address())))
+        else {
+            return;
+        };
+
+        if let Some(mut attributes) = pool_parameter_updates(log.log, block.timestamp_seconds()) {
+            let tx: Transaction = log.receipt.transaction.into();
+            if created_components.contains(&(tx.index, component_id.clone())) {
+                attributes
+                    .iter_mut()
+                    .for_each(|attribute| attribute.change = ChangeType::Creation.into());
+            }
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx));
+
+            builder.add_entity_change(&EntityChanges { component_id, attributes });
+        }
+    });
+
     // Extract and insert any storage changes that happened for any of the components.
     extract_contract_changes_builder(
         &block,
@@ -311,3 +524,168 @@ pub fn map_protocol_changes(
         storage_changes: block_storage_changes,
     })
 }
+
+/// Decodes the pool events changing a pool parameter into the corresponding entity attributes.
+fn pool_parameter_updates(log: &eth::v2::Log, timestamp: u64) -> Option<Vec<Attribute>> {
+    use abi::{
+        balancer_pool::events::{
+            AmpUpdateStarted, AmpUpdateStopped, PausedStateChanged, RecoveryModeStateChanged,
+            SwapFeePercentageChanged, TargetsSet, TokenRateCacheUpdated,
+        },
+        meta_stable_pool::events::TokenRateCacheUpdated as TokenRateCacheUpdatedByToken,
+    };
+
+    let update = |name: &str, value: Vec<u8>| Attribute {
+        name: name.to_string(),
+        value,
+        change: ChangeType::Update.into(),
+    };
+    // Flags are created unset along with the pool
+    let flag = |name: &str, set: bool| update(name, vec![set as u8]);
+
+    if let Some(ev) = SwapFeePercentageChanged::match_and_decode(log) {
+        Some(vec![update(
+            "swap_fee_percentage",
+            ev.swap_fee_percentage
+                .to_signed_bytes_be(),
+        )])
+    } else if let Some(ev) = AmpUpdateStarted::match_and_decode(log) {
+        Some(vec![
+            update("amp_start_value", ev.start_value.to_signed_bytes_be()),
+            update("amp_end_value", ev.end_value.to_signed_bytes_be()),
+            update("amp_start_time", ev.start_time.to_signed_bytes_be()),
+            update("amp_end_time", ev.end_time.to_signed_bytes_be()),
+        ])
+    } else if let Some(ev) = AmpUpdateStopped::match_and_decode(log) {
+        // Stopping a ramp fixes the amplification at its current value
+        let now = BigInt::from(timestamp).to_signed_bytes_be();
+        Some(vec![
+            update("amp_start_value", ev.current_value.to_signed_bytes_be()),
+            update("amp_end_value", ev.current_value.to_signed_bytes_be()),
+            update("amp_start_time", now.clone()),
+            update("amp_end_time", now),
+        ])
+    } else if let Some(ev) = TokenRateCacheUpdated::match_and_decode(log) {
+        Some(vec![update(&format!("token_rates/{}", ev.token_index), ev.rate.to_signed_bytes_be())])
+    } else if let Some(ev) = TokenRateCacheUpdatedByToken::match_and_decode(log) {
+        // Meta stable pools key their rate cache by token rather than by index
+        Some(vec![update(
+            &format!("token_rates/0x{}", hex::encode(ev.token)),
+            ev.rate.to_signed_bytes_be(),
+        )])
+    } else if let Some(ev) = TargetsSet::match_and_decode(log) {
+        // Linear pools set their initial targets in their constructor
+        Some(vec![
+            update("targets/lower", ev.lower_target.to_signed_bytes_be()),
+            update("targets/upper", ev.upper_target.to_signed_bytes_be()),
+        ])
+    } else if let Some(ev) = PausedStateChanged::match_and_decode(log) {
+        Some(vec![flag("paused", ev.paused)])
+    } else {
+        RecoveryModeStateChanged::match_and_decode(log)
+            .map(|ev| vec![flag("recovery_mode", ev.enabled)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BPT: [u8; 20] = [0xbb; 20];
+    const USER: [u8; 20] = [0x11; 20];
+
+    fn transfer(from: &[u8], to: &[u8], value: i64) -> abi::erc20::events::Transfer {
+        abi::erc20::events::Transfer { from: from.to_vec(), to: to.to_vec(), value: value.into() }
+    }
+
+    #[test]
+    fn test_supply_delta() {
+        assert_eq!(supply_delta(&transfer(ZERO_ADDRESS, &USER, 100)), Some(BigInt::from(100)));
+        assert_eq!(supply_delta(&transfer(&USER, ZERO_ADDRESS, 100)), Some(BigInt::from(-100)));
+        assert_eq!(supply_delta(&transfer(&USER, VAULT_ADDRESS, 100)), None);
+        assert_eq!(supply_delta(&transfer(ZERO_ADDRESS, ZERO_ADDRESS, 100)), None);
+    }
+
+    fn word(value: u64) -> Vec<u8> {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        word.to_vec()
+    }
+
+    fn log(topics: &[Vec<u8>], data: Vec<u8>) -> eth::v2::Log {
+        eth::v2::Log { topics: topics.to_vec(), data, ..Default::default() }
+    }
+
+    #[test]
+    fn test_flag_updates() {
+        let paused_state_changed =
+            hex!("9e3a5e37224532dea67b89face185703738a228a6e8a23dee546960180d3be64").to_vec();
+        let recovery_mode_state_changed =
+            hex!("eff3d4d215b42bf0960be9c6d5e05c22cba4df6627a3a523e2acee733b5854c8").to_vec();
+
+        let attributes = pool_parameter_updates(&log(&[paused_state_changed], word(1)), 0);
+        assert_eq!(
+            attributes,
+            Some(vec![Attribute {
+                name: "paused".to_string(),
+                value: vec![1],
+                change: ChangeType::Update.into(),
+            }])
+        );
+
+        // Unsetting a flag updates it rather than deleting it, as it may never have been set
+        let attributes = pool_parameter_updates(&log(&[recovery_mode_state_changed], word(0)), 0);
+        assert_eq!(
+            attributes,
+            Some(vec![Attribute {
+                name: "recovery_mode".to_string(),
+                value: vec![0],
+                change: ChangeType::Update.into(),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_token_rate_updates() {
+        let by_index =
+            hex!("b77a83204ca282e08dc3a65b0a1ca32ea4e6875c38ef0bf5bf75e52a67354fac").to_vec();
+        let by_token =
+            hex!("cfed57d90913b5ee1895919dcccaff83a9f7a842d912ef137272e5fc6123aa86").to_vec();
+
+        let attributes = pool_parameter_updates(&log(&[by_index, word(2)], word(1000)), 0).unwrap();
+        assert_eq!(attributes[0].name, "token_rates/2");
+        assert_eq!(attributes[0].value, BigInt::from(1000).to_signed_bytes_be());
+
+        let token = [[0u8; 12].as_slice(), &USER].concat();
+        let attributes = pool_parameter_updates(&log(&[by_token, token], word(1000)), 0).unwrap();
+        assert_eq!(attributes[0].name, format!("token_rates/0x{}", hex::encode(USER)));
+        assert_eq!(attributes[0].value, BigInt::from(1000).to_signed_bytes_be());
+    }
+
+    #[test]
+    fn test_targets_updates() {
+        let targets_set =
+            hex!("d0e27a0d0c2cb09280fa5e4487315455b32afcdcf012dc35b6ef2a0e3c4d1280").to_vec();
+        let token = [[0u8; 12].as_slice(), &USER].concat();
+
+        let attributes = pool_parameter_updates(
+            &log(&[targets_set, token], [word(100), word(2000)].concat()),
+            0,
+        )
+        .unwrap();
+        assert_eq!(attributes[0].name, "targets/lower");
+        assert_eq!(attributes[0].value, BigInt::from(100).to_signed_bytes_be());
+        assert_eq!(attributes[1].name, "targets/upper");
+        assert_eq!(attributes[1].value, BigInt::from(2000).to_signed_bytes_be());
+    }
+
+    #[test]
+    fn test_tracks_bpt() {
+        let pool_id = format!("0x{}{}", hex::encode(BPT), "0".repeat(24));
+        let component = ProtocolComponent::new(&pool_id).with_tokens(&[USER.to_vec()]);
+        assert!(!tracks_bpt(&component));
+
+        let component = component.with_tokens(&[USER.to_vec(), BPT.to_vec()]);
+        assert!(tracks_bpt(&component));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{abi, modules::VAULT_ADDRESS};
-use substreams::hex;
+use substreams::{hex, scalar::BigInt};
 use substreams_ethereum::{
     pb::eth::v2::{Call, Log, TransactionTrace},
     Event, Function,
@@ -22,23 +22,46 @@ fn get_pool_registered(
         .clone()
 }
 
-// TODO: add this back if we need to track BPT
-// fn get_token_registered(
-//     tx: &TransactionTrace,
-//     pool_id: &[u8],
-// ) -> abi::vault::events::TokensRegistered {
-//     tx.logs_with_calls()
-//         .filter(|(log, _)| log.address == VAULT_ADDRESS)
-//         .filter_map(|(log, _)| abi::vault::events::TokensRegistered::match_and_decode(log))
-//         .find(|ev| ev.pool_id == pool_id)
-//         .unwrap()
-//         .clone()
-// }
+/// Helper function to get tokens_registered event
+fn get_token_registered(
+    tx: &TransactionTrace,
+    pool_id: &[u8],
+) -> Option<abi::vault::events::TokensRegistered> {
+    tx.logs_with_calls()
+        .filter(|(log, _)| log.address == VAULT_ADDRESS)
+        .filter_map(|(log, _)| abi::vault::events::TokensRegistered::match_and_decode(log))
+        .find(|ev| ev.pool_id == pool_id)
+}
+
+/// Pools with a phantom BPT register their own BPT as one of their tokens. It's only included in
+///  the component's tokens when BPT tracking is enabled, otherwise the given tokens are used. The
+///  given tokens are also used if the pool registered its tokens outside of its creation tx, in
+///  which case its BPT isn't tracked.
+fn get_pool_tokens(
+    tx: &TransactionTrace,
+    pool_id: &[u8],
+    track_bpt: bool,
+    tokens: Vec<Vec<u8>>,
+) -> Vec<Vec<u8>> {
+    if !track_bpt {
+        return tokens;
+    }
+    match get_token_registered(tx, pool_id) {
+        Some(tokens_registered) => tokens_registered.tokens,
+        None => {
+            substreams::log::info!(
+                "No TokensRegistered for pool 0x{} in its creation tx, its BPT isn't tracked",
+                hex::encode(pool_id)
+            );
+            tokens
+        }
+    }
+}
 
 // This is the main function that handles the creation of `ProtocolComponent`s with `Attribute`s
 //  based on the specific factory address. There's 3 factory groups that are represented here:
 //  - Weighted Pool Factories
-//  - Linear Pool Factories
+//  - Linear Pool Factories (ERC4626, Euler, Gearbox, Silo and Yearn)
 //  - Stable Pool Factories
 // (Balancer does have a bit more (esp. in the deprecated section) that could be implemented as
 //  desired.)
@@ -51,6 +74,7 @@ pub fn address_map(
     log: &Log,
     call: &Call,
     tx: &TransactionTrace,
+    track_bpt: bool,
 ) -> Option<ProtocolComponent> {
     match *pool_factory_address {
         hex!("8E9aa87E45e92bad84D5F8DD1bff34Fb92637dE9") => {
@@ -180,14 +204,13 @@ pub fn address_map(
             let pool_created =
                 abi::composable_stable_pool_factory::events::PoolCreated::match_and_decode(log)?;
             let pool_registered = get_pool_registered(tx, &pool_created.pool);
-            // let tokens_registered = get_token_registered(tx, &pool_registered.pool_id);
+            let tokens =
+                get_pool_tokens(tx, &pool_registered.pool_id, track_bpt, create_call.tokens);
 
             Some(
                 ProtocolComponent::new(&format!("0x{}", hex::encode(pool_registered.pool_id)))
                     .with_contracts(&[pool_created.pool.clone(), VAULT_ADDRESS.to_vec()])
-                    // .with_tokens(&tokens_registered.tokens) // TODO: add this back if we need to
-                    // track BPT
-                    .with_tokens(&create_call.tokens)
+                    .with_tokens(&tokens)
                     .with_attributes(&[
                         ("pool_type", "ComposableStablePoolFactory".as_bytes()),
                         ("bpt", &pool_created.pool),
@@ -212,38 +235,134 @@ pub fn address_map(
             let pool_created =
                 abi::erc_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
             let pool_registered = get_pool_registered(tx, &pool_created.pool);
-            // let tokens_registered = get_token_registered(tx, &pool_registered.pool_id);
 
-            Some(
-                ProtocolComponent::new(&format!("0x{}", hex::encode(pool_registered.pool_id)))
-                    .with_contracts(&[pool_created.pool.clone(), VAULT_ADDRESS.to_vec()])
-                    // .with_tokens(&tokens_registered.tokens) // TODO: add this back if we need to
-                    // track BPT
-                    .with_tokens(&[
-                        create_call.main_token.clone(),
-                        create_call.wrapped_token.clone(),
-                    ])
-                    .with_attributes(&[
-                        ("pool_type", "ERC4626LinearPoolFactory".as_bytes()),
-                        (
-                            "upper_target",
-                            &create_call
-                                .upper_target
-                                .to_signed_bytes_be(),
-                        ),
-                        ("manual_updates", &[1u8]),
-                        ("bpt", &pool_created.pool),
-                        ("main_token", &create_call.main_token),
-                        ("wrapped_token", &create_call.wrapped_token),
-                        (
-                            "fee",
-                            &create_call
-                                .swap_fee_percentage
-                                .to_signed_bytes_be(),
-                        ),
-                    ])
-                    .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
-            )
+            let tokens = get_pool_tokens(
+                tx,
+                &pool_registered.pool_id,
+                track_bpt,
+                vec![create_call.main_token.clone(), create_call.wrapped_token.clone()],
+            );
+
+            Some(linear_pool_component(
+                "ERC4626LinearPoolFactory",
+                &pool_registered.pool_id,
+                &pool_created.pool,
+                &tokens,
+                LinearPoolParams {
+                    main_token: &create_call.main_token,
+                    wrapped_token: &create_call.wrapped_token,
+                    upper_target: &create_call.upper_target,
+                    swap_fee_percentage: &create_call.swap_fee_percentage,
+                },
+            ))
+        }
+        hex!("5F43FBa61f63Fa6bFF101a0A0458cEA917f6B347") => {
+            let create_call =
+                abi::euler_linear_pool_factory::functions::Create::match_and_decode(call)?;
+            let pool_created =
+                abi::euler_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
+            let pool_registered = get_pool_registered(tx, &pool_created.pool);
+
+            let tokens = get_pool_tokens(
+                tx,
+                &pool_registered.pool_id,
+                track_bpt,
+                vec![create_call.main_token.clone(), create_call.wrapped_token.clone()],
+            );
+
+            Some(linear_pool_component(
+                "EulerLinearPoolFactory",
+                &pool_registered.pool_id,
+                &pool_created.pool,
+                &tokens,
+                LinearPoolParams {
+                    main_token: &create_call.main_token,
+                    wrapped_token: &create_call.wrapped_token,
+                    upper_target: &create_call.upper_target,
+                    swap_fee_percentage: &create_call.swap_fee_percentage,
+                },
+            ))
+        }
+        hex!("39A79EB449Fc05C92c39aA6f0e9BfaC03BE8dE5B") => {
+            let create_call =
+                abi::gearbox_linear_pool_factory::functions::Create::match_and_decode(call)?;
+            let pool_created =
+                abi::gearbox_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
+            let pool_registered = get_pool_registered(tx, &pool_created.pool);
+
+            let tokens = get_pool_tokens(
+                tx,
+                &pool_registered.pool_id,
+                track_bpt,
+                vec![create_call.main_token.clone(), create_call.wrapped_token.clone()],
+            );
+
+            Some(linear_pool_component(
+                "GearboxLinearPoolFactory",
+                &pool_registered.pool_id,
+                &pool_created.pool,
+                &tokens,
+                LinearPoolParams {
+                    main_token: &create_call.main_token,
+                    wrapped_token: &create_call.wrapped_token,
+                    upper_target: &create_call.upper_target,
+                    swap_fee_percentage: &create_call.swap_fee_percentage,
+                },
+            ))
+        }
+        hex!("4E11AEec21baF1660b1a46472963cB3DA7811C89") => {
+            let create_call =
+                abi::silo_linear_pool_factory::functions::Create::match_and_decode(call)?;
+            let pool_created =
+                abi::silo_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
+            let pool_registered = get_pool_registered(tx, &pool_created.pool);
+
+            let tokens = get_pool_tokens(
+                tx,
+                &pool_registered.pool_id,
+                track_bpt,
+                vec![create_call.main_token.clone(), create_call.wrapped_token.clone()],
+            );
+
+            Some(linear_pool_component(
+                "SiloLinearPoolFactory",
+                &pool_registered.pool_id,
+                &pool_created.pool,
+                &tokens,
+                LinearPoolParams {
+                    main_token: &create_call.main_token,
+                    wrapped_token: &create_call.wrapped_token,
+                    upper_target: &create_call.upper_target,
+                    swap_fee_percentage: &create_call.swap_fee_percentage,
+                },
+            ))
+        }
+        hex!("5F5222Ffa40F2AEd6380D022184D6ea67C776eE0") => {
+            let create_call =
+                abi::yearn_linear_pool_factory::functions::Create::match_and_decode(call)?;
+            let pool_created =
+                abi::yearn_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
+            let pool_registered = get_pool_registered(tx, &pool_created.pool);
+
+            let tokens = get_pool_tokens(
+                tx,
+                &pool_registered.pool_id,
+                track_bpt,
+                vec![create_call.main_token.clone(), create_call.wrapped_token.clone()],
+            );
+
+            Some(linear_pool_component(
+                "YearnLinearPoolFactory",
+                &pool_registered.pool_id,
+                &pool_created.pool,
+                &tokens,
+                LinearPoolParams {
+                    main_token: &create_call.main_token,
+                    wrapped_token: &create_call.wrapped_token,
+                    upper_target: &create_call.upper_target,
+                    swap_fee_percentage: &create_call.swap_fee_percentage,
+                },
+            ))
         }
         // The `WeightedPool2TokenFactory` is a deprecated contract, but we've included
         // it to be able to track one of the highest TVL pools: 80BAL-20WETH.
@@ -273,73 +392,6 @@ pub fn address_map(
             )
         }
 
-        // ❌ EulerLinearPoolFactory: factory is disabled and no existing pools have relevant
-        // liquidity hex!("5F43FBa61f63Fa6bFF101a0A0458cEA917f6B347") => {
-        //     let create_call =
-        //         abi::euler_linear_pool_factory::functions::Create::match_and_decode(call)?;
-        //     let pool_created =
-        //         abi::euler_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
-        //     let pool_registered = get_pool_registered(tx, &pool_created.pool);
-        //     let tokens_registered = get_token_registered(tx, &pool_registered.pool_id);
-
-        //     Some(
-        //         ProtocolComponent::new(&format!("0x{}", hex::encode(pool_registered.pool_id)))
-        //             .with_contracts(&[pool_created.pool.clone(), VAULT_ADDRESS.to_vec()])
-        //             // .with_tokens(&tokens_registered.tokens)
-        //             .with_tokens(&[
-        //                 create_call.main_token.clone(),
-        //                 create_call.wrapped_token.clone(),
-        //             ])
-        //             .with_attributes(&[
-        //                 ("pool_type", "EulerLinearPoolFactory".as_bytes()),
-        //                 (
-        //                     "upper_target",
-        //                     &create_call
-        //                         .upper_target
-        //                         .to_signed_bytes_be(),
-        //                 ),
-        //                 ("manual_updates", &[1u8]),
-        //                 ("bpt", &pool_created.pool),
-        //                 ("main_token", &create_call.main_token),
-        //                 ("wrapped_token", &create_call.wrapped_token),
-        //                 (
-        //                     "fee",
-        //                     &create_call
-        //                         .swap_fee_percentage
-        //                         .to_signed_bytes_be(),
-        //                 ),
-        //             ])
-        //             .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
-        //     )
-        // }
-
-        // ❌ Reading the deployed factory for Gearbox showcases that it's currently disabled
-        // hex!("39A79EB449Fc05C92c39aA6f0e9BfaC03BE8dE5B") => {
-        //     let create_call =
-        //         abi::gearbox_linear_pool_factory::functions::Create::match_and_decode(call)?;
-        //     let pool_created =
-        //         abi::gearbox_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
-
-        //     Some(tycho::ProtocolComponent {
-        //         id: hex::encode(&pool_created.pool),
-        //         tokens: vec![create_call.main_token, create_call.wrapped_token],
-        //         contracts: vec![pool_addr.into(), pool_created.pool],
-        //         static_att: vec![
-        //             tycho::Attribute {
-        //                 name: "pool_type".into(),
-        //                 value: "GearboxLinearPoolFactory".into(),
-        //                 change: tycho::ChangeType::Creation.into(),
-        //             },
-        //             tycho::Attribute {
-        //                 name: "upper_target".into(),
-        //                 value: create_call.upper_target.to_signed_bytes_be(),
-        //                 change: tycho::ChangeType::Creation.into(),
-        //             },
-        //         ],
-        //         change: tycho::ChangeType::Creation.into(),
-        //     })
-        // }
-
         // ❌ The `ManagedPoolFactory` is a bit ✨ unique ✨, so we'll leave it commented out for
         // now. Take a look at it's `Create` call to see how the params are structured.
         // hex!("BF904F9F340745B4f0c4702c7B6Ab1e808eA6b93") => {
@@ -362,78 +414,44 @@ pub fn address_map(
         //         change: tycho::ChangeType::Creation.into(),
         //     })
         // }
-
-        // ❌ SiloLinearPoolFactory: factory is disabled and no existing pools have relevant
-        // liquidity hex!("4E11AEec21baF1660b1a46472963cB3DA7811C89") => {
-        //     let create_call =
-        //         abi::silo_linear_pool_factory::functions::Create::match_and_decode(call)?;
-        //     let pool_created =
-        //         abi::silo_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
-        //     let pool_registered = get_pool_registered(tx, &pool_created.pool);
-        //     let tokens_registered = get_token_registered(tx, &pool_registered.pool_id);
-
-        //     Some(
-        //         ProtocolComponent::new(&format!("0x{}", hex::encode(pool_registered.pool_id)))
-        //             .with_contracts(&[pool_created.pool.clone(), VAULT_ADDRESS.to_vec()])
-        //             .with_tokens(&tokens_registered.tokens)
-        //             .with_attributes(&[
-        //                 ("pool_type", "SiloLinearPoolFactory".as_bytes()),
-        //                 (
-        //                     "upper_target",
-        //                     &create_call
-        //                         .upper_target
-        //                         .to_signed_bytes_be(),
-        //                 ),
-        //                 ("manual_updates", &[1u8]),
-        //                 ("bpt", &pool_created.pool),
-        //                 ("main_token", &create_call.main_token),
-        //                 ("wrapped_token", &create_call.wrapped_token),
-        //                 (
-        //                     "fee",
-        //                     &create_call
-        //                         .swap_fee_percentage
-        //                         .to_signed_bytes_be(),
-        //                 ),
-        //             ])
-        //             .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
-        //     )
-        // }
-
-        // ❌ YearnLinearPoolFactory: factory is disabled and no existing pools have relevant
-        // liquidity hex!("5F5222Ffa40F2AEd6380D022184D6ea67C776eE0") => {
-        //     let create_call =
-        //         abi::yearn_linear_pool_factory::functions::Create::match_and_decode(call)?;
-        //     let pool_created =
-        //         abi::yearn_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
-        //     let pool_registered = get_pool_registered(tx, &pool_created.pool);
-        //     let tokens_registered = get_token_registered(tx, &pool_registered.pool_id);
-
-        //     Some(
-        //         ProtocolComponent::new(&format!("0x{}", hex::encode(pool_registered.pool_id)))
-        //             .with_contracts(&[pool_created.pool.clone(), VAULT_ADDRESS.to_vec()])
-        //             .with_tokens(&tokens_registered.tokens)
-        //             .with_attributes(&[
-        //                 ("pool_type", "YearnLinearPoolFactory".as_bytes()),
-        //                 (
-        //                     "upper_target",
-        //                     &create_call
-        //                         .upper_target
-        //                         .to_signed_bytes_be(),
-        //                 ),
-        //                 ("manual_updates", &[1u8]),
-        //                 ("bpt", &pool_created.pool),
-        //                 ("main_token", &create_call.main_token),
-        //                 ("wrapped_token", &create_call.wrapped_token),
-        //                 (
-        //                     "fee",
-        //                     &create_call
-        //                         .swap_fee_percentage
-        //                         .to_signed_bytes_be(),
-        //                 ),
-        //             ])
-        //             .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
-        //     )
-        // }
         _ => None,
     }
 }
+
+struct LinearPoolParams<'a> {
+    main_token: &'a [u8],
+    wrapped_token: &'a [u8],
+    upper_target: &'a BigInt,
+    swap_fee_percentage: &'a BigInt,
+}
+
+// All linear pool factories share the same `create` parameters, only the way the pool reads the
+//  wrapped token rate differs. The pool exposes that rate through `getWrappedTokenRate()`, so the
+//  `bpt` contract is the rate provider of its wrapped token. The targets can be changed after
+//  deployment, their current values are the `targets/*` attributes emitted on `TargetsSet`.
+fn linear_pool_component(
+    pool_type: &str,
+    pool_id: &[u8],
+    pool: &[u8],
+    tokens: &[Vec<u8>],
+    params: LinearPoolParams,
+) -> ProtocolComponent {
+    ProtocolComponent::new(&format!("0x{}", hex::encode(pool_id)))
+        .with_contracts(&[pool, VAULT_ADDRESS])
+        .with_tokens(tokens)
+        .with_attributes(&[
+            ("pool_type", pool_type.as_bytes()),
+            ("upper_target", &params.upper_target.to_signed_bytes_be()),
+            ("manual_updates", &[1u8]),
+            ("bpt", pool),
+            ("main_token", params.main_token),
+            ("wrapped_token", params.wrapped_token),
+            (
+                "fee",
+                &params
+                    .swap_fee_percentage
+                    .to_signed_bytes_be(),
+            ),
+        ])
+        .as_swap_type("balancer_v2_pool", ImplementationType::Vm)
+}
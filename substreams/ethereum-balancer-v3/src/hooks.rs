@@ -0,0 +1,183 @@
+use ethabi::{ethereum_types::U256, Address, Token};
+use tycho_substreams::{
+    entrypoint::create_entrypoint, models::entry_point_params::TraceData, prelude::*,
+};
+
+use crate::modules::VAULT_ADDRESS;
+
+const COMPUTE_DYNAMIC_SWAP_FEE_SIGNATURE: &str = "onComputeDynamicSwapFeePercentage((uint8,uint256,uint256[],uint256,uint256,address,bytes),address,uint256)";
+const COMPUTE_DYNAMIC_SWAP_FEE_SELECTOR: [u8; 4] = [0xa0, 0xe8, 0xf5, 0xac];
+const BEFORE_SWAP_SIGNATURE: &str =
+    "onBeforeSwap((uint8,uint256,uint256[],uint256,uint256,address,bytes),address)";
+const BEFORE_SWAP_SELECTOR: [u8; 4] = [0x52, 0x11, 0xfa, 0x77];
+const AFTER_SWAP_SIGNATURE: &str = "onAfterSwap((uint8,address,address,uint256,uint256,uint256,uint256,uint256,uint256,address,address,bytes))";
+const AFTER_SWAP_SELECTOR: [u8; 4] = [0x18, 0xb6, 0xeb, 0x55];
+
+/// `SwapKind.EXACT_IN`
+const EXACT_IN: u64 = 0;
+/// The amounts and balances the callbacks are traced with, one token in 18 decimals.
+const ONE: u64 = 1_000_000_000_000_000_000;
+
+/// Creates the entrypoints of the swap callbacks enabled in a pool's hooks config, so the
+/// contracts the hook reads during a swap can be traced by the DCI.
+///
+/// The callbacks are traced with the Vault as caller, as hooks only accept calls from it, for an
+/// exact in swap of one token from the first to the second pool token.
+pub fn swap_entrypoints(component: &ProtocolComponent) -> Vec<(EntryPoint, EntryPointParams)> {
+    let attribute = |name: &str| {
+        component
+            .static_att
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| attr.value.clone())
+    };
+    let enabled = |name: &str| attribute(name).is_some_and(|value| value == [1u8]);
+    let (Some(hooks), [token_in, token_out, ..]) =
+        (attribute("hooks"), component.tokens.as_slice())
+    else {
+        return vec![];
+    };
+    let Ok(pool) = hex::decode(component.id.trim_start_matches("0x")) else {
+        return vec![];
+    };
+
+    let callbacks = [
+        (
+            "hooks_should_call_compute_dynamic_swap_fee",
+            COMPUTE_DYNAMIC_SWAP_FEE_SIGNATURE,
+            COMPUTE_DYNAMIC_SWAP_FEE_SELECTOR,
+            // The static swap fee only feeds the hook's fee logic, so any value reaches it.
+            vec![
+                pool_swap_params_token(component.tokens.len()),
+                address_token(&pool),
+                Token::Uint(U256::zero()),
+            ],
+        ),
+        (
+            "hooks_should_call_before_swap",
+            BEFORE_SWAP_SIGNATURE,
+            BEFORE_SWAP_SELECTOR,
+            vec![pool_swap_params_token(component.tokens.len()), address_token(&pool)],
+        ),
+        (
+            "hooks_should_call_after_swap",
+            AFTER_SWAP_SIGNATURE,
+            AFTER_SWAP_SELECTOR,
+            vec![after_swap_params_token(token_in, token_out, &pool)],
+        ),
+    ];
+
+    callbacks
+        .into_iter()
+        .filter(|(flag, ..)| enabled(flag))
+        .map(|(_, signature, selector, arguments)| {
+            let calldata = [selector.as_slice(), &ethabi::encode(&arguments)].concat();
+            create_entrypoint(
+                hooks.clone(),
+                signature.to_string(),
+                component.id.clone(),
+                TraceData::Rpc(RpcTraceData { caller: Some(VAULT_ADDRESS.to_vec()), calldata }),
+            )
+        })
+        .collect()
+}
+
+fn address_token(address: &[u8]) -> Token {
+    Token::Address(Address::from_slice(address))
+}
+
+/// `PoolSwapParams(kind, amountGivenScaled18, balancesScaled18, indexIn, indexOut, router,
+/// userData)`, the router left unset.
+fn pool_swap_params_token(n_tokens: usize) -> Token {
+    Token::Tuple(vec![
+        Token::Uint(U256::from(EXACT_IN)),
+        Token::Uint(U256::from(ONE)),
+        Token::Array(vec![Token::Uint(U256::from(ONE)); n_tokens]),
+        Token::Uint(U256::zero()),
+        Token::Uint(U256::one()),
+        address_token(&[0u8; 20]),
+        Token::Bytes(vec![]),
+    ])
+}
+
+/// `AfterSwapParams(kind, tokenIn, tokenOut, amountInScaled18, amountOutScaled18,
+/// tokenInBalanceScaled18, tokenOutBalanceScaled18, amountCalculatedScaled18, amountCalculatedRaw,
+/// router, pool, userData)`, the router left unset.
+fn after_swap_params_token(token_in: &[u8], token_out: &[u8], pool: &[u8]) -> Token {
+    Token::Tuple(vec![
+        Token::Uint(U256::from(EXACT_IN)),
+        address_token(token_in),
+        address_token(token_out),
+        Token::Uint(U256::from(ONE)),
+        Token::Uint(U256::from(ONE)),
+        Token::Uint(U256::from(ONE)),
+        Token::Uint(U256::from(ONE)),
+        Token::Uint(U256::from(ONE)),
+        Token::Uint(U256::from(ONE)),
+        address_token(&[0u8; 20]),
+        address_token(pool),
+        Token::Bytes(vec![]),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool_factories::hook_attributes;
+
+    fn component(before_swap: bool, after_swap: bool, dynamic_fee: bool) -> ProtocolComponent {
+        let mut component = ProtocolComponent::new(&format!("0x{}", "aa".repeat(20)))
+            .with_tokens(&[vec![1u8; 20], vec![2u8; 20]]);
+        component.static_att = hook_attributes(&(
+            false,
+            false,
+            false,
+            dynamic_fee,
+            before_swap,
+            after_swap,
+            true,
+            true,
+            false,
+            false,
+            vec![3u8; 20],
+        ));
+        component
+    }
+
+    #[test]
+    fn test_swap_entrypoints() {
+        let entrypoints = swap_entrypoints(&component(true, false, true));
+
+        let signatures = entrypoints
+            .iter()
+            .map(|(entrypoint, _)| entrypoint.signature.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(signatures, [COMPUTE_DYNAMIC_SWAP_FEE_SIGNATURE, BEFORE_SWAP_SIGNATURE]);
+        for (entrypoint, params) in entrypoints {
+            assert_eq!(entrypoint.target, vec![3u8; 20]);
+            let Some(TraceData::Rpc(trace_data)) = params.trace_data else {
+                panic!("expected RPC trace data");
+            };
+            assert_eq!(trace_data.caller, Some(VAULT_ADDRESS.to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_after_swap_calldata() {
+        let entrypoints = swap_entrypoints(&component(false, true, false));
+
+        assert_eq!(entrypoints.len(), 1);
+        let Some(TraceData::Rpc(trace_data)) = &entrypoints[0].1.trace_data else {
+            panic!("expected RPC trace data");
+        };
+        assert_eq!(trace_data.calldata[..4], AFTER_SWAP_SELECTOR);
+        // The dynamic params tuple is encoded behind its offset: 12 words and `userData`'s length
+        assert_eq!(trace_data.calldata.len(), 4 + 32 * 14);
+    }
+
+    #[test]
+    fn test_swap_entrypoints_without_swap_hooks() {
+        assert!(swap_entrypoints(&component(false, false, false)).is_empty());
+        assert!(swap_entrypoints(&ProtocolComponent::new("0x00")).is_empty());
+    }
+}
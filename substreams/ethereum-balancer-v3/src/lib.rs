@@ -1,3 +1,4 @@
 mod abi;
+mod hooks;
 mod modules;
 mod pool_factories;
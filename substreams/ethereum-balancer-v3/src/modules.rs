@@ -1,20 +1,27 @@
 use crate::{
     abi::vault_contract::{
-        events::{LiquidityAdded, LiquidityRemoved, PoolPausedStateChanged, Swap},
+        events::{
+            LiquidityAdded, LiquidityAddedToBuffer, LiquidityRemoved, LiquidityRemovedFromBuffer,
+            PoolPausedStateChanged, PoolRegistered, Swap, Unwrap, Wrap,
+        },
         functions::{Erc4626BufferWrapOrUnwrap, SendTo, Settle},
     },
-    pool_factories,
+    hooks,
+    pool_factories::{self, RegisteredPoolFactory},
 };
 use anyhow::Result;
 use itertools::Itertools;
 use keccak_hash::keccak;
+use serde::Deserialize;
 use std::collections::HashMap;
 use substreams::{
     hex, log,
     pb::substreams::StoreDeltas,
+    scalar::BigInt,
     store::{
-        StoreAddBigInt, StoreGet, StoreGetInt64, StoreGetProto, StoreNew, StoreSetIfNotExists,
-        StoreSetIfNotExistsInt64, StoreSetIfNotExistsProto,
+        StoreAddBigInt, StoreGet, StoreGetInt64, StoreGetProto, StoreGetString, StoreNew,
+        StoreSetIfNotExists, StoreSetIfNotExistsInt64, StoreSetIfNotExistsProto,
+        StoreSetIfNotExistsString,
     },
 };
 use substreams_ethereum::{
@@ -32,18 +39,98 @@ pub const VAULT_EXTENSION_ADDRESS: &[u8; 20] = &hex!("0E8B07657D719B86e06bF0806D
 pub const BATCH_ROUTER_ADDRESS: &[u8; 20] = &hex!("136f1efcc3f8f88516b9e94110d56fdbfb1778d1");
 pub const PERMIT_2_ADDRESS: &[u8; 20] = &hex!("000000000022D473030F116dDEE9F6B43aC78BA3");
 
+#[derive(Deserialize)]
+struct Params {
+    /// Keep emitting the deprecated `balance_owner` attribute until all consumers read the vault
+    /// account balances instead
+    #[serde(default)]
+    emit_balance_owner: bool,
+}
+
+#[derive(Deserialize)]
+struct ComponentsParams {
+    /// Factories whose pools are indexed from the Vault's `PoolRegistered` event
+    #[serde(default)]
+    registered_factories: Vec<RegisteredPoolFactory>,
+}
+
+/// Stores the underlying token of every initialized ERC4626 buffer, keyed by the wrapped token.
+#[substreams::handlers::store]
+pub fn store_buffer_assets(block: eth::v2::Block, store: StoreSetIfNotExistsString) {
+    block.transactions().for_each(|tx| {
+        pool_factories::buffer_initializations(tx)
+            .into_iter()
+            .for_each(|(wrapped_token, underlying_token)| {
+                store.set_if_not_exists(
+                    0,
+                    hex::encode(wrapped_token),
+                    &hex::encode(underlying_token),
+                )
+            })
+    });
+}
+
 #[substreams::handlers::map]
-pub fn map_components(block: eth::v2::Block) -> Result<BlockTransactionProtocolComponents> {
+pub fn map_components(
+    params: String,
+    block: eth::v2::Block,
+    buffer_assets_store: StoreGetString,
+) -> Result<BlockTransactionProtocolComponents> {
+    let params: ComponentsParams = serde_qs::from_str(&params)?;
+
     let mut tx_components = Vec::new();
     for tx in block.transactions() {
-        let mut components = Vec::new();
+        let mut components = pool_factories::buffer_initializations(tx)
+            .into_iter()
+            .map(|(wrapped_token, underlying_token)| {
+                pool_factories::buffer_component(&wrapped_token, &underlying_token)
+            })
+            .collect::<Vec<_>>();
         for (log, call) in tx.logs_with_calls() {
-            if let Some(component) =
-                pool_factories::address_map(log.address.as_slice(), log, call.call)
-            {
+            if let Some(component) = pool_factories::address_map(
+                log.address.as_slice(),
+                log,
+                call.call,
+                &buffer_assets_store,
+            ) {
                 components.push(component);
             }
         }
+        // Every pool registers with the Vault after its factory created it, so the hooks config
+        //  can be attached to the components decoded above. Pools from the configured factories
+        //  are built from the registration alone.
+        for log in tx
+            .logs_with_calls()
+            .map(|(log, _)| log)
+            .filter(|log| log.address == VAULT_ADDRESS)
+        {
+            if let Some(registered) = PoolRegistered::match_and_decode(log) {
+                let component_id = address_to_string_with_0x(&registered.pool);
+                let hook_attributes = pool_factories::hook_attributes(&registered.hooks_config);
+                if let Some(component) = components
+                    .iter_mut()
+                    .find(|c| c.id == component_id)
+                {
+                    component
+                        .static_att
+                        .extend(hook_attributes);
+                } else if let Some(factory) = params
+                    .registered_factories
+                    .iter()
+                    .find(|f| f.address == registered.factory)
+                {
+                    let mut component = pool_factories::registered_pool_component(
+                        &registered,
+                        factory,
+                        &buffer_assets_store,
+                    );
+                    component
+                        .static_att
+                        .extend(hook_attributes);
+                    components.push(component);
+                }
+            }
+        }
         if !components.is_empty() {
             tx_components.push(TransactionProtocolComponents { tx: Some(tx.into()), components });
         }
@@ -210,6 +297,7 @@ pub fn store_balances(deltas: BlockBalanceDeltas, store: StoreAddBigInt) {
 /// `BlockChanges`  is ordered by transactions properly.
 #[substreams::handlers::map]
 pub fn map_protocol_changes(
+    params: String,
     block: eth::v2::Block,
     grouped_components: BlockTransactionProtocolComponents,
     deltas: BlockBalanceDeltas,
@@ -217,6 +305,8 @@ pub fn map_protocol_changes(
     tokens_store: StoreGetInt64,
     balance_store: StoreDeltas, // Note, this map module is using the `deltas` mode for the store.
 ) -> Result<BlockChanges> {
+    let params: Params = serde_qs::from_str(&params)?;
+
     // We merge contract changes by transaction (identified by transaction index) making it easy to
     //  sort them at the very end.
     let mut transaction_changes: HashMap<_, TransactionChangesBuilder> = HashMap::new();
@@ -258,13 +348,7 @@ pub fn map_protocol_changes(
 
     // `ProtocolComponents` are gathered from `map_pools_created` which just need a bit of work to
     //   convert into `TransactionChanges`
-    let default_attributes = vec![
-        Attribute {
-            // TODO: remove this and track account_balances instead
-            name: "balance_owner".to_string(),
-            value: VAULT_ADDRESS.to_vec(),
-            change: ChangeType::Creation.into(),
-        },
+    let mut default_attributes = vec![
         Attribute {
             name: "stateless_contract_addr_0".into(),
             value: address_to_bytes_with_0x(VAULT_EXTENSION_ADDRESS),
@@ -286,6 +370,13 @@ pub fn map_protocol_changes(
             change: ChangeType::Creation.into(),
         },
     ];
+    if params.emit_balance_owner {
+        default_attributes.push(Attribute {
+            name: "balance_owner".to_string(),
+            value: VAULT_ADDRESS.to_vec(),
+            change: ChangeType::Creation.into(),
+        });
+    }
     grouped_components
         .tx_components
         .iter()
@@ -324,6 +415,13 @@ pub fn map_protocol_changes(
                         }
                     }
 
+                    // Hook contracts are called by the Vault on every swap, so the callbacks
+                    //  they enable are traced to pick up the contracts they depend on.
+                    for (entrypoint, entrypoint_params) in hooks::swap_entrypoints(component) {
+                        builder.add_entrypoint(&entrypoint);
+                        builder.add_entrypoint_params(&entrypoint_params);
+                    }
+
                     builder.add_protocol_component(component);
                     let entity_change = EntityChanges {
                         component_id: component.id.clone(),
@@ -353,6 +451,32 @@ pub fn map_protocol_changes(
                 });
         });
 
+    // Every buffer event of the Vault emits the buffer balances after the operation
+    block
+        .logs()
+        .filter(|log| log.address() == VAULT_ADDRESS)
+        .for_each(|log| {
+            if let Some((wrapped_token, balances)) = buffer_balances(log.log) {
+                let component_id = address_to_string_with_0x(&wrapped_token);
+                if let Some(component) =
+                    components_store.get_last(format!("pool:{}", &component_id))
+                {
+                    let tx: Transaction = log.receipt.transaction.into();
+                    let builder = transaction_changes
+                        .entry(tx.index)
+                        .or_insert_with(|| TransactionChangesBuilder::new(&tx));
+
+                    for (token, balance) in component.tokens.iter().zip(balances) {
+                        builder.add_balance_change(&BalanceChange {
+                            token: token.clone(),
+                            balance: balance.to_signed_bytes_be(),
+                            component_id: component_id.as_bytes().to_vec(),
+                        });
+                    }
+                }
+            }
+        });
+
     // Extract and insert any storage changes that happened for any of the components.
     extract_contract_changes_builder(
         &block,
@@ -481,7 +605,7 @@ fn get_vault_reserves(
                 for change in &call.storage_changes {
                     let wrapped_token = params.2.clone();
                     let component_id = format!("0x{}", hex::encode(&wrapped_token));
-                    if let Some(component) = store.get_last(component_id) {
+                    if let Some(component) = store.get_last(format!("pool:{}", component_id)) {
                         let underlying_token = component.tokens[1].clone();
                         add_change_if_accounted(
                             &mut reserves_of,
@@ -539,3 +663,88 @@ fn get_storage_key_for_token(token_address: &[u8]) -> Vec<u8> {
         .to_vec();
     result
 }
+
+/// Decodes the wrapped token and the `[wrapped, underlying]` balances of a buffer from a `Wrap`,
+/// `Unwrap`, `LiquidityAddedToBuffer` or `LiquidityRemovedFromBuffer` event.
+///
+/// Buffer balances are packed into a single Vault storage slot: the underlying balance in the
+/// lower 128 bits and the wrapped balance in the upper 128 bits.
+fn buffer_balances(log: &eth::v2::Log) -> Option<(Vec<u8>, [BigInt; 2])> {
+    let (wrapped_token, buffer_balances) = if let Some(ev) = Wrap::match_and_decode(log) {
+        (ev.wrapped_token, ev.buffer_balances)
+    } else if let Some(ev) = Unwrap::match_and_decode(log) {
+        (ev.wrapped_token, ev.buffer_balances)
+    } else if let Some(ev) = LiquidityAddedToBuffer::match_and_decode(log) {
+        (ev.wrapped_token, ev.buffer_balances)
+    } else {
+        let ev = LiquidityRemovedFromBuffer::match_and_decode(log)?;
+        (ev.wrapped_token, ev.buffer_balances)
+    };
+
+    Some((
+        wrapped_token,
+        [
+            BigInt::from_unsigned_bytes_be(&buffer_balances[..16]),
+            BigInt::from_unsigned_bytes_be(&buffer_balances[16..]),
+        ],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_components_params() {
+        let params: ComponentsParams = serde_qs::from_str(
+            "registered_factories[0][address]=b96524227c4B5Ab908FC3d42005FE3B07abA40E9&registered_factories[0][pool_type]=Gyro2CLPPoolFactory",
+        )
+        .unwrap();
+
+        assert_eq!(
+            params.registered_factories,
+            vec![RegisteredPoolFactory {
+                address: hex!("b96524227c4B5Ab908FC3d42005FE3B07abA40E9").to_vec(),
+                pool_type: "Gyro2CLPPoolFactory".to_string(),
+            }]
+        );
+
+        let params: ComponentsParams = serde_qs::from_str("").unwrap();
+        assert!(params.registered_factories.is_empty());
+    }
+
+    fn buffer_log(
+        topic: [u8; 32],
+        wrapped_balance: u128,
+        underlying_balance: u128,
+    ) -> eth::v2::Log {
+        let word = |value: u128| [[0u8; 16], value.to_be_bytes()].concat();
+        eth::v2::Log {
+            address: VAULT_ADDRESS.to_vec(),
+            topics: vec![topic.to_vec(), [[0u8; 12].as_slice(), &[0xaa; 20]].concat()],
+            data: [
+                word(100),
+                word(90),
+                [wrapped_balance.to_be_bytes(), underlying_balance.to_be_bytes()].concat(),
+            ]
+            .concat(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_buffer_balances() {
+        let wrap = hex!("3771d13c67011e31e12031c54bb59b0bf544a80b81d280a3711e172aa8b7f47b");
+        let unwrap = hex!("eeb740c90bf2b18c9532eb7d473137767036d893dff3e009f32718f821b2a4c0");
+
+        assert_eq!(
+            buffer_balances(&buffer_log(wrap, 500, 1200)),
+            Some((vec![0xaa; 20], [BigInt::from(500), BigInt::from(1200)]))
+        );
+        assert_eq!(
+            buffer_balances(&buffer_log(unwrap, 410, 1300)),
+            Some((vec![0xaa; 20], [BigInt::from(410), BigInt::from(1300)]))
+        );
+        assert_eq!(buffer_balances(&buffer_log([0; 32], 500, 1200)), None);
+    }
+}
@@ -3,13 +3,19 @@ use abi::{
     stable_pool_factory_contract::{
         events::PoolCreated as StablePoolCreated, functions::Create as StablePoolCreate,
     },
+    vault_contract::events::{BufferSharesMinted, PoolRegistered},
     weighted_pool_factory_contract::{
         events::PoolCreated as WeightedPoolCreated, functions::Create as WeightedPoolCreate,
     },
 };
-use substreams::{hex, scalar::BigInt};
+use serde::Deserialize;
+use substreams::{
+    hex,
+    scalar::BigInt,
+    store::{StoreGet, StoreGetString},
+};
 use substreams_ethereum::{
-    pb::eth::v2::{Call, Log},
+    pb::eth::v2::{Call, Log, TransactionTrace},
     Event, Function,
 };
 use tycho_substreams::{
@@ -20,6 +26,19 @@ use tycho_substreams::{
 // Token config: (token_address, rate, rate_provider_address, is_exempt_from_yield_fees)
 type TokenConfig = Vec<(Vec<u8>, substreams::scalar::BigInt, Vec<u8>, bool)>;
 
+// Hooks config: the ten hook flags in `HooksConfig` order followed by the hooks contract address
+type HooksConfig = (bool, bool, bool, bool, bool, bool, bool, bool, bool, bool, Vec<u8>);
+
+/// A pool factory whose pools are indexed from the Vault's `PoolRegistered` event alone, without
+/// decoding the factory's own `create` call.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct RegisteredPoolFactory {
+    #[serde(with = "hex::serde")]
+    pub address: Vec<u8>,
+    /// Value of the `pool_type` static attribute, e.g. `GyroECLPPoolFactory`
+    pub pool_type: String,
+}
+
 pub fn collect_rate_providers(tokens: &TokenConfig) -> Vec<Vec<u8>> {
     tokens
         .iter()
@@ -28,10 +47,159 @@ pub fn collect_rate_providers(tokens: &TokenConfig) -> Vec<Vec<u8>> {
         .collect::<Vec<_>>()
 }
 
+/// Looks up the underlying asset of every pool token that has an initialized Vault buffer. Tokens
+/// without a buffer map to the zero address so the list stays aligned with the pool tokens.
+fn collect_underlying_tokens(tokens: &[Vec<u8>], buffer_assets: &StoreGetString) -> Vec<Vec<u8>> {
+    tokens
+        .iter()
+        .map(|token| {
+            buffer_assets
+                .get_last(hex::encode(token))
+                .map(|underlying| hex::decode(underlying).expect("invalid buffer asset"))
+                .unwrap_or_else(|| vec![0u8; 20])
+        })
+        .collect::<Vec<_>>()
+}
+
+/// Finds the ERC4626 buffers initialized within a transaction and returns them as
+/// `(wrapped_token, underlying_token)` pairs.
+///
+/// `initializeBuffer` always mints the minimum buffer supply to the zero address, which is how we
+/// tell an initialization apart from a regular liquidity addition. The underlying token isn't part
+/// of any event, so it is taken from the `asset()` call the Vault makes on the wrapped token.
+pub fn buffer_initializations(tx: &TransactionTrace) -> Vec<(Vec<u8>, Vec<u8>)> {
+    tx.logs_with_calls()
+        .filter(|(log, _)| log.address == VAULT_ADDRESS)
+        .filter_map(|(log, _)| BufferSharesMinted::match_and_decode(log))
+        .filter(|minted| minted.to == [0u8; 20])
+        .filter_map(|BufferSharesMinted { wrapped_token, .. }| {
+            tx.calls
+                .iter()
+                .filter(|call| !call.state_reverted)
+                .find(|call| {
+                    call.caller == VAULT_ADDRESS &&
+                        call.address == wrapped_token &&
+                        call.input.starts_with(&hex!("38d52e0f")) && // asset()
+                        call.return_data.len() == 32
+                })
+                .map(|call| (wrapped_token.clone(), call.return_data[12..].to_vec()))
+        })
+        .collect::<Vec<_>>()
+}
+
+/// Builds the component of an ERC4626 Vault buffer. The component id is the wrapped token address
+/// and the tokens are ordered `[wrapped, underlying]`.
+pub fn buffer_component(wrapped_token: &[u8], underlying_token: &[u8]) -> ProtocolComponent {
+    ProtocolComponent::new(&format!("0x{}", hex::encode(wrapped_token)))
+        .with_contracts(&[wrapped_token.to_vec(), VAULT_ADDRESS.to_vec()])
+        .with_tokens(&[wrapped_token.to_vec(), underlying_token.to_vec()])
+        .with_attributes(&[("pool_type", "Erc4626Buffer".as_bytes()), ("manual_updates", &[1u8])])
+        .as_swap_type("balancer_v3_buffer", ImplementationType::Vm)
+}
+
+/// Builds a component from the Vault's `PoolRegistered` event for the pool types that don't need
+/// any factory specific attributes.
+pub fn registered_pool_component(
+    registered: &PoolRegistered,
+    factory: &RegisteredPoolFactory,
+    buffer_assets: &StoreGetString,
+) -> ProtocolComponent {
+    let rate_providers = collect_rate_providers(&registered.token_config);
+    let tokens = registered
+        .token_config
+        .iter()
+        .map(|t| t.0.clone())
+        .collect::<Vec<_>>();
+    let underlying_tokens = collect_underlying_tokens(&tokens, buffer_assets);
+
+    let fee_bytes = registered
+        .swap_fee_percentage
+        .to_signed_bytes_be();
+    let rate_providers_bytes = json_serialize_address_list(rate_providers.as_slice());
+    let underlying_tokens_bytes = json_serialize_address_list(underlying_tokens.as_slice());
+
+    let mut attributes = vec![
+        ("pool_type", factory.pool_type.as_bytes()),
+        ("bpt", &registered.pool),
+        ("fee", &fee_bytes),
+        ("manual_updates", &[1u8]),
+    ];
+
+    if !rate_providers.is_empty() {
+        attributes.push(("rate_providers", &rate_providers_bytes));
+    }
+
+    if underlying_tokens
+        .iter()
+        .any(|token| token != &[0u8; 20])
+    {
+        attributes.push(("underlying_tokens", &underlying_tokens_bytes));
+    }
+
+    ProtocolComponent::new(&format!("0x{}", hex::encode(&registered.pool)))
+        .with_contracts(&[registered.pool.clone(), VAULT_ADDRESS.to_vec()])
+        .with_tokens(tokens.as_slice())
+        .with_attributes(&attributes)
+        .as_swap_type("balancer_v3_pool", ImplementationType::Vm)
+}
+
+/// Decodes the hooks configuration a pool was registered with into static attributes. Pools
+/// without a hooks contract get no attributes at all.
+pub fn hook_attributes(hooks_config: &HooksConfig) -> Vec<Attribute> {
+    let (
+        enable_hook_adjusted_amounts,
+        should_call_before_initialize,
+        should_call_after_initialize,
+        should_call_compute_dynamic_swap_fee,
+        should_call_before_swap,
+        should_call_after_swap,
+        should_call_before_add_liquidity,
+        should_call_after_add_liquidity,
+        should_call_before_remove_liquidity,
+        should_call_after_remove_liquidity,
+        hooks_contract,
+    ) = hooks_config;
+
+    if hooks_contract == &[0u8; 20] {
+        return vec![];
+    }
+
+    [
+        ("hooks", hooks_contract.clone()),
+        ("hooks_enable_hook_adjusted_amounts", vec![*enable_hook_adjusted_amounts as u8]),
+        ("hooks_should_call_before_initialize", vec![*should_call_before_initialize as u8]),
+        ("hooks_should_call_after_initialize", vec![*should_call_after_initialize as u8]),
+        (
+            "hooks_should_call_compute_dynamic_swap_fee",
+            vec![*should_call_compute_dynamic_swap_fee as u8],
+        ),
+        ("hooks_should_call_before_swap", vec![*should_call_before_swap as u8]),
+        ("hooks_should_call_after_swap", vec![*should_call_after_swap as u8]),
+        ("hooks_should_call_before_add_liquidity", vec![*should_call_before_add_liquidity as u8]),
+        ("hooks_should_call_after_add_liquidity", vec![*should_call_after_add_liquidity as u8]),
+        (
+            "hooks_should_call_before_remove_liquidity",
+            vec![*should_call_before_remove_liquidity as u8],
+        ),
+        (
+            "hooks_should_call_after_remove_liquidity",
+            vec![*should_call_after_remove_liquidity as u8],
+        ),
+    ]
+    .into_iter()
+    .map(|(name, value)| Attribute {
+        name: name.to_string(),
+        value,
+        change: ChangeType::Creation.into(),
+    })
+    .collect::<Vec<_>>()
+}
+
 pub fn address_map(
     pool_factory_address: &[u8],
     log: &Log,
     call: &Call,
+    buffer_assets: &StoreGetString,
 ) -> Option<ProtocolComponent> {
     match *pool_factory_address {
         hex!("201efd508c8DfE9DE1a13c2452863A78CB2a86Cc") => {
@@ -44,19 +212,17 @@ pub fn address_map(
             let WeightedPoolCreated { pool } = WeightedPoolCreated::match_and_decode(log)?;
             let rate_providers = collect_rate_providers(&token_config);
 
-            // TODO: to add "buffers" support for boosted pools, we need to add the unwrapped
-            // version of all ERC4626 tokens to the pool tokens list. Skipped for now - we need
-            // to test that the adapter supports it correctly and ERC4626 overwrites are handled
-            // correctly in simulation.
             let tokens = token_config
                 .into_iter()
                 .map(|t| t.0)
                 .collect::<Vec<_>>();
+            let underlying_tokens = collect_underlying_tokens(&tokens, buffer_assets);
 
             let normalized_weights_bytes =
                 json_serialize_bigint_list(normalized_weights.as_slice());
             let fee_bytes = swap_fee_percentage.to_signed_bytes_be();
             let rate_providers_bytes = json_serialize_address_list(rate_providers.as_slice());
+            let underlying_tokens_bytes = json_serialize_address_list(underlying_tokens.as_slice());
 
             let mut attributes = vec![
                 ("pool_type", "WeightedPoolFactory".as_bytes()),
@@ -69,6 +235,14 @@ pub fn address_map(
                 attributes.push(("rate_providers", &rate_providers_bytes));
             }
 
+            // Boosted pools: expose the underlying tokens reachable through the Vault buffers.
+            if underlying_tokens
+                .iter()
+                .any(|token| token != &[0u8; 20])
+            {
+                attributes.push(("underlying_tokens", &underlying_tokens_bytes));
+            }
+
             Some(
                 ProtocolComponent::new(&format!("0x{}", hex::encode(&pool)))
                     .with_contracts(&[pool, VAULT_ADDRESS.to_vec()])
@@ -83,17 +257,15 @@ pub fn address_map(
             let StablePoolCreated { pool } = StablePoolCreated::match_and_decode(log)?;
             let rate_providers = collect_rate_providers(&token_config);
 
-            // TODO: to add "buffers" support for boosted pools, we need to add the unwrapped
-            // version of all ERC4626 tokens to the pool tokens list. Skipped for now - we need
-            // to test that the adapter supports it correctly and ERC4626 overwrites are handled
-            // correctly in simulation.
             let tokens = token_config
                 .into_iter()
                 .map(|t| t.0)
                 .collect::<Vec<_>>();
+            let underlying_tokens = collect_underlying_tokens(&tokens, buffer_assets);
 
             let fee_bytes = swap_fee_percentage.to_signed_bytes_be();
             let rate_providers_bytes = json_serialize_address_list(rate_providers.as_slice());
+            let underlying_tokens_bytes = json_serialize_address_list(underlying_tokens.as_slice());
 
             let mut attributes = vec![
                 ("pool_type", "StablePoolFactory".as_bytes()),
@@ -106,6 +278,14 @@ pub fn address_map(
                 attributes.push(("rate_providers", &rate_providers_bytes));
             }
 
+            // Boosted pools: expose the underlying tokens reachable through the Vault buffers.
+            if underlying_tokens
+                .iter()
+                .any(|token| token != &[0u8; 20])
+            {
+                attributes.push(("underlying_tokens", &underlying_tokens_bytes));
+            }
+
             Some(
                 ProtocolComponent::new(&format!("0x{}", hex::encode(&pool)))
                     .with_contracts(&[pool.to_owned(), VAULT_ADDRESS.to_vec()])
@@ -117,3 +297,31 @@ pub fn address_map(
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_attributes() {
+        let mut hooks_config =
+            (true, false, false, true, false, true, false, false, false, false, vec![7u8; 20]);
+
+        let attributes = hook_attributes(&hooks_config);
+        let value = |name: &str| {
+            attributes
+                .iter()
+                .find(|attr| attr.name == name)
+                .map(|attr| attr.value.clone())
+        };
+        assert_eq!(attributes.len(), 11);
+        assert_eq!(value("hooks"), Some(vec![7u8; 20]));
+        assert_eq!(value("hooks_enable_hook_adjusted_amounts"), Some(vec![1]));
+        assert_eq!(value("hooks_should_call_compute_dynamic_swap_fee"), Some(vec![1]));
+        assert_eq!(value("hooks_should_call_before_swap"), Some(vec![0]));
+        assert_eq!(value("hooks_should_call_after_swap"), Some(vec![1]));
+
+        hooks_config.10 = vec![0u8; 20];
+        assert!(hook_attributes(&hooks_config).is_empty());
+    }
+}
@@ -1,9 +1,17 @@
 #![allow(clippy::all)]
+pub mod crypto_pool;
 pub mod crypto_pool_factory;
 pub mod crypto_swap_ng_factory;
+pub mod crypto_swap_registry;
 pub mod erc20;
+pub mod main_registry;
 pub mod meta_pool_factory;
 pub mod meta_registry;
+pub mod multicall;
+pub mod pool;
 pub mod stableswap_factory;
+pub mod stableswap_ng_pool;
+pub mod steth;
 pub mod tricrypto_factory;
+pub mod tricrypto_ng_pool;
 pub mod twocrypto_factory;
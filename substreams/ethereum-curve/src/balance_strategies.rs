@@ -0,0 +1,598 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use substreams::{
+    scalar::BigInt,
+    store::{StoreGet, StoreGetArray, StoreGetBigInt, StoreGetInt64, StoreGetString},
+};
+use substreams_ethereum::{
+    pb::eth::v2::{Block, CallType, Log, TransactionTrace},
+    Event,
+};
+use tycho_substreams::prelude::*;
+
+use crate::{
+    abi,
+    consts::{ETH_ADDRESS, MULTICALL, NEW_SUSD, OLD_SUSD, STETH},
+};
+
+/// How the balance of a token held by a pool is tracked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BalanceStrategy {
+    /// Balances only change through `Transfer` events. This is the default.
+    Transfer,
+    /// Native ETH, tracked from the balance changes of the calls (see `emit_eth_deltas`).
+    NativeEth,
+    /// Rebasing tokens accounted in shares (Lido stETH-like). Balances change without `Transfer`
+    ///  events when the token rebases, so they are derived from the shares of the pools and the
+    ///  pooled ether per share (see `share_balance_changes`).
+    Shares,
+    /// Proxy emitting `Transfer` events on behalf of the canonical token.
+    Alias([u8; 20]),
+}
+
+const BALANCE_STRATEGIES: [([u8; 20], BalanceStrategy); 3] = [
+    (ETH_ADDRESS, BalanceStrategy::NativeEth),
+    (OLD_SUSD, BalanceStrategy::Alias(NEW_SUSD)),
+    (STETH, BalanceStrategy::Shares),
+];
+
+pub fn balance_strategy(token: &[u8]) -> BalanceStrategy {
+    BALANCE_STRATEGIES
+        .iter()
+        .find(|(address, _)| address == token)
+        .map(|(_, strategy)| *strategy)
+        .unwrap_or(BalanceStrategy::Transfer)
+}
+
+/// Returns the token balances should be attributed to, resolving aliases.
+pub fn canonical_token(token: &[u8]) -> Vec<u8> {
+    match balance_strategy(token) {
+        BalanceStrategy::Alias(canonical) => canonical.to_vec(),
+        _ => token.to_vec(),
+    }
+}
+
+/// Whether the balance of `token` is tracked from `Transfer` events in `map_relative_balances`.
+pub fn is_transfer_based(token: &[u8]) -> bool {
+    matches!(balance_strategy(token), BalanceStrategy::Transfer | BalanceStrategy::Alias(_))
+}
+
+/// Share price changes of share based tokens: rebases, and share mints and burns which happen on
+///  oracle reports (older Lido versions don't emit `TokenRebased`).
+fn is_rebase(log: &Log) -> bool {
+    abi::steth::events::TokenRebased::match_log(log) ||
+        abi::steth::events::SharesBurnt::match_log(log) ||
+        abi::steth::events::TransferShares::match_and_decode(log)
+            .is_some_and(|transfer| transfer.from == [0; 20] || transfer.to == [0; 20])
+}
+
+fn holds(tokens_store: &StoreGetString, pool: &[u8], token: &[u8]) -> bool {
+    tokens_store
+        .get_last(format!("pool:{}", hex::encode(pool)))
+        .is_some_and(|tokens| {
+            tokens
+                .split(':')
+                .any(|t| t == hex::encode(token))
+        })
+}
+
+/// Key of a share based token in `store_share_rates` and `store_first_rebase_blocks`.
+pub fn share_token_key(token: &[u8]) -> String {
+    format!("token:{}", hex::encode(token))
+}
+
+/// The `TokenRebased` events of share based tokens, with their ordinal and token.
+pub fn rebases(
+    block: &Block,
+) -> impl Iterator<Item = (u64, Vec<u8>, abi::steth::events::TokenRebased)> + '_ {
+    block
+        .transactions()
+        .flat_map(|tx| tx.logs_with_calls())
+        .filter(|(log, call)| {
+            !call.call.state_reverted && balance_strategy(&log.address) == BalanceStrategy::Shares
+        })
+        .filter_map(|(log, _)| {
+            Some((
+                log.ordinal,
+                log.address.clone(),
+                abi::steth::events::TokenRebased::match_and_decode(log)?,
+            ))
+        })
+}
+
+/// The pooled ether per share stored after a rebase, as `{total_pooled_ether}:{total_shares}`.
+pub fn share_rate(rebase: &abi::steth::events::TokenRebased) -> String {
+    format!("{}:{}", rebase.post_total_ether, rebase.post_total_shares)
+}
+
+/// Converts shares to a token balance like `getPooledEthByShares`, with a rate from `share_rate`.
+fn pooled_ether(shares: BigInt, rate: &str) -> Option<BigInt> {
+    let (total_ether, total_shares) = rate.split_once(':')?;
+    let total_shares = BigInt::try_from(total_shares.to_string()).ok()?;
+    if total_shares == BigInt::zero() {
+        return None;
+    }
+    Some(shares * BigInt::try_from(total_ether.to_string()).ok()? / total_shares)
+}
+
+/// The components emitted in a transaction that didn't deploy them, with the transaction. These are
+///  mostly registry pools, which are listed long after their deployment and may already hold
+///  liquidity, so their balances are read once instead of being tallied from zero.
+fn listed_components<'a>(
+    block: &'a Block,
+    components: &'a BlockChanges,
+) -> impl Iterator<Item = (&'a TransactionTrace, &'a Transaction, &'a ProtocolComponent)> {
+    components
+        .changes
+        .iter()
+        .filter_map(|tx_changes| {
+            let tx = tx_changes.tx.as_ref()?;
+            let trace = block
+                .transactions()
+                .find(|trace| trace.hash == tx.hash)?;
+            Some((trace, tx, tx_changes))
+        })
+        .flat_map(|(trace, tx, tx_changes)| {
+            tx_changes
+                .component_changes
+                .iter()
+                .filter(|component| {
+                    hex::decode(&component.id).is_ok_and(|pool| !is_deployment_tx(trace, &pool))
+                })
+                .map(move |component| (trace, tx, component))
+        })
+}
+
+fn is_deployment_tx(tx: &TransactionTrace, pool: &[u8]) -> bool {
+    tx.calls.iter().any(|call| {
+        !call.state_reverted && call.call_type() == CallType::Create && call.address == pool
+    })
+}
+
+/// Returns the pools bootstrapped in this block, see `listed_components`, and the snapshot of
+///  their transfer based and native ETH balances at the end of the block. The snapshot is attached
+///  to the end of the listing transaction and replaces the balance deltas of the pool in the block.
+///
+/// Native ETH is read with the Multicall `getEthBalance`, as the deltas from `emit_eth_deltas` are
+///  the actual balance of the pool, admin fees included. Share based tokens are snapshotted in
+///  `share_deltas`.
+pub fn bootstrap_balances(
+    block: &Block,
+    components: &BlockChanges,
+) -> Result<(HashSet<Vec<u8>>, Vec<BalanceDelta>)> {
+    let mut bootstrapped = HashSet::new();
+    let mut deltas = vec![];
+    for (trace, tx, component) in listed_components(block, components) {
+        let pool = hex::decode(&component.id)?;
+        for token in &component.tokens {
+            let balance = match balance_strategy(token) {
+                BalanceStrategy::Shares => continue,
+                BalanceStrategy::NativeEth => {
+                    abi::multicall::functions::GetEthBalance { addr: pool.clone() }
+                        .call(MULTICALL.to_vec())
+                }
+                BalanceStrategy::Transfer | BalanceStrategy::Alias(_) => {
+                    abi::erc20::functions::BalanceOf { owner: pool.clone() }.call(token.clone())
+                }
+            }
+            .ok_or_else(|| {
+                anyhow!(
+                    "Failed to snapshot the balance of 0x{} in pool 0x{}",
+                    hex::encode(token),
+                    component.id
+                )
+            })?;
+            deltas.push(BalanceDelta {
+                ord: trace.end_ordinal,
+                tx: Some(tx.clone()),
+                token: canonical_token(token),
+                delta: balance.to_signed_bytes_be(),
+                component_id: component.id.clone().into(),
+            });
+        }
+        bootstrapped.insert(component.id.as_bytes().to_vec());
+    }
+    Ok((bootstrapped, deltas))
+}
+
+/// Tracks the shares of share based tokens held by pools, from `TransferShares` events.
+///
+/// Tokens are only tracked from their first `TokenRebased` event on, as the pooled ether per share
+///  is not emitted before. The shares of the pools holding the token at that block are read once
+///  with `sharesOf`, and the share transfers of that block are skipped as the read includes them.
+///  Pools listed after their deployment (see `listed_components`) are read the same way in the
+///  block they are listed in.
+pub fn share_deltas(
+    block: &Block,
+    components: &BlockChanges,
+    tokens_store: &StoreGetString,
+    share_token_pools_store: &StoreGetArray<String>,
+    first_rebase_store: &StoreGetInt64,
+) -> Result<Vec<BalanceDelta>> {
+    let mut bootstrapped = HashSet::new();
+    let mut snapshots = vec![];
+    for (trace, tx, component) in listed_components(block, components) {
+        let pool = hex::decode(&component.id)?;
+        for token in component
+            .tokens
+            .iter()
+            .filter(|token| balance_strategy(token) == BalanceStrategy::Shares)
+        {
+            // Untracked tokens are read with `balanceOf`, and all pools are read on the first
+            //  rebase
+            let Some(first_rebase) = first_rebase_store.get_last(share_token_key(token)) else {
+                continue;
+            };
+            if first_rebase as u64 == block.number {
+                continue;
+            }
+            let shares = abi::steth::functions::SharesOf { account: pool.clone() }
+                .call(token.clone())
+                .ok_or_else(|| anyhow!("Failed to read the shares of pool 0x{}", component.id))?;
+            snapshots.push(BalanceDelta {
+                ord: trace.end_ordinal,
+                tx: Some(tx.clone()),
+                token: token.clone(),
+                delta: shares.to_signed_bytes_be(),
+                component_id: component.id.clone().into(),
+            });
+            bootstrapped.insert((component.id.as_bytes().to_vec(), token.clone()));
+        }
+    }
+
+    let mut deltas = vec![];
+    let mut snapshotted: Vec<Vec<u8>> = vec![];
+    for tx in block.transactions() {
+        let transaction = Transaction {
+            hash: tx.hash.clone(),
+            from: tx.from.clone(),
+            to: tx.to.clone(),
+            index: tx.index.into(),
+        };
+        for (log, _) in tx
+            .logs_with_calls()
+            .filter(|(_, call)| !call.call.state_reverted)
+        {
+            let token = &log.address;
+            if balance_strategy(token) != BalanceStrategy::Shares {
+                continue;
+            }
+            let Some(first_rebase) = first_rebase_store.get_last(share_token_key(token)) else {
+                continue;
+            };
+            let delta = |pool: &[u8], shares: BigInt| BalanceDelta {
+                ord: log.ordinal,
+                tx: Some(transaction.clone()),
+                token: token.clone(),
+                delta: shares.to_signed_bytes_be(),
+                component_id: hex::encode(pool).into(),
+            };
+
+            if first_rebase as u64 != block.number {
+                if let Some(transfer) = abi::steth::events::TransferShares::match_and_decode(log) {
+                    if transfer.from == transfer.to {
+                        continue;
+                    }
+                    if holds(tokens_store, &transfer.from, token) {
+                        deltas.push(delta(
+                            transfer.from.as_slice(),
+                            BigInt::zero() - transfer.shares_value.clone(),
+                        ));
+                    }
+                    if holds(tokens_store, &transfer.to, token) {
+                        deltas.push(delta(transfer.to.as_slice(), transfer.shares_value));
+                    }
+                }
+            } else if abi::steth::events::TokenRebased::match_log(log) &&
+                !snapshotted.contains(token)
+            {
+                snapshotted.push(token.clone());
+                for pool in share_token_pools_store
+                    .get_last(share_token_key(token))
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|pool| hex::decode(pool).ok())
+                {
+                    let shares = abi::steth::functions::SharesOf { account: pool.clone() }
+                        .call(token.clone())
+                        .ok_or_else(|| {
+                            anyhow!("Failed to read the shares of pool 0x{}", hex::encode(&pool))
+                        })?;
+                    deltas.push(delta(pool.as_slice(), shares));
+                }
+            }
+        }
+    }
+
+    deltas
+        .retain(|delta| !bootstrapped.contains(&(delta.component_id.clone(), delta.token.clone())));
+    deltas.extend(snapshots);
+    deltas.sort_by_key(|delta| delta.ord);
+    Ok(deltas)
+}
+
+/// The stores the balances of share based tokens are derived from.
+pub struct ShareStores<'a> {
+    /// Pools holding each share based token, from `store_share_token_pools`.
+    pub pools: &'a StoreGetArray<String>,
+    /// Block of the first `TokenRebased` event of each token, from `store_first_rebase_blocks`.
+    pub first_rebases: &'a StoreGetInt64,
+    /// Pooled ether per share after the last rebase, from `store_share_rates`.
+    pub rates: &'a StoreGetString,
+    /// Shares held by each pool, from `store_share_balances`.
+    pub balances: &'a StoreGetBigInt,
+}
+
+/// Computes the balances of share based tokens held by pools at the end of the block.
+///
+/// A balance is refreshed if the shares of the pool changed (see `share_deltas`), if the pool was
+///  created in this block, or if the token rebased, in which case all pools holding it are
+///  refreshed. Balances are attached to the last transaction that triggered the refresh.
+///
+/// Balances are the shares of the pool at the pooled ether per share of the last rebase. Before
+///  the first `TokenRebased` event of a token, the rate is unknown: balances are refreshed on any
+///  transfer of the pool or share price change instead, and read with `balanceOf`.
+///
+/// The rate is not refreshed between rebases: `Submitted` mints shares rounded down, and since
+///  Lido V2 `SharesBurnt` only happens within the oracle report emitting `TokenRebased`. Rounding
+///  down a mint raises the actual pooled ether per share by less than the rate over the total
+///  shares, so a balance lags `balanceOf` by less than the rate, i.e. about one wei, per submission
+///  since the last rebase.
+pub fn share_balance_changes(
+    block: &Block,
+    created_components: &BlockChanges,
+    share_deltas: &BlockBalanceDeltas,
+    tokens_store: &StoreGetString,
+    stores: &ShareStores,
+) -> Vec<(Transaction, BalanceChange)> {
+    let tracked = |token: &[u8]| {
+        stores
+            .first_rebases
+            .get_last(share_token_key(token))
+            .is_some()
+    };
+    let pools_holding = |token: &[u8]| {
+        stores
+            .pools
+            .get_last(share_token_key(token))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|pool| hex::decode(pool).ok())
+            .collect::<Vec<_>>()
+    };
+
+    let mut refreshes: Vec<(Transaction, Vec<u8>, Vec<u8>)> = vec![];
+    for tx in block.transactions() {
+        let transaction = Transaction {
+            hash: tx.hash.clone(),
+            from: tx.from.clone(),
+            to: tx.to.clone(),
+            index: tx.index.into(),
+        };
+        for (log, _) in tx
+            .logs_with_calls()
+            .filter(|(_, call)| !call.call.state_reverted)
+        {
+            if balance_strategy(&log.address) != BalanceStrategy::Shares {
+                continue;
+            }
+            let rebased = if tracked(&log.address) {
+                abi::steth::events::TokenRebased::match_log(log)
+            } else {
+                is_rebase(log)
+            };
+            if rebased {
+                pools_holding(&log.address)
+                    .into_iter()
+                    .for_each(|pool| {
+                        refreshes.push((transaction.clone(), pool, log.address.clone()))
+                    });
+            } else if tracked(&log.address) {
+                // Share transfers of tracked tokens are refreshed from `share_deltas`
+                continue;
+            } else if let Some(transfer) = abi::erc20::events::Transfer::match_and_decode(log) {
+                [transfer.from, transfer.to]
+                    .into_iter()
+                    .filter(|address| holds(tokens_store, address, &log.address))
+                    .for_each(|pool| {
+                        refreshes.push((transaction.clone(), pool, log.address.clone()))
+                    });
+            }
+        }
+    }
+
+    share_deltas
+        .balance_deltas
+        .iter()
+        .for_each(|delta| {
+            refreshes.push((
+                delta.tx.clone().unwrap(),
+                hex::decode(&delta.component_id).expect("component id should be hex"),
+                delta.token.clone(),
+            ))
+        });
+
+    created_components
+        .changes
+        .iter()
+        .for_each(|tx_changes| {
+            tx_changes
+                .component_changes
+                .iter()
+                .for_each(|component| {
+                    component
+                        .tokens
+                        .iter()
+                        .filter(|token| balance_strategy(token) == BalanceStrategy::Shares)
+                        .for_each(|token| {
+                            refreshes.push((
+                                tx_changes.tx.clone().unwrap(),
+                                hex::decode(&component.id).expect("component id should be hex"),
+                                token.clone(),
+                            ))
+                        })
+                })
+        });
+
+    let mut last_refresh: HashMap<(Vec<u8>, Vec<u8>), Transaction> = HashMap::new();
+    for (tx, pool, token) in refreshes {
+        last_refresh
+            .entry((pool, token))
+            .and_modify(|last_tx| {
+                if last_tx.index < tx.index {
+                    *last_tx = tx.clone();
+                }
+            })
+            .or_insert(tx);
+    }
+
+    last_refresh
+        .into_iter()
+        .sorted_unstable_by(|(a, _), (b, _)| a.cmp(b))
+        .filter_map(|((pool, token), tx)| {
+            let balance = if tracked(&token) {
+                let shares = stores
+                    .balances
+                    .get_last(format!("{}:{}", hex::encode(&pool), hex::encode(&token)))
+                    .unwrap_or_else(BigInt::zero);
+                pooled_ether(
+                    shares,
+                    &stores
+                        .rates
+                        .get_last(share_token_key(&token))?,
+                )?
+            } else {
+                abi::erc20::functions::BalanceOf { owner: pool.clone() }.call(token.clone())?
+            };
+            Some((
+                tx,
+                BalanceChange {
+                    token,
+                    balance: balance.to_signed_bytes_be(),
+                    component_id: format!("0x{}", hex::encode(pool)).into(),
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use substreams_ethereum::pb::eth::v2::{Call, TransactionTraceStatus};
+
+    use super::*;
+
+    #[test]
+    fn test_balance_strategy() {
+        assert_eq!(balance_strategy(&ETH_ADDRESS), BalanceStrategy::NativeEth);
+        assert_eq!(balance_strategy(&STETH), BalanceStrategy::Shares);
+        assert_eq!(balance_strategy(&OLD_SUSD), BalanceStrategy::Alias(NEW_SUSD));
+        assert_eq!(balance_strategy(&NEW_SUSD), BalanceStrategy::Transfer);
+        assert_eq!(balance_strategy(&[1; 20]), BalanceStrategy::Transfer);
+
+        assert!(is_transfer_based(&OLD_SUSD));
+        assert!(is_transfer_based(&[1; 20]));
+        assert!(!is_transfer_based(&STETH));
+        assert!(!is_transfer_based(&ETH_ADDRESS));
+    }
+
+    #[test]
+    fn test_canonical_token() {
+        assert_eq!(canonical_token(&OLD_SUSD), NEW_SUSD.to_vec());
+        assert_eq!(canonical_token(&NEW_SUSD), NEW_SUSD.to_vec());
+        assert_eq!(canonical_token(&STETH), STETH.to_vec());
+    }
+
+    fn steth_log(topic: &str, topics: Vec<Vec<u8>>, data: Vec<u8>) -> Log {
+        Log {
+            address: STETH.to_vec(),
+            topics: [vec![hex::decode(topic).unwrap()], topics].concat(),
+            data,
+            ..Default::default()
+        }
+    }
+
+    fn word(value: u64) -> Vec<u8> {
+        [vec![0u8; 24], value.to_be_bytes().to_vec()].concat()
+    }
+
+    fn transfer_shares(from: [u8; 20], to: [u8; 20]) -> Log {
+        // `TransferShares(address indexed from, address indexed to, uint256 sharesValue)`
+        steth_log(
+            "9d9c909296d9c674451c0c24f02cb64981eb3b727f99865939192f880a755dcb",
+            vec![[vec![0u8; 12], from.to_vec()].concat(), [vec![0u8; 12], to.to_vec()].concat()],
+            word(1),
+        )
+    }
+
+    #[test]
+    fn test_is_rebase() {
+        // `TokenRebased(uint256 indexed reportTimestamp, uint256 timeElapsed, uint256
+        //  preTotalShares, uint256 preTotalEther, uint256 postTotalShares, uint256 postTotalEther,
+        //  uint256 sharesMintedAsFees)`
+        let token_rebased = steth_log(
+            "ff08c3ef606d198e316ef5b822193c489965899eb4e3c248cea1a4626c3eda50",
+            vec![word(1)],
+            (0..6).flat_map(word).collect(),
+        );
+        assert!(is_rebase(&token_rebased));
+
+        // Mints and burns change the share price on oracle reports of older versions
+        assert!(is_rebase(&transfer_shares([0; 20], [1; 20])));
+        assert!(is_rebase(&transfer_shares([1; 20], [0; 20])));
+        assert!(!is_rebase(&transfer_shares([1; 20], [2; 20])));
+    }
+
+    #[test]
+    fn test_listed_components() {
+        let tx = |hash: u8, calls: Vec<Call>| TransactionTrace {
+            hash: vec![hash; 32],
+            status: TransactionTraceStatus::Succeeded.into(),
+            calls,
+            ..Default::default()
+        };
+        let create = |address: [u8; 20]| Call {
+            call_type: CallType::Create.into(),
+            address: address.to_vec(),
+            ..Default::default()
+        };
+        let block = Block {
+            transaction_traces: vec![
+                tx(1, vec![create([1; 20])]),
+                tx(2, vec![create([3; 20]), Call { state_reverted: true, ..create([2; 20]) }]),
+            ],
+            ..Default::default()
+        };
+        let tx_changes = |hash: u8, pools: &[[u8; 20]]| TransactionChanges {
+            tx: Some(Transaction { hash: vec![hash; 32], ..Default::default() }),
+            component_changes: pools
+                .iter()
+                .map(|pool| ProtocolComponent { id: hex::encode(pool), ..Default::default() })
+                .collect(),
+            ..Default::default()
+        };
+        let components = BlockChanges {
+            block: None,
+            changes: vec![tx_changes(1, &[[1; 20]]), tx_changes(2, &[[1; 20], [2; 20]])],
+        };
+
+        // Pools are listed if their transaction didn't deploy them
+        let listed: Vec<_> = listed_components(&block, &components)
+            .map(|(_, tx, component)| (tx.hash[0], component.id.clone()))
+            .collect();
+        assert_eq!(listed, vec![(2, hex::encode([1; 20])), (2, hex::encode([2; 20]))]);
+    }
+
+    #[test]
+    fn test_pooled_ether() {
+        let rate = "9000000000000000000000000:8000000000000000000000000";
+
+        assert_eq!(pooled_ether(BigInt::from(8), rate), Some(BigInt::from(9)));
+        // Rounded down like `getPooledEthByShares`
+        assert_eq!(pooled_ether(BigInt::from(7), rate), Some(BigInt::from(7)));
+        assert_eq!(pooled_ether(BigInt::from(7), "1:0"), None);
+        assert_eq!(pooled_ether(BigInt::from(7), "invalid"), None);
+    }
+}
@@ -1,7 +1,10 @@
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 mod abi;
+mod balance_strategies;
 mod consts;
 pub mod modules;
+mod plain_pools;
 mod pool_changes;
 mod pool_factories;
 mod pools;
+mod registries;
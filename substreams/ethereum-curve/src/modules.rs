@@ -2,21 +2,33 @@ use std::collections::HashMap;
 
 use anyhow::Result;
 use itertools::Itertools;
+use serde::Deserialize;
 use substreams::{
     pb::substreams::StoreDeltas,
     scalar::BigInt,
     store::{
-        StoreAddBigInt, StoreGet, StoreGetInt64, StoreGetString, StoreNew, StoreSet, StoreSetInt64,
-        StoreSetString,
+        StoreAddBigInt, StoreAppend, StoreGet, StoreGetArray, StoreGetBigInt, StoreGetInt64,
+        StoreGetString, StoreNew, StoreSet, StoreSetIfNotExists, StoreSetIfNotExistsInt64,
+        StoreSetInt64, StoreSetString,
     },
 };
-use substreams_ethereum::pb::eth;
+use substreams_ethereum::pb::eth::{self, v2::CallType};
 
 use crate::{
-    consts::{CONTRACTS_TO_INDEX, NEW_SUSD, OLD_SUSD},
-    pool_changes::emit_eth_deltas,
+    balance_strategies::{
+        balance_strategy, bootstrap_balances, canonical_token, is_transfer_based, rebases,
+        share_balance_changes, share_deltas, share_rate, share_token_key, BalanceStrategy,
+        ShareStores,
+    },
+    consts::{CONTRACTS_TO_INDEX, FACTORIES},
+    plain_pools,
+    pool_changes::{
+        add_creation_attributes, attribute_key, emit_eth_deltas, emit_pool_attributes,
+        pool_attribute_changes,
+    },
     pool_factories,
-    pools::emit_specific_pools,
+    pools::{emit_specific_pools, override_addresses, parse_params},
+    registries,
 };
 use tycho_substreams::{
     balances::{extract_balance_deltas_from_tx, store_balance_changes},
@@ -35,9 +47,51 @@ impl PartialEq for TransactionWrapper {
     }
 }
 
+#[derive(Deserialize)]
+struct ProtocolChangesParams {
+    /// Emit plain StableSwap pools as native components, with their state as entity attributes,
+    /// instead of indexing their contract storage.
+    #[serde(default)]
+    native_plain_pools: bool,
+}
+
+/// Stores the ordinal of the first registry `PoolAdded` event of every pool. Pools can be removed
+///  from and re-added to a registry, or be listed by several registries, but must only be emitted
+///  once. Contracts deployed by one of the indexed factories are stored first with `-1`, as they
+///  are emitted at creation.
+#[substreams::handlers::store]
+pub fn store_registry_pools(block: eth::v2::Block, store: StoreSetIfNotExistsInt64) {
+    block
+        .transactions()
+        .flat_map(|tx| &tx.calls)
+        .filter(|call| {
+            !call.state_reverted &&
+                matches!(call.call_type(), CallType::Create) &&
+                FACTORIES.contains(&call.caller.as_slice())
+        })
+        .for_each(|call| {
+            store.set_if_not_exists(call.begin_ordinal, hex::encode(&call.address), &-1)
+        });
+
+    block
+        .logs()
+        .filter_map(|log| Some((registries::pool_added(log.log)?, log.ordinal())))
+        .for_each(|(pool, ordinal)| {
+            store.set_if_not_exists(ordinal, hex::encode(pool), &(ordinal as i64))
+        });
+}
+
 #[substreams::handlers::map]
 // Map all created components and their related entity changes.
-pub fn map_components(params: String, block: eth::v2::Block) -> Result<BlockChanges> {
+pub fn map_components(
+    params: String,
+    block: eth::v2::Block,
+    registry_pools_store: StoreGetInt64,
+) -> Result<BlockChanges> {
+    let pools = parse_params(&params)
+        .expect("An unexpected error occurred when parsing params for emitting specific pools");
+    let overrides = override_addresses(&pools);
+
     let changes = block
         .transactions()
         .filter_map(|tx| {
@@ -58,18 +112,37 @@ pub fn map_components(params: String, block: eth::v2::Block) -> Result<BlockChan
                     call.call,
                     tx,
                 ) {
+                    add_creation_attributes(log, &component.id, &mut state);
                     entity_changes.append(&mut state);
                     components.push(component);
                 }
             }
 
-            if let Some((component, mut state)) = emit_specific_pools(&params, tx).expect(
-                "An unexpected error occurred when parsing params for emitting specific pools",
-            ) {
+            if let Some((component, mut state)) = emit_specific_pools(&pools, tx)
+                .expect("An unexpected error occurred when emitting specific pools")
+            {
                 entity_changes.append(&mut state);
                 components.push(component);
             }
 
+            for (log, call) in tx
+                .logs_with_calls()
+                .filter(|(_, call)| !call.call.state_reverted)
+            {
+                let first_added = registries::pool_added(log).is_some_and(|pool| {
+                    registry_pools_store.get_last(hex::encode(pool)) == Some(log.ordinal as i64)
+                });
+                if !first_added {
+                    continue;
+                }
+                if let Some((component, mut state)) =
+                    registries::address_map(log, call.call, tx, &overrides)
+                {
+                    entity_changes.append(&mut state);
+                    components.push(component);
+                }
+            }
+
             if components.is_empty() {
                 None
             } else {
@@ -132,16 +205,150 @@ pub fn store_non_component_accounts(map: BlockChanges, store: StoreSetInt64) {
                     .expect("didn't find lp_token attribute");
                 store.set(0, hex::encode(lp_token), &1);
             }
+
+            // Metapools route `exchange_underlying` through their base pool, so the base pool and
+            // its LP token need to be indexed even if the base pool isn't a component itself
+            if component.has_attributes(&[("pool_type", "metapool".into())]) {
+                component
+                    .contracts
+                    .iter()
+                    .filter(|contract| hex::encode(contract) != component.id)
+                    .for_each(|contract| store.set(0, hex::encode(contract), &1));
+            }
+        });
+}
+
+/// Stores the pools holding each share based token, so that their balances can be refreshed when
+///  the token rebases.
+#[substreams::handlers::store]
+pub fn store_share_token_pools(map: BlockChanges, store: StoreAppend<String>) {
+    map.changes
+        .iter()
+        .flat_map(|tx_changes| &tx_changes.component_changes)
+        .for_each(|component| {
+            component
+                .tokens
+                .iter()
+                .filter(|token| balance_strategy(token) == BalanceStrategy::Shares)
+                .for_each(|token| {
+                    store.append(0, format!("token:{}", hex::encode(token)), component.id.clone())
+                });
+        });
+}
+
+/// Stores the pooled ether per share of share based tokens after each rebase.
+#[substreams::handlers::store]
+pub fn store_share_rates(block: eth::v2::Block, store: StoreSetString) {
+    rebases(&block).for_each(|(ordinal, token, rebase)| {
+        store.set(ordinal, share_token_key(&token), &share_rate(&rebase))
+    });
+}
+
+/// Stores the block of the first `TokenRebased` event of share based tokens, from which on their
+///  pool balances are derived from shares.
+#[substreams::handlers::store]
+pub fn store_first_rebase_blocks(block: eth::v2::Block, store: StoreSetIfNotExistsInt64) {
+    rebases(&block).for_each(|(ordinal, token, _)| {
+        store.set_if_not_exists(ordinal, share_token_key(&token), &(block.number as i64))
+    });
+}
+
+/// Maps the share changes of pools holding share based tokens, see `share_deltas`.
+#[substreams::handlers::map]
+pub fn map_share_deltas(
+    block: eth::v2::Block,
+    components: BlockChanges,
+    tokens_store: StoreGetString,
+    share_token_pools_store: StoreGetArray<String>,
+    first_rebase_store: StoreGetInt64,
+) -> Result<BlockBalanceDeltas> {
+    Ok(BlockBalanceDeltas {
+        balance_deltas: share_deltas(
+            &block,
+            &components,
+            &tokens_store,
+            &share_token_pools_store,
+            &first_rebase_store,
+        )?,
+    })
+}
+
+/// Tallies the shares held by pools, keyed by pool and token like `store_balances`.
+#[substreams::handlers::store]
+pub fn store_share_balances(deltas: BlockBalanceDeltas, store: StoreAddBigInt) {
+    store_balance_changes(deltas, store)
+}
+
+/// Stores the plain StableSwap pools whose state is fully decoded, which can be indexed natively.
+#[substreams::handlers::store]
+pub fn store_plain_pools(map: BlockChanges, store: StoreSetString) {
+    map.changes
+        .iter()
+        .for_each(|tx_changes| {
+            tx_changes
+                .component_changes
+                .iter()
+                .filter(|component| {
+                    plain_pools::is_native_candidate(component, &tx_changes.entity_changes)
+                })
+                .for_each(|component| {
+                    store.set(0, format!("pool:{0}", component.id), &plain_pools::NG.to_string());
+                });
+        });
+}
+
+/// Stores the ordinal of the first write of every dynamic pool attribute, so that it can be
+///  emitted as `Creation` once and as `Update` afterwards. Attributes emitted at component creation
+///  are stored first with `-1`.
+#[substreams::handlers::store]
+pub fn store_pool_attributes(
+    block: eth::v2::Block,
+    map: BlockChanges,
+    tokens_store: StoreGetString,
+    store: StoreSetIfNotExistsInt64,
+) {
+    map.changes
+        .iter()
+        .flat_map(|tx_changes| &tx_changes.entity_changes)
+        .for_each(|entity_change| {
+            let Ok(pool) = hex::decode(
+                entity_change
+                    .component_id
+                    .trim_start_matches("0x"),
+            ) else {
+                return;
+            };
+            entity_change
+                .attributes
+                .iter()
+                .filter(|attribute| attribute.change == ChangeType::Creation as i32)
+                .for_each(|attribute| {
+                    store.set_if_not_exists(0, attribute_key(&pool, &attribute.name), &-1)
+                });
+        });
+
+    block
+        .transactions()
+        .flat_map(|tx| pool_attribute_changes(tx, &tokens_store))
+        .for_each(|(ordinal, pool, attributes)| {
+            attributes.iter().for_each(|(name, _)| {
+                store.set_if_not_exists(ordinal, attribute_key(&pool, name), &(ordinal as i64))
+            });
         });
 }
 
 /// Since the `PoolBalanceChanged` events administer only deltas, we need to leverage a map and a
 ///  store to be able to tally up final balances for tokens in a pool.
+///
+/// Pools listed after their deployment are bootstrapped with a snapshot of their balances instead,
+///  see `bootstrap_balances`.
 #[substreams::handlers::map]
 pub fn map_relative_balances(
     block: eth::v2::Block,
+    components: BlockChanges,
     tokens_store: StoreGetString,
 ) -> Result<BlockBalanceDeltas, anyhow::Error> {
+    let (bootstrapped, snapshots) = bootstrap_balances(&block, &components)?;
     Ok(BlockBalanceDeltas {
         balance_deltas: {
             let mut deltas: Vec<_> = block
@@ -151,13 +358,12 @@ pub fn map_relative_balances(
                         .into_iter()
                         .chain(
                             extract_balance_deltas_from_tx(tx, |token, transactor| {
+                                if !is_transfer_based(token) {
+                                    return false;
+                                }
                                 let pool_key = format!("pool:{}", hex::encode(transactor));
                                 if let Some(tokens) = tokens_store.get_last(pool_key) {
-                                    let token_id = if token == OLD_SUSD {
-                                        hex::encode(NEW_SUSD)
-                                    } else {
-                                        hex::encode(token)
-                                    };
+                                    let token_id = hex::encode(canonical_token(token));
                                     tokens.split(':').any(|t| t == token_id)
                                 } else {
                                     false
@@ -165,14 +371,14 @@ pub fn map_relative_balances(
                             })
                             .into_iter()
                             .map(|mut balance| {
-                                if balance.token == OLD_SUSD {
-                                    balance.token = NEW_SUSD.into();
-                                }
+                                balance.token = canonical_token(&balance.token);
                                 balance
                             })
                             .collect::<Vec<_>>(),
                         )
                 })
+                .filter(|delta| !bootstrapped.contains(&delta.component_id))
+                .chain(snapshots)
                 .collect();
 
             // Keep it consistent with how it's inserted in the store. This step is important
@@ -199,17 +405,48 @@ pub fn store_balances(deltas: BlockBalanceDeltas, store: StoreAddBigInt) {
 /// `BlockContractChanges` is ordered by transactions properly.
 #[substreams::handlers::map]
 pub fn map_protocol_changes(
+    params: String,
     block: eth::v2::Block,
     grouped_components: BlockChanges,
     deltas: BlockBalanceDeltas,
+    share_balance_deltas: BlockBalanceDeltas,
     components_store: StoreGetString,
     non_component_accounts_store: StoreGetInt64,
+    plain_pools_store: StoreGetString,
+    share_token_pools_store: StoreGetArray<String>,
+    first_rebase_store: StoreGetInt64,
+    share_rates_store: StoreGetString,
+    share_balances_store: StoreGetBigInt,
+    pool_attributes_store: StoreGetInt64,
     balance_store: StoreDeltas, // Note, this map module is using the `deltas` mode for the store.
 ) -> Result<BlockChanges> {
+    let params: ProtocolChangesParams = serde_qs::from_str(&params)?;
+    let is_native = |addr: &[u8]| {
+        params.native_plain_pools &&
+            plain_pools_store
+                .get_last(format!("pool:{0}", hex::encode(addr)))
+                .is_some()
+    };
+
     // We merge contract changes by transaction (identified by transaction index) making it easy to
     //  sort them at the very end.
     let mut transaction_changes: HashMap<_, TransactionChanges> = HashMap::new();
 
+    // Share based tokens (e.g. stETH) aren't tracked by `map_relative_balances`, their balances
+    //  are derived from the shares of the pools at the end of the block.
+    let share_balances = share_balance_changes(
+        &block,
+        &grouped_components,
+        &share_balance_deltas,
+        &components_store,
+        &ShareStores {
+            pools: &share_token_pools_store,
+            first_rebases: &first_rebase_store,
+            rates: &share_rates_store,
+            balances: &share_balances_store,
+        },
+    );
+
     // `ProtocolComponents` are gathered with some entity changes from `map_pools_created` which
     // just need a bit of work to  convert into `TransactionChanges`
     grouped_components
@@ -227,10 +464,18 @@ pub fn map_protocol_changes(
                     entity_changes: vec![],
                 });
 
+            let entity_changes = tx_changes.entity_changes;
             let formatted_components: Vec<_> = tx_changes //TODO: format directly at creation
                 .component_changes
                 .into_iter()
                 .map(|mut component| {
+                    if params.native_plain_pools &&
+                        plain_pools::is_native_candidate(&component, &entity_changes)
+                    {
+                        component.protocol_type = Some(plain_pools::native_protocol_type());
+                        component.contracts.clear();
+                    }
+                    pool_factories::add_underlying_coins(&mut component, &components_store);
                     component.id = format!("0x{}", component.id);
                     component
                 })
@@ -241,7 +486,7 @@ pub fn map_protocol_changes(
                 .extend(formatted_components);
             transaction_entry
                 .entity_changes
-                .extend(tx_changes.entity_changes);
+                .extend(entity_changes);
         });
 
     // Balance changes are gathered by the `StoreDelta` based on `TokenExchange`, etc. creating
@@ -290,15 +535,76 @@ pub fn map_protocol_changes(
                 .extend(group.map(|(_, change)| change));
         });
 
+    share_balances
+        .into_iter()
+        .for_each(|(tx, balance_change)| {
+            transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChanges {
+                    tx: Some(tx.clone()),
+                    contract_changes: vec![],
+                    component_changes: vec![],
+                    balance_changes: vec![],
+                    entity_changes: vec![],
+                })
+                .balance_changes
+                .push(balance_change);
+        });
+
+    // Native pools hold their admin fees along with their coins, their admin balances are read at
+    //  the end of the block.
+    if params.native_plain_pools {
+        plain_pools::admin_balance_changes(&block, &is_native, &components_store)?
+            .into_iter()
+            .for_each(|(tx, entity_change)| {
+                transaction_changes
+                    .entry(tx.index)
+                    .or_insert_with(|| TransactionChanges {
+                        tx: Some(tx.clone()),
+                        contract_changes: vec![],
+                        component_changes: vec![],
+                        balance_changes: vec![],
+                        entity_changes: vec![],
+                    })
+                    .entity_changes
+                    .push(entity_change);
+            });
+    }
+
+    // Admin actions (A ramps, fee changes and kills) are surfaced as entity attributes so that
+    //  pools can be filtered without a VM call.
+    block.transactions().for_each(|tx| {
+        let entity_changes = emit_pool_attributes(tx, &components_store, &pool_attributes_store);
+        if !entity_changes.is_empty() {
+            transaction_changes
+                .entry(tx.index.into())
+                .or_insert_with(|| TransactionChanges {
+                    tx: Some(Transaction {
+                        hash: tx.hash.clone(),
+                        from: tx.from.clone(),
+                        to: tx.to.clone(),
+                        index: tx.index.into(),
+                    }),
+                    contract_changes: vec![],
+                    component_changes: vec![],
+                    balance_changes: vec![],
+                    entity_changes: vec![],
+                })
+                .entity_changes
+                .extend(entity_changes);
+        }
+    });
+
     // General helper for extracting contract changes. Uses block, our component store which holds
     //  all of our tracked deployed pool addresses, and the map of tx contract changes which we
     //  output into for final processing later.
     extract_contract_changes(
         &block,
         |addr| {
-            components_store
+            (components_store
                 .get_last(format!("pool:{0}", hex::encode(addr)))
-                .is_some() ||
+                .is_some() &&
+                !is_native(addr)) ||
                 non_component_accounts_store
                     .get_last(hex::encode(addr))
                     .is_some() ||
@@ -342,7 +648,8 @@ pub fn map_protocol_changes(
             .filter_map(|(_, change)| {
                 if change.contract_changes.is_empty() &&
                     change.component_changes.is_empty() &&
-                    change.balance_changes.is_empty()
+                    change.balance_changes.is_empty() &&
+                    change.entity_changes.is_empty()
                 {
                     None
                 } else {
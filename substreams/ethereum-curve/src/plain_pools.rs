@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use substreams::{
+    scalar::BigInt,
+    store::{StoreGet, StoreGetString},
+};
+use substreams_ethereum::pb::eth::v2::{Block, Call, CallType, TransactionTrace};
+use tycho_substreams::prelude::*;
+
+use crate::{
+    abi::{
+        crypto_swap_ng_factory::functions::DeployPlainPool, erc20::functions::Decimals,
+        stableswap_ng_pool::functions::AdminBalances,
+    },
+    consts::A_PRECISION,
+};
+
+/// Plain pools deployed by the StableSwap-NG factory, the only ones indexed natively.
+pub const NG: &str = "ng";
+
+/// Returns whether `component` can be simulated natively, i.e. whether its whole state was decoded
+///  at creation by [`ng_creation_state`]: StableSwap-NG plain pools of standard coins.
+///
+/// Other plain pools are kept in VM mode: old pools only expose `balances(int128)`, lending pools
+///  and NG pools with oracle, ERC4626 or rebasing coins have rates that move on every block.
+///
+/// TODO: plain pools of the legacy factories (`meta_pool_factory`, `stable_swap_factory`) could be
+///  decoded the same way from their `deploy_plain_pool` call, but they have no
+///  `offpeg_fee_multiplier` and ship several implementations (ETH, optimized, rebasing coins), so
+///  they need their own native protocol type. They stay in VM mode until it exists.
+pub fn is_native_candidate(
+    component: &ProtocolComponent,
+    entity_changes: &[EntityChanges],
+) -> bool {
+    if !component.has_attributes(&[
+        ("pool_type", "plain_pool".into()),
+        ("factory_name", "crypto_swap_ng_factory".into()),
+    ]) {
+        return false;
+    }
+    let rates = entity_changes
+        .iter()
+        .filter(|change| {
+            change
+                .component_id
+                .trim_start_matches("0x") ==
+                component.id
+        })
+        .flat_map(|change| &change.attributes)
+        .filter(|attribute| attribute.name.starts_with("rate_"))
+        .count();
+    rates == component.tokens.len()
+}
+
+/// The protocol type of plain pools indexed in native mode.
+pub fn native_protocol_type() -> ProtocolType {
+    ProtocolType {
+        name: "curve_stableswap".into(),
+        financial_type: FinancialType::Swap.into(),
+        attribute_schema: Vec::new(),
+        implementation_type: ImplementationType::Custom.into(),
+    }
+}
+
+/// Decodes the state of a StableSwap-NG plain pool from its `deploy_plain_pool` call:
+///  - `initial_a`, `future_a`, `initial_a_time` and `future_a_time`: pools start without a ramp
+///  - `fee` and `offpeg_fee_multiplier`
+///  - `rate_{i}`: `10 ** (36 - decimals)`, from the `decimals()` calls the factory makes on the
+///    coins
+///  - `admin_balance_{i}`: `0`, see [`admin_balance_changes`]
+///
+/// Rates are only emitted if every coin is a standard ERC20 and its decimals were read by the
+///  factory. Later changes are decoded from `RampA`, `StopRampA` and `ApplyNewFee`, and the
+///  balances of the pool are the component balances minus the admin balances.
+pub fn ng_creation_state(
+    deploy: &DeployPlainPool,
+    call: &Call,
+    tx: &TransactionTrace,
+) -> Vec<Attribute> {
+    let a = deploy.a.clone() * BigInt::from(A_PRECISION);
+    let mut state = vec![
+        ("initial_a".to_string(), a.clone()),
+        ("future_a".to_string(), a),
+        ("initial_a_time".to_string(), BigInt::zero()),
+        ("future_a_time".to_string(), BigInt::zero()),
+        ("fee".to_string(), deploy.fee.clone()),
+        ("offpeg_fee_multiplier".to_string(), deploy.offpeg_fee_multiplier.clone()),
+    ];
+
+    let decimals = deploy
+        .coins
+        .iter()
+        .map(|coin| coin_decimals(coin, call, tx))
+        .collect::<Vec<_>>();
+    state.extend((0..deploy.coins.len()).map(|i| (format!("admin_balance_{i}"), BigInt::zero())));
+    if let Some(rates) = standard_rates(&deploy.asset_types, &decimals) {
+        state.extend(
+            rates
+                .into_iter()
+                .enumerate()
+                .map(|(i, rate)| (format!("rate_{i}"), rate)),
+        );
+    }
+
+    state
+        .into_iter()
+        .map(|(name, value)| Attribute {
+            name,
+            value: value.to_signed_bytes_be(),
+            change: ChangeType::Creation.into(),
+        })
+        .collect()
+}
+
+/// Reads the admin balances of the native pools called in this block, as `admin_balance_{i}`.
+///
+/// NG pools keep the admin fees they charge along with the coins of the pool until they are
+///  withdrawn, so the component balances, which are the coin balances of the pool, include them.
+///  Admin balances change on every swap and liquidity change and are reset by
+///  `withdraw_admin_fees`, which emits no pool event, so they are read with `admin_balances(i)` at
+///  the end of the block and attached to the last transaction calling the pool.
+pub fn admin_balance_changes(
+    block: &Block,
+    is_native: impl Fn(&[u8]) -> bool,
+    tokens_store: &StoreGetString,
+) -> Result<Vec<(Transaction, EntityChanges)>> {
+    let mut last_call: HashMap<Vec<u8>, Transaction> = HashMap::new();
+    for tx in block.transactions() {
+        tx.calls
+            .iter()
+            .filter(|call| {
+                !call.state_reverted &&
+                    call.call_type() != CallType::Create &&
+                    is_native(&call.address)
+            })
+            .for_each(|call| {
+                last_call.insert(
+                    call.address.clone(),
+                    Transaction {
+                        hash: tx.hash.clone(),
+                        from: tx.from.clone(),
+                        to: tx.to.clone(),
+                        index: tx.index.into(),
+                    },
+                );
+            });
+    }
+
+    last_call
+        .into_iter()
+        .sorted_unstable_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(pool, tx)| {
+            let n_coins = tokens_store
+                .get_last(format!("pool:{}", hex::encode(&pool)))
+                .map(|tokens| tokens.split(':').count())
+                .unwrap_or_default();
+            let attributes = (0..n_coins)
+                .map(|i| {
+                    let admin_balance = AdminBalances { arg0: BigInt::from(i as u64) }
+                        .call(pool.clone())
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "Failed to read admin balance {i} of pool 0x{}",
+                                hex::encode(&pool)
+                            )
+                        })?;
+                    Ok(Attribute {
+                        name: format!("admin_balance_{i}"),
+                        value: admin_balance.to_signed_bytes_be(),
+                        change: ChangeType::Update.into(),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok((
+                tx,
+                EntityChanges { component_id: format!("0x{}", hex::encode(&pool)), attributes },
+            ))
+        })
+        .collect()
+}
+
+/// Returns the decimals of `coin` read by the factory during the `deploy` call.
+fn coin_decimals(coin: &[u8], deploy: &Call, tx: &TransactionTrace) -> Option<u64> {
+    tx.calls
+        .iter()
+        .filter(|call| call.parent_index == deploy.index && !call.state_reverted)
+        .find(|call| call.address == coin && Decimals::match_call(call))
+        .and_then(|call| Decimals::output(&call.return_data).ok())
+        .map(|decimals| decimals.to_u64())
+}
+
+/// Returns the rates of the coins if they are all standard ERC20s (asset type `0`) with known
+///  decimals.
+fn standard_rates(asset_types: &[BigInt], decimals: &[Option<u64>]) -> Option<Vec<BigInt>> {
+    if asset_types.len() != decimals.len() ||
+        asset_types
+            .iter()
+            .any(|asset_type| *asset_type != BigInt::zero())
+    {
+        return None;
+    }
+    decimals
+        .iter()
+        .map(|decimals| Some(BigInt::from(10).pow(36u32.checked_sub((*decimals)? as u32)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_rates() {
+        let standard = vec![BigInt::zero(), BigInt::zero()];
+
+        assert_eq!(
+            standard_rates(&standard, &[Some(18), Some(6)]),
+            Some(vec![BigInt::from(10).pow(18), BigInt::from(10).pow(30)])
+        );
+        // Missing decimals
+        assert_eq!(standard_rates(&standard, &[Some(18), None]), None);
+        // Oracle coin
+        assert_eq!(standard_rates(&[BigInt::zero(), BigInt::from(1)], &[Some(18), Some(18)]), None);
+        // Coins and asset types don't match
+        assert_eq!(standard_rates(&standard, &[Some(18)]), None);
+    }
+
+    fn component(factory_name: &str) -> ProtocolComponent {
+        ProtocolComponent {
+            id: "aa".repeat(20),
+            tokens: vec![vec![1; 20], vec![2; 20]],
+            static_att: vec![
+                Attribute {
+                    name: "pool_type".into(),
+                    value: "plain_pool".into(),
+                    change: ChangeType::Creation.into(),
+                },
+                Attribute {
+                    name: "factory_name".into(),
+                    value: factory_name.into(),
+                    change: ChangeType::Creation.into(),
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn rates(component_id: &str, n: usize) -> Vec<EntityChanges> {
+        vec![EntityChanges {
+            component_id: format!("0x{component_id}"),
+            attributes: (0..n)
+                .map(|i| Attribute {
+                    name: format!("rate_{i}"),
+                    value: vec![1],
+                    change: ChangeType::Creation.into(),
+                })
+                .collect(),
+        }]
+    }
+
+    #[test]
+    fn test_is_native_candidate() {
+        let ng = component("crypto_swap_ng_factory");
+        assert!(is_native_candidate(&ng, &rates(&ng.id, 2)));
+        // Rates unknown
+        assert!(!is_native_candidate(&ng, &rates(&ng.id, 1)));
+        assert!(!is_native_candidate(&ng, &rates(&"bb".repeat(20), 2)));
+
+        let legacy = component("stable_swap_factory");
+        assert!(!is_native_candidate(&legacy, &rates(&legacy.id, 2)));
+    }
+}
@@ -1,11 +1,25 @@
 use substreams::{
     scalar::BigInt,
-    store::{StoreGet, StoreGetString},
+    store::{StoreGet, StoreGetInt64, StoreGetString},
+};
+use substreams_ethereum::{
+    pb::eth::v2::{Log, TransactionTrace},
+    Event,
 };
-use substreams_ethereum::pb::eth::v2::TransactionTrace;
 use tycho_substreams::prelude::*;
 
-use crate::consts::ETH_ADDRESS;
+use crate::{
+    abi::{
+        crypto_pool, crypto_pool_factory, crypto_swap_ng_factory, meta_pool_factory, pool,
+        stableswap_factory, stableswap_ng_pool, tricrypto_factory, tricrypto_ng_pool,
+        twocrypto_factory,
+    },
+    consts::{A_PRECISION, ETH_ADDRESS},
+};
+
+/// Attribute changes of a pool: the ordinal they happened at, the pool address and the changed
+///  attributes.
+pub type PoolAttributeChange = (u64, Vec<u8>, Vec<(&'static str, Vec<u8>)>);
 
 fn get_pool_tokens(pool_address: &Vec<u8>, tokens_store: &StoreGetString) -> Option<Vec<String>> {
     let pool_key = format!("pool:{}", hex::encode(pool_address));
@@ -77,3 +91,498 @@ pub fn emit_eth_deltas(tx: &TransactionTrace, tokens_store: &StoreGetString) ->
         })
         .collect::<Vec<_>>()
 }
+
+/// Decodes the initial dynamic attributes of a pool from the factory event that deployed it, so
+///  that every factory pool has them from creation on:
+///  - StableSwap pools: `initial_a`, `future_a` (`A * 100`), `initial_a_time`, `future_a_time`
+///    (`0`) and `fee`
+///  - Crypto pools: `initial_a`, `future_a`, `initial_gamma`, `future_gamma`,
+///    `initial_a_gamma_time`, `future_a_gamma_time` (`0`), `mid_fee`, `out_fee` and `fee_gamma`
+///  - All pools: `is_killed` (`0x00`)
+fn creation_attributes(log: &Log) -> Option<Vec<(&'static str, Vec<u8>)>> {
+    let attributes = if let Some(deployed) =
+        meta_pool_factory::events::PlainPoolDeployed::match_and_decode(log)
+    {
+        stable_attributes(deployed.a, deployed.fee)
+    } else if let Some(deployed) =
+        stableswap_factory::events::PlainPoolDeployed::match_and_decode(log)
+    {
+        stable_attributes(deployed.a, deployed.fee)
+    } else if let Some(deployed) =
+        meta_pool_factory::events::MetaPoolDeployed::match_and_decode(log)
+    {
+        stable_attributes(deployed.a, deployed.fee)
+    } else if let Some(deployed) =
+        crypto_swap_ng_factory::events::PlainPoolDeployed::match_and_decode(log)
+    {
+        stable_attributes(deployed.a, deployed.fee)
+    } else if let Some(deployed) =
+        crypto_pool_factory::events::CryptoPoolDeployed::match_and_decode(log)
+    {
+        crypto_attributes(
+            deployed.a,
+            deployed.gamma,
+            deployed.mid_fee,
+            deployed.out_fee,
+            deployed.fee_gamma,
+        )
+    } else if let Some(deployed) =
+        tricrypto_factory::events::TricryptoPoolDeployed::match_and_decode(log)
+    {
+        packed_crypto_attributes(&deployed.packed_a_gamma, &deployed.packed_fee_params)
+    } else if let Some(deployed) =
+        twocrypto_factory::events::TwocryptoPoolDeployed::match_and_decode(log)
+    {
+        packed_crypto_attributes(&deployed.packed_a_gamma, &deployed.packed_fee_params)
+    } else {
+        return None;
+    };
+    Some(
+        attributes
+            .into_iter()
+            .map(|(name, value)| (name, value.to_signed_bytes_be()))
+            .chain([("is_killed", vec![0u8])])
+            .collect(),
+    )
+}
+
+/// Adds the creation attributes decoded from `log` to the entity changes of `component_id`,
+///  leaving the attributes the component already has at creation untouched.
+pub fn add_creation_attributes(
+    log: &Log,
+    component_id: &str,
+    entity_changes: &mut Vec<EntityChanges>,
+) {
+    let Some(attributes) = creation_attributes(log) else {
+        return;
+    };
+    let component_id = format!("0x{component_id}");
+    let index = match entity_changes
+        .iter()
+        .position(|change| change.component_id == component_id)
+    {
+        Some(index) => index,
+        None => {
+            entity_changes.push(EntityChanges { component_id, attributes: vec![] });
+            entity_changes.len() - 1
+        }
+    };
+    let entity_change = &mut entity_changes[index];
+    for (name, value) in attributes {
+        if entity_change
+            .attributes
+            .iter()
+            .any(|attribute| attribute.name == name)
+        {
+            continue;
+        }
+        entity_change
+            .attributes
+            .push(Attribute { name: name.to_string(), value, change: ChangeType::Creation.into() });
+    }
+}
+
+fn stable_attributes(a: BigInt, fee: BigInt) -> Vec<(&'static str, BigInt)> {
+    let a = a * BigInt::from(A_PRECISION);
+    vec![
+        ("initial_a", a.clone()),
+        ("future_a", a),
+        ("initial_a_time", BigInt::zero()),
+        ("future_a_time", BigInt::zero()),
+        ("fee", fee),
+    ]
+}
+
+fn crypto_attributes(
+    a: BigInt,
+    gamma: BigInt,
+    mid_fee: BigInt,
+    out_fee: BigInt,
+    fee_gamma: BigInt,
+) -> Vec<(&'static str, BigInt)> {
+    vec![
+        ("initial_a", a.clone()),
+        ("future_a", a),
+        ("initial_gamma", gamma.clone()),
+        ("future_gamma", gamma),
+        ("initial_a_gamma_time", BigInt::zero()),
+        ("future_a_gamma_time", BigInt::zero()),
+        ("mid_fee", mid_fee),
+        ("out_fee", out_fee),
+        ("fee_gamma", fee_gamma),
+    ]
+}
+
+/// Tricrypto-NG and Twocrypto-NG log `A << 128 | gamma` and
+///  `mid_fee << 128 | out_fee << 64 | fee_gamma`, the fee parameters being 64 bits wide.
+fn packed_crypto_attributes(
+    packed_a_gamma: &BigInt,
+    packed_fee_params: &BigInt,
+) -> Vec<(&'static str, BigInt)> {
+    let fee_param = |offset: u8| (packed_fee_params.clone() >> offset) & (u64::MAX as u128);
+    crypto_attributes(
+        packed_a_gamma.clone() >> 128_u8,
+        packed_a_gamma.clone() & u128::MAX,
+        fee_param(128),
+        fee_param(64),
+        fee_param(0),
+    )
+}
+
+/// Decodes the dynamic attributes of tracked pools that are changed by admin actions:
+///  - `RampA` and `StopRampA` set `initial_a`, `future_a`, `initial_a_time` and `future_a_time`,
+///    from which the current amplification can be interpolated
+///  - `RampAgamma` and `StopRampA` of crypto pools set `initial_a`, `future_a`, `initial_gamma`,
+///    `future_gamma`, `initial_a_gamma_time` and `future_a_gamma_time`
+///  - `CommitNewFee` sets `future_fee`, `future_admin_fee` and `admin_actions_deadline`, and
+///    `NewFee` (emitted when the commit is applied) sets `fee` and `admin_fee`
+///  - `ApplyNewFee` of StableSwap-NG pools sets `fee` and `offpeg_fee_multiplier`
+///  - `NewParameters` of crypto pools sets `mid_fee`, `out_fee` and `fee_gamma`, and `admin_fee`
+///    for the legacy crypto pools
+///  - `kill_me` and `unkill_me` calls set `is_killed`, so that dead pools can be excluded
+///
+/// The changes are sorted by ordinal.
+pub fn pool_attribute_changes(
+    tx: &TransactionTrace,
+    tokens_store: &StoreGetString,
+) -> Vec<PoolAttributeChange> {
+    let events = tx
+        .logs_with_calls()
+        .filter(|(_, call)| !call.call.state_reverted)
+        .filter_map(|(log, call)| {
+            Some((
+                log.ordinal,
+                log.address.clone(),
+                event_attributes(log, call.call.input.len(), || logged_a_factor(&log.address))?
+                    .into_iter()
+                    .map(|(name, value)| (name, value.to_signed_bytes_be()))
+                    .collect::<Vec<_>>(),
+            ))
+        });
+
+    let kills = tx
+        .calls
+        .iter()
+        .filter(|call| !call.state_reverted)
+        .filter_map(|call| {
+            let is_killed = if pool::functions::KillMe::match_call(call) {
+                1u8
+            } else if pool::functions::UnkillMe::match_call(call) {
+                0u8
+            } else {
+                return None;
+            };
+            Some((call.begin_ordinal, call.address.clone(), vec![("is_killed", vec![is_killed])]))
+        });
+
+    let mut changes = events
+        .chain(kills)
+        .filter(|(_, pool, _)| get_pool_tokens(pool, tokens_store).is_some())
+        .collect::<Vec<_>>();
+    changes.sort_by_key(|(ordinal, _, _)| *ordinal);
+    changes
+}
+
+/// StableSwap pools log `A` the way they store it: `A * A_PRECISION` for the pools exposing
+///  `A_precise()` (factory pools and most pools since 2021), `A` itself for the older ones (e.g.
+///  3pool, susd). Returns the factor normalising the `A` logged by `pool` to `A * A_PRECISION`.
+fn logged_a_factor(pool: &[u8]) -> u64 {
+    let precise = pool::functions::APrecise {}
+        .call(pool.to_vec())
+        .is_some();
+    if precise {
+        1
+    } else {
+        A_PRECISION
+    }
+}
+
+/// Decodes the attributes set by a pool event. `call_input_len` is the input length of the call
+///  that emitted the event, used to tell apart the `NewParameters` events of legacy crypto pools,
+///  emitted by `apply_new_parameters()`, and of Twocrypto-NG pools, which share its signature.
+///
+/// The `A` of StableSwap ramps is emitted as `A * A_PRECISION` like at creation, `a_factor` being
+///  the factor normalising the logged `A` (see `logged_a_factor`). It is only read for ramps.
+fn event_attributes(
+    log: &Log,
+    call_input_len: usize,
+    a_factor: impl Fn() -> u64,
+) -> Option<Vec<(&'static str, BigInt)>> {
+    let attributes = if let Some(ramp) = pool::events::RampA::match_and_decode(log) {
+        let a_factor = BigInt::from(a_factor());
+        vec![
+            ("initial_a", ramp.old_a * a_factor.clone()),
+            ("future_a", ramp.new_a * a_factor),
+            ("initial_a_time", ramp.initial_time),
+            ("future_a_time", ramp.future_time),
+        ]
+    } else if let Some(stop) = pool::events::StopRampA::match_and_decode(log) {
+        let a = stop.a * BigInt::from(a_factor());
+        vec![
+            ("initial_a", a.clone()),
+            ("future_a", a),
+            ("initial_a_time", stop.t.clone()),
+            ("future_a_time", stop.t),
+        ]
+    } else if let Some(ramp) = crypto_pool::events::RampAgamma::match_and_decode(log) {
+        vec![
+            ("initial_a", ramp.initial_a),
+            ("future_a", ramp.future_a),
+            ("initial_gamma", ramp.initial_gamma),
+            ("future_gamma", ramp.future_gamma),
+            ("initial_a_gamma_time", ramp.initial_time),
+            ("future_a_gamma_time", ramp.future_time),
+        ]
+    } else if let Some(stop) = crypto_pool::events::StopRampA::match_and_decode(log) {
+        vec![
+            ("initial_a", stop.current_a.clone()),
+            ("future_a", stop.current_a),
+            ("initial_gamma", stop.current_gamma.clone()),
+            ("future_gamma", stop.current_gamma),
+            ("initial_a_gamma_time", stop.time.clone()),
+            ("future_a_gamma_time", stop.time),
+        ]
+    } else if let Some(commit) = pool::events::CommitNewFee::match_and_decode(log) {
+        vec![
+            ("future_fee", commit.fee),
+            ("future_admin_fee", commit.admin_fee),
+            ("admin_actions_deadline", commit.deadline),
+        ]
+    } else if let Some(new_fee) = pool::events::NewFee::match_and_decode(log) {
+        vec![("fee", new_fee.fee), ("admin_fee", new_fee.admin_fee)]
+    } else if let Some(new_fee) = stableswap_ng_pool::events::ApplyNewFee::match_and_decode(log) {
+        vec![("fee", new_fee.fee), ("offpeg_fee_multiplier", new_fee.offpeg_fee_multiplier)]
+    } else if let Some(params) = crypto_pool::events::NewParameters::match_and_decode(log) {
+        if call_input_len == 4 {
+            vec![
+                ("admin_fee", params.admin_fee),
+                ("mid_fee", params.mid_fee),
+                ("out_fee", params.out_fee),
+                ("fee_gamma", params.fee_gamma),
+            ]
+        } else {
+            // Twocrypto-NG: `NewParameters(mid_fee, out_fee, fee_gamma, ...)`
+            vec![
+                ("mid_fee", params.admin_fee),
+                ("out_fee", params.mid_fee),
+                ("fee_gamma", params.out_fee),
+            ]
+        }
+    } else if let Some(params) = tricrypto_ng_pool::events::NewParameters::match_and_decode(log) {
+        vec![
+            ("mid_fee", params.mid_fee),
+            ("out_fee", params.out_fee),
+            ("fee_gamma", params.fee_gamma),
+        ]
+    } else {
+        return None;
+    };
+    Some(attributes)
+}
+
+/// Emits the dynamic attributes of tracked pools changed within `tx`, merged per pool so that the
+///  last value of an attribute within the transaction wins.
+///
+/// An attribute is emitted as `Creation` if `attributes_store` recorded this change as its first
+///  write, see `store_pool_attributes`, and as `Update` otherwise.
+pub fn emit_pool_attributes(
+    tx: &TransactionTrace,
+    tokens_store: &StoreGetString,
+    attributes_store: &StoreGetInt64,
+) -> Vec<EntityChanges> {
+    let mut entity_changes: Vec<EntityChanges> = vec![];
+    for (ordinal, pool, attributes) in pool_attribute_changes(tx, tokens_store) {
+        let component_id = format!("0x{}", hex::encode(&pool));
+        let index = match entity_changes
+            .iter()
+            .position(|change| change.component_id == component_id)
+        {
+            Some(index) => index,
+            None => {
+                entity_changes.push(EntityChanges { component_id, attributes: vec![] });
+                entity_changes.len() - 1
+            }
+        };
+        let entity_change = &mut entity_changes[index];
+        for (name, value) in attributes {
+            let created = attributes_store.get_last(attribute_key(&pool, name)) ==
+                Some(ordinal as i64) ||
+                entity_change
+                    .attributes
+                    .iter()
+                    .any(|attribute| {
+                        attribute.name == name && attribute.change == ChangeType::Creation as i32
+                    });
+            entity_change
+                .attributes
+                .retain(|attribute| attribute.name != name);
+            entity_change
+                .attributes
+                .push(Attribute {
+                    name: name.to_string(),
+                    value,
+                    change: if created { ChangeType::Creation } else { ChangeType::Update }.into(),
+                });
+        }
+    }
+    entity_changes
+}
+
+/// Key of a pool attribute in `store_pool_attributes`.
+pub fn attribute_key(pool: &[u8], name: &str) -> String {
+    format!("pool:{}:{name}", hex::encode(pool))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(topic: &str, words: &[BigInt]) -> Log {
+        Log {
+            address: vec![0xaa; 20],
+            topics: vec![hex::decode(topic).unwrap()],
+            data: words
+                .iter()
+                .flat_map(|word| {
+                    let bytes = word.to_bytes_be().1;
+                    [vec![0u8; 32 - bytes.len()], bytes].concat()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn value(attributes: &[(&str, Vec<u8>)], name: &str) -> BigInt {
+        let (_, value) = attributes
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .unwrap_or_else(|| panic!("missing {name}"));
+        BigInt::from_signed_bytes_be(value)
+    }
+
+    #[test]
+    fn test_packed_crypto_attributes() {
+        let a = BigInt::from(1_707_629);
+        let gamma = BigInt::from(11_809_167_828_997u64);
+        let packed_a_gamma = (a.clone() << 128_u32) + gamma.clone();
+        let packed_fee_params = (BigInt::from(3_000_000) << 128_u32) +
+            (BigInt::from(30_000_000) << 64_u32) +
+            BigInt::from(500_000_000_000_000u64);
+
+        let attributes = packed_crypto_attributes(&packed_a_gamma, &packed_fee_params)
+            .into_iter()
+            .map(|(name, value)| (name, value.to_signed_bytes_be()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(value(&attributes, "initial_a"), a);
+        assert_eq!(value(&attributes, "future_a"), a);
+        assert_eq!(value(&attributes, "initial_gamma"), gamma);
+        assert_eq!(value(&attributes, "initial_a_gamma_time"), BigInt::zero());
+        assert_eq!(value(&attributes, "mid_fee"), BigInt::from(3_000_000));
+        assert_eq!(value(&attributes, "out_fee"), BigInt::from(30_000_000));
+        assert_eq!(value(&attributes, "fee_gamma"), BigInt::from(500_000_000_000_000u64));
+    }
+
+    #[test]
+    fn test_add_creation_attributes() {
+        // `MetaPoolDeployed(coin, base_pool, A, fee, deployer)`, all fields unindexed
+        let deployed = log(
+            "01f31cd2abdeb4e5e10ba500f2db0f937d9e8c735ab04681925441b4ea37eda5",
+            &[
+                BigInt::from(1),
+                BigInt::from(2),
+                BigInt::from(200),
+                BigInt::from(4_000_000),
+                BigInt::from(3),
+            ],
+        );
+        let component_id = "bb".repeat(20);
+        let mut entity_changes = vec![EntityChanges {
+            component_id: format!("0x{component_id}"),
+            attributes: vec![Attribute {
+                name: "fee".to_string(),
+                value: vec![1],
+                change: ChangeType::Creation.into(),
+            }],
+        }];
+
+        add_creation_attributes(&deployed, &component_id, &mut entity_changes);
+
+        assert_eq!(entity_changes.len(), 1);
+        let attributes = entity_changes[0]
+            .attributes
+            .iter()
+            .map(|attribute| {
+                assert_eq!(attribute.change, ChangeType::Creation as i32);
+                (attribute.name.as_str(), attribute.value.clone())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(value(&attributes, "initial_a"), BigInt::from(20_000));
+        assert_eq!(value(&attributes, "future_a_time"), BigInt::zero());
+        // Attributes set at creation are kept
+        assert_eq!(value(&attributes, "fee"), BigInt::from(1));
+        assert_eq!(value(&attributes, "is_killed"), BigInt::zero());
+    }
+
+    #[test]
+    fn test_new_parameters_attributes() {
+        let words = (1..=7)
+            .map(BigInt::from)
+            .collect::<Vec<_>>();
+        let new_parameters =
+            log("1c65bbdc939f346e5d6f0bde1f072819947438d4fc7b182cc59c2f6dc5504087", &words);
+
+        let legacy = event_attributes(&new_parameters, 4, || 1).unwrap();
+        assert_eq!(
+            legacy,
+            vec![
+                ("admin_fee", BigInt::from(1)),
+                ("mid_fee", BigInt::from(2)),
+                ("out_fee", BigInt::from(3)),
+                ("fee_gamma", BigInt::from(4)),
+            ]
+        );
+        let twocrypto = event_attributes(&new_parameters, 4 + 32 * 7, || 1).unwrap();
+        assert_eq!(
+            twocrypto,
+            vec![
+                ("mid_fee", BigInt::from(1)),
+                ("out_fee", BigInt::from(2)),
+                ("fee_gamma", BigInt::from(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ramp_a_attributes() {
+        // `RampA(old_A, new_A, initial_time, future_time)`
+        let ramp = log(
+            "a2b71ec6df949300b59aab36b55e189697b750119dd349fcfa8c0f779e83c254",
+            &[BigInt::from(200), BigInt::from(400), BigInt::from(1), BigInt::from(2)],
+        );
+        // `StopRampA(A, t)`
+        let stop = log(
+            "46e22fb3709ad289f62ce63d469248536dbc78d82b84a3d7e74ad606dc201938",
+            &[BigInt::from(300), BigInt::from(3)],
+        );
+
+        // Legacy pools log `A` itself
+        let legacy = event_attributes(&ramp, 4 + 32 * 2, || A_PRECISION).unwrap();
+        assert_eq!(legacy[0], ("initial_a", BigInt::from(20_000)));
+        assert_eq!(legacy[1], ("future_a", BigInt::from(40_000)));
+        assert_eq!(legacy[3], ("future_a_time", BigInt::from(2)));
+        let legacy = event_attributes(&stop, 4, || A_PRECISION).unwrap();
+        assert_eq!(legacy[1], ("future_a", BigInt::from(30_000)));
+
+        let precise = event_attributes(&ramp, 4 + 32 * 2, || 1).unwrap();
+        assert_eq!(precise[0], ("initial_a", BigInt::from(200)));
+        // Other events don't need the factor
+        let new_parameters = log(
+            "1c65bbdc939f346e5d6f0bde1f072819947438d4fc7b182cc59c2f6dc5504087",
+            &(1..=7)
+                .map(BigInt::from)
+                .collect::<Vec<_>>(),
+        );
+        assert!(event_attributes(&new_parameters, 4, || panic!("read the A factor")).is_some());
+    }
+}
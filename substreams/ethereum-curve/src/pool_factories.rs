@@ -3,14 +3,17 @@ use substreams_ethereum::{
     Event, Function,
 };
 
-use crate::abi;
+use crate::{abi, plain_pools};
 use tycho_substreams::{
     attributes::{json_serialize_address_list, json_serialize_bigint_list},
     prelude::*,
 };
 
 use crate::consts::*;
-use substreams::scalar::BigInt;
+use substreams::{
+    scalar::BigInt,
+    store::{StoreGet, StoreGetString},
+};
 
 /// This trait defines some helpers for serializing and deserializing `Vec<BigInt>` which is needed
 ///  to be able to encode some of the `Attribute`s. This should also be handled by any downstream
@@ -258,189 +261,55 @@ pub fn address_map(
                         }],
                     }],
                 ))
-            }
-            // else if let Some(pool_added) =
-            //     abi::meta_pool_factory::events::MetaPoolDeployed::match_and_decode(log)
-            // {
-            //     let add_pool =
-            //         abi::meta_pool_factory::functions::DeployMetapool1::match_and_decode(call)
-            //             .map(|add_pool| abi::meta_pool_factory::functions::DeployMetapool2 {
-            //                 base_pool: add_pool.base_pool,
-            //                 name: add_pool.name,
-            //                 symbol: add_pool.symbol,
-            //                 coin: add_pool.coin,
-            //                 a: add_pool.a,
-            //                 fee: add_pool.fee,
-            //                 implementation_idx: BigInt::from(0),
-            //             })
-            //             .or_else(|| {
-            //                 abi::meta_pool_factory::functions::DeployMetapool2::match_and_decode(
-            //                     call,
-            //                 )
-            //             })?;
-
-            //     let component_id = &call.return_data[12..];
-
-            //     // The `add_pool.base_pool` may only refer to the contract of the base pool and
-            // not     //  the token itself. This means we **have** to make an RPC call
-            // to the     //  `meta_registry` in order to get the real LP token address.
-            //     let get_lp_token =
-            //         abi::meta_registry::functions::GetLpToken1 { pool: add_pool.base_pool.clone()
-            // };     let lp_token = get_lp_token.call(META_REGISTRY.to_vec())?;
-
-            //     let pool_implementation = extract_proxy_impl(call, tx, 0).unwrap_or([1u8; 20]);
-
-            //     Some((
-            //         ProtocolComponent {
-            //             id: hex::encode(component_id),
-            //             tx: Some(Transaction {
-            //                 to: tx.to.clone(),
-            //                 from: tx.from.clone(),
-            //                 hash: tx.hash.clone(),
-            //                 index: tx.index.into(),
-            //             }),
-            //             tokens: vec![pool_added.coin, lp_token],
-            //             contracts: vec![component_id.into(), add_pool.base_pool.clone()],
-            //             static_att: vec![
-            //                 Attribute {
-            //                     name: "pool_type".into(),
-            //                     value: "metapool".into(),
-            //                     change: ChangeType::Creation.into(),
-            //                 },
-            //                 Attribute {
-            //                     name: "name".into(),
-            //                     value: add_pool.name.into(),
-            //                     change: ChangeType::Creation.into(),
-            //                 },
-            //                 Attribute {
-            //                     name: "factory_name".into(),
-            //                     value: "meta_pool_factory".into(),
-            //                     change: ChangeType::Creation.into(),
-            //                 },
-            //                 Attribute {
-            //                     name: "factory".into(),
-            //                     value: address_to_bytes_with_0x(&META_POOL_FACTORY),
-            //                     change: ChangeType::Creation.into(),
-            //                 },
-            //                 Attribute {
-            //                     name: "base_pool".into(),
-            //                     value: address_to_bytes_with_0x(
-            //                         &add_pool.base_pool.try_into().unwrap(),
-            //                     ),
-            //                     change: ChangeType::Creation.into(),
-            //                 },
-            //                 TODO:ADD COINS
-            //             ],
-            //             change: ChangeType::Creation.into(),
-            //             protocol_type: Some(ProtocolType {
-            //                 name: "curve_pool".into(),
-            //                 financial_type: FinancialType::Swap.into(),
-            //                 attribute_schema: Vec::new(),
-            //                 implementation_type: ImplementationType::Vm.into(),
-            //             }),
-            //         },
-            //         vec![EntityChanges {
-            //             component_id: address_to_string_with_0x(component_id),
-            //             attributes: vec![Attribute {
-            //                 name: "stateless_contract_addr_0".into(),
-            //                 value: address_to_bytes_with_0x(&pool_implementation),
-            //                 change: ChangeType::Creation.into(),
-            //             }],
-            //         }],
-            //     ))
-            // }
-            else {
+            } else if let Some(pool_added) =
+                abi::meta_pool_factory::events::MetaPoolDeployed::match_and_decode(log)
+            {
+                let add_pool =
+                    abi::meta_pool_factory::functions::DeployMetapool1::match_and_decode(call)
+                        .map(|add_pool| add_pool.name)
+                        .or_else(|| {
+                            abi::meta_pool_factory::functions::DeployMetapool2::match_and_decode(
+                                call,
+                            )
+                            .map(|add_pool| add_pool.name)
+                        })?;
+                let pool_implementation = extract_proxy_impl(call, tx, 0).unwrap_or([1u8; 20]);
+
+                Some(metapool_component(
+                    &META_POOL_FACTORY,
+                    "meta_pool_factory",
+                    &call.return_data[12..],
+                    add_pool,
+                    pool_added.coin,
+                    pool_added.base_pool,
+                    pool_implementation,
+                ))
+            } else {
                 None
             }
         }
-        // META_POOL_FACTORY_OLD => {
-        //     if let Some(pool_added) =
-        //         abi::meta_pool_factory::events::MetaPoolDeployed::match_and_decode(log)
-        //     {
-        //         let add_pool =
-        //             abi::meta_pool_factory::functions::DeployMetapool1::match_and_decode(call)
-        //                 .map(|add_pool| abi::meta_pool_factory::functions::DeployMetapool2 {
-        //                     base_pool: add_pool.base_pool,
-        //                     name: add_pool.name,
-        //                     symbol: add_pool.symbol,
-        //                     coin: add_pool.coin,
-        //                     a: add_pool.a,
-        //                     fee: add_pool.fee,
-        //                     implementation_idx: BigInt::from(0),
-        //                 })
-        //                 .or_else(|| {
-        //                     abi::meta_pool_factory::functions::DeployMetapool2::match_and_decode(
-        //                         call,
-        //                     )
-        //                 })?;
-
-        //         let pool_implementation = extract_proxy_impl(call, tx, 0).unwrap_or([1u8; 20]);
-
-        //         let component_id = &call.return_data[12..];
-        //         let lp_token = get_token_from_pool(&pool_added.base_pool);
-
-        //         Some((
-        //             ProtocolComponent {
-        //                 id: hex::encode(component_id),
-        //                 tx: Some(Transaction {
-        //                     to: tx.to.clone(),
-        //                     from: tx.from.clone(),
-        //                     hash: tx.hash.clone(),
-        //                     index: tx.index.into(),
-        //                 }),
-        //                 tokens: vec![pool_added.coin, lp_token],
-        //                 contracts: vec![component_id.into()],
-        //                 static_att: vec![
-        //                     Attribute {
-        //                         name: "pool_type".into(),
-        //                         value: "metapool".into(),
-        //                         change: ChangeType::Creation.into(),
-        //                     },
-        //                     Attribute {
-        //                         name: "name".into(),
-        //                         value: add_pool.name.into(),
-        //                         change: ChangeType::Creation.into(),
-        //                     },
-        //                     Attribute {
-        //                         name: "factory_name".into(),
-        //                         value: "meta_pool_factory_old".into(),
-        //                         change: ChangeType::Creation.into(),
-        //                     },
-        //                     Attribute {
-        //                         name: "factory".into(),
-        //                         value: address_to_bytes_with_0x(&META_POOL_FACTORY_OLD),
-        //                         change: ChangeType::Creation.into(),
-        //                     },
-        //                     Attribute {
-        //                         name: "base_pool".into(),
-        //                         value: address_to_bytes_with_0x(
-        //                             &add_pool.base_pool.try_into().unwrap(),
-        //                         ),
-        //                         change: ChangeType::Creation.into(),
-        //                     },
-        //                 TODO:ADD COINS
-        //                 ],
-        //                 change: ChangeType::Creation.into(),
-        //                 protocol_type: Some(ProtocolType {
-        //                     name: "curve_pool".into(),
-        //                     financial_type: FinancialType::Swap.into(),
-        //                     attribute_schema: Vec::new(),
-        //                     implementation_type: ImplementationType::Vm.into(),
-        //                 }),
-        //             },
-        //             vec![EntityChanges {
-        //                 component_id: address_to_string_with_0x(component_id),
-        //                 attributes: vec![Attribute {
-        //                     name: "stateless_contract_addr_0".into(),
-        //                     value: address_to_bytes_with_0x(&pool_implementation),
-        //                     change: ChangeType::Creation.into(),
-        //                 }],
-        //             }],
-        //         ))
-        //     } else {
-        //         None
-        //     }
-        // }
+        META_POOL_FACTORY_OLD => {
+            let pool_added =
+                abi::meta_pool_factory::events::MetaPoolDeployed::match_and_decode(log)?;
+            let add_pool =
+                abi::meta_pool_factory::functions::DeployMetapool1::match_and_decode(call)
+                    .map(|add_pool| add_pool.name)
+                    .or_else(|| {
+                        abi::meta_pool_factory::functions::DeployMetapool2::match_and_decode(call)
+                            .map(|add_pool| add_pool.name)
+                    })?;
+            let pool_implementation = extract_proxy_impl(call, tx, 0).unwrap_or([1u8; 20]);
+
+            Some(metapool_component(
+                &META_POOL_FACTORY_OLD,
+                "meta_pool_factory_old",
+                &call.return_data[12..],
+                add_pool,
+                pool_added.coin,
+                pool_added.base_pool,
+                pool_implementation,
+            ))
+        }
         CRYPTO_SWAP_NG_FACTORY => {
             if let Some(pool_added) =
                 abi::crypto_swap_ng_factory::events::PlainPoolDeployed::match_and_decode(log)
@@ -463,7 +332,7 @@ pub fn address_map(
                             },
                             Attribute {
                                 name: "name".into(),
-                                value: add_pool.name.into(),
+                                value: add_pool.name.clone().into(),
                                 change: ChangeType::Creation.into(),
                             },
                             Attribute {
@@ -497,7 +366,7 @@ pub fn address_map(
                     },
                     vec![EntityChanges {
                         component_id: address_to_string_with_0x(component_id),
-                        attributes: vec![Attribute {
+                        attributes: [Attribute {
                             name: "stateless_contract_addr_0".into(),
                             // Call views_implementation() on CRYPTO_SWAP_NG_FACTORY
                             value: format!(
@@ -506,7 +375,10 @@ pub fn address_map(
                             )
                             .into(),
                             change: ChangeType::Creation.into(),
-                        }],
+                        }]
+                        .into_iter()
+                        .chain(plain_pools::ng_creation_state(&add_pool, call, tx))
+                        .collect(),
                     }],
                 ))
             } else if let Some(pool_added) =
@@ -774,87 +646,30 @@ pub fn address_map(
                         }],
                     }],
                 ))
-            }
-            // else if let Some(pool_added) =
-            //     abi::stableswap_factory::events::MetaPoolDeployed::match_and_decode(log)
-            // {
-            //     let add_pool = if let Some(pool) =
-            //         abi::stableswap_factory::functions::DeployMetapool1::match_and_decode(call)
-            //     {
-            //         abi::stableswap_factory::functions::DeployMetapool2 {
-            //             base_pool: pool.base_pool,
-            //             name: pool.name,
-            //             symbol: pool.symbol,
-            //             coin: pool.coin,
-            //             a: pool.a,
-            //             fee: pool.fee,
-            //             implementation_idx: BigInt::from(0),
-            //         }
-            //     } else if let Some(pool) =
-            //         abi::stableswap_factory::functions::DeployMetapool2::match_and_decode(call)
-            //     {
-            //         pool
-            //     } else {
-            //         return None;
-            //     };
-            //     let component_id = &call.return_data[12..];
-
-            //     let get_lp_token =
-            //         abi::meta_registry::functions::GetLpToken1 { pool: add_pool.base_pool.clone()
-            // };     let lp_token = get_lp_token.call(META_REGISTRY.to_vec())?;
-
-            //     Some((
-            //         ProtocolComponent {
-            //             id: hex::encode(component_id),
-            //             tx: Some(Transaction {
-            //                 to: tx.to.clone(),
-            //                 from: tx.from.clone(),
-            //                 hash: tx.hash.clone(),
-            //                 index: tx.index.into(),
-            //             }),
-            //             tokens: vec![pool_added.coin, lp_token],
-            //             contracts: vec![component_id.into()],
-            //             static_att: vec![
-            //                 Attribute {
-            //                     name: "pool_type".into(),
-            //                     value: "metapool".into(),
-            //                     change: ChangeType::Creation.into(),
-            //                 },
-            //                 Attribute {
-            //                     name: "name".into(),
-            //                     value: add_pool.name.into(),
-            //                     change: ChangeType::Creation.into(),
-            //                 },
-            //                 Attribute {
-            //                     name: "factory_name".into(),
-            //                     value: "stable_swap_factory".into(),
-            //                     change: ChangeType::Creation.into(),
-            //                 },
-            //                 Attribute {
-            //                     name: "factory".into(),
-            //                     value: address_to_bytes_with_0x(&STABLESWAP_FACTORY),
-            //                     change: ChangeType::Creation.into(),
-            //                 },
-            //                 Attribute {
-            //                     name: "base_pool".into(),
-            //                     value: address_to_bytes_with_0x(
-            //                         &pool_added.base_pool.try_into().unwrap(),
-            //                     ),
-            //                     change: ChangeType::Creation.into(),
-            //                 },
-            //             ],
-            //             change: ChangeType::Creation.into(),
-            //             protocol_type: Some(ProtocolType {
-            //                 name: "curve_pool".into(),
-            //                 financial_type: FinancialType::Swap.into(),
-            //                 attribute_schema: Vec::new(),
-            //                 implementation_type: ImplementationType::Vm.into(),
-            //             }),
-            //         },
-            //         vec![],
-            //     ))
-            // }
-            else {
+            } else if let Some(pool_added) =
+                abi::stableswap_factory::events::MetaPoolDeployed::match_and_decode(log)
+            {
+                let add_pool =
+                    abi::stableswap_factory::functions::DeployMetapool1::match_and_decode(call)
+                        .map(|add_pool| add_pool.name)
+                        .or_else(|| {
+                            abi::stableswap_factory::functions::DeployMetapool2::match_and_decode(
+                                call,
+                            )
+                            .map(|add_pool| add_pool.name)
+                        })?;
+                let pool_implementation = extract_proxy_impl(call, tx, 0).unwrap_or([1u8; 20]);
+
+                Some(metapool_component(
+                    &STABLESWAP_FACTORY,
+                    "stable_swap_factory",
+                    &call.return_data[12..],
+                    add_pool,
+                    pool_added.coin,
+                    pool_added.base_pool,
+                    pool_implementation,
+                ))
+            } else {
                 None
             }
         }
@@ -938,6 +753,137 @@ pub fn address_map(
     }
 }
 
+/// Builds the `ProtocolComponent` of a metapool deployed by one of the factories sharing the
+///  original metapool implementation (`meta_pool_factory`, `meta_pool_factory_old` and
+///  `stable_swap_factory`).
+///
+/// The component tokens are the metapool coin and the base pool LP token. To be able to simulate
+///  `exchange_underlying`, the metapool coin followed by the base pool coins are exposed as the
+///  `underlying_coins` attribute (see `add_underlying_coins`), and the base pool and its LP token
+///  are linked as contracts.
+fn metapool_component(
+    factory: &[u8; 20],
+    factory_name: &str,
+    component_id: &[u8],
+    name: String,
+    coin: Vec<u8>,
+    base_pool: Vec<u8>,
+    pool_implementation: [u8; 20],
+) -> (ProtocolComponent, Vec<EntityChanges>) {
+    let lp_token = get_token_from_pool(&base_pool);
+
+    let mut contracts = vec![component_id.to_vec(), base_pool.clone()];
+    if lp_token != base_pool {
+        contracts.push(lp_token.clone());
+    }
+
+    (
+        ProtocolComponent {
+            id: hex::encode(component_id),
+            tokens: vec![coin.clone(), lp_token.clone()],
+            contracts,
+            static_att: vec![
+                Attribute {
+                    name: "pool_type".into(),
+                    value: "metapool".into(),
+                    change: ChangeType::Creation.into(),
+                },
+                Attribute {
+                    name: "name".into(),
+                    value: name.into(),
+                    change: ChangeType::Creation.into(),
+                },
+                Attribute {
+                    name: "factory_name".into(),
+                    value: factory_name.into(),
+                    change: ChangeType::Creation.into(),
+                },
+                Attribute {
+                    name: "factory".into(),
+                    value: address_to_bytes_with_0x(factory),
+                    change: ChangeType::Creation.into(),
+                },
+                Attribute {
+                    name: "base_pool".into(),
+                    value: address_to_bytes_with_0x(&base_pool.try_into().unwrap()),
+                    change: ChangeType::Creation.into(),
+                },
+                Attribute {
+                    name: "coins".into(),
+                    value: json_serialize_address_list(&[coin, lp_token]),
+                    change: ChangeType::Creation.into(),
+                },
+            ],
+            change: ChangeType::Creation.into(),
+            protocol_type: Some(ProtocolType {
+                name: "curve_pool".into(),
+                financial_type: FinancialType::Swap.into(),
+                attribute_schema: Vec::new(),
+                implementation_type: ImplementationType::Vm.into(),
+            }),
+        },
+        vec![EntityChanges {
+            component_id: address_to_string_with_0x(component_id),
+            attributes: vec![Attribute {
+                name: "stateless_contract_addr_0".into(),
+                value: address_to_bytes_with_0x(&pool_implementation),
+                change: ChangeType::Creation.into(),
+            }],
+        }],
+    )
+}
+
+/// Exposes the metapool coin followed by the base pool coins as the `underlying_coins` attribute
+///  of a metapool, with the base pool coins taken from its component tokens in `tokens_store`.
+///  Metapools whose base pool is not a tracked component are kept without the attribute.
+pub fn add_underlying_coins(component: &mut ProtocolComponent, tokens_store: &StoreGetString) {
+    if !component.has_attributes(&[("pool_type", "metapool".into())]) ||
+        component
+            .get_attribute_value("underlying_coins")
+            .is_some()
+    {
+        return;
+    }
+    match underlying_coins(component, |base_pool| {
+        tokens_store.get_last(format!("pool:{}", hex::encode(base_pool)))
+    }) {
+        Some(coins) => component.static_att.push(Attribute {
+            name: "underlying_coins".into(),
+            value: json_serialize_address_list(&coins),
+            change: ChangeType::Creation.into(),
+        }),
+        None => substreams::log::info!(
+            "Base pool of metapool {} is not tracked, underlying coins are unknown",
+            component.id
+        ),
+    }
+}
+
+/// The metapool coin followed by the coins of the base pool, from `base_pool_tokens` which returns
+///  the `:` separated component tokens of a pool.
+fn underlying_coins(
+    component: &ProtocolComponent,
+    base_pool_tokens: impl Fn(&[u8]) -> Option<String>,
+) -> Option<Vec<Vec<u8>>> {
+    let base_pool = component.get_attribute_value("base_pool")?;
+    let base_pool = hex::decode(
+        String::from_utf8(base_pool)
+            .ok()?
+            .trim_start_matches("0x"),
+    )
+    .ok()?;
+    let base_pool_tokens = base_pool_tokens(&base_pool)?;
+    Some(
+        std::iter::once(component.tokens.first()?.clone())
+            .chain(
+                base_pool_tokens
+                    .split(':')
+                    .filter_map(|token| hex::decode(token).ok()),
+            )
+            .collect(),
+    )
+}
+
 /// This function makes 3 attempts to confirm / get the LP token address from a pool address.
 ///
 /// 1. We attempt to see if the pool address is a token address itself by calling an ERC 20 func.
@@ -999,3 +945,38 @@ fn extract_proxy_impl(call: &Call, tx: &TransactionTrace, index: usize) -> Optio
         .first()?;
     Some(extract_eip1167_target_from_code(&code_change.new_code))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_underlying_coins() {
+        let mut component = ProtocolComponent {
+            id: "aa".repeat(20),
+            tokens: vec![vec![1; 20], vec![2; 20]],
+            static_att: vec![Attribute {
+                name: "base_pool".into(),
+                value: "0xbebc44782c7db0a1a60cb6fe97d0b483032ff1c7".into(),
+                change: ChangeType::Creation.into(),
+            }],
+            ..Default::default()
+        };
+        let base_pool_tokens = |pool: &[u8]| {
+            (hex::encode(pool) == "bebc44782c7db0a1a60cb6fe97d0b483032ff1c7")
+                .then(|| format!("{}:{}", "03".repeat(20), "04".repeat(20)))
+        };
+
+        assert_eq!(
+            underlying_coins(&component, base_pool_tokens),
+            Some(vec![vec![1; 20], vec![3; 20], vec![4; 20]])
+        );
+        // Base pool not tracked
+        assert_eq!(underlying_coins(&component, |_| None), None);
+        // Base pool unknown
+        component
+            .static_att
+            .retain(|attribute| attribute.name != "base_pool");
+        assert_eq!(underlying_coins(&component, base_pool_tokens), None);
+    }
+}
@@ -1,13 +1,16 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
-use std::{collections::HashMap, iter::zip};
+use std::{
+    collections::{HashMap, HashSet},
+    iter::zip,
+};
 use substreams_ethereum::pb::eth::v2::TransactionTrace;
 use tycho_substreams::prelude::*;
 
 const PARAMS_SEPERATOR: &str = "#";
 
 #[derive(Debug, Deserialize, PartialEq)]
-struct PoolQueryParams {
+pub struct PoolQueryParams {
     address: String,
     contracts: Option<Vec<String>>,
     tx_hash: String,
@@ -18,30 +21,39 @@ struct PoolQueryParams {
     attribute_vals: Option<Vec<String>>,
 }
 
-/// This function parses the `params` string and extracts the pool query parameters. `params` are
-///  comma-separated, URL-encoded (defined by `serde-qs`) strings, with each component defining the
-///  pool query parameters defined in the struct above. We then iterate through the transactions in
-///  a block, and then if the transaction hash matches our parameter, we emit a `ProtocolComponent`
+/// This function emits the `ProtocolComponent` of the pool created by `tx`, if any, among the pools
+///  parsed from the `params` string by `parse_params`. We iterate through the transactions in a
+///  block, and then if the transaction hash matches our parameter, we emit a `ProtocolComponent`
 ///  defined by the metadata from above alongside some basic defaults that we know for Curve.
 ///
+/// These pools act as overrides: registry discovery skips them, so that pools which need extra
+///  contracts or attributes (e.g. tricrypto2) keep the ones defined in `params.json`.
+///
 /// Static attributes are defined as a vector of tuples with the name and value of the attribute.
 ///  These contain things like the pool type, specific pool fees, etc. You can see
 ///  `pool_factories.rs` for an example of the modern curve pool attributes and also the ones chosen
 ///  for 3pool, etc.
 ///
-/// This function can error based on some basic parsing errors and deeper down hex decoding errors
-///  if various addresses are not formatted properly.
+/// This function can error based on deeper down hex decoding errors if various addresses are not
+///  formatted properly.
 pub fn emit_specific_pools(
-    params: &str,
+    pools: &HashMap<String, PoolQueryParams>,
     tx: &TransactionTrace,
 ) -> Result<Option<(ProtocolComponent, Vec<EntityChanges>)>> {
-    let pools = parse_params(params)?;
     create_component(tx, pools)
 }
 
+/// Returns the lowercase addresses of the pools defined in `params`.
+pub fn override_addresses(pools: &HashMap<String, PoolQueryParams>) -> HashSet<String> {
+    pools
+        .values()
+        .map(|pool| pool.address.to_lowercase())
+        .collect()
+}
+
 fn create_component(
     tx: &TransactionTrace,
-    pools: HashMap<String, PoolQueryParams>,
+    pools: &HashMap<String, PoolQueryParams>,
 ) -> Result<Option<(ProtocolComponent, Vec<EntityChanges>)>> {
     let encoded_hash = hex::encode(tx.hash.clone());
     if let Some(pool) = pools.get(&encoded_hash) {
@@ -116,7 +128,10 @@ fn create_component(
     }
 }
 
-fn parse_params(params: &str) -> Result<HashMap<String, PoolQueryParams>, anyhow::Error> {
+/// Parses the `params` string into pool query parameters keyed by creation transaction hash.
+///  `params` are `#`-separated, URL-encoded (defined by `serde-qs`) strings, with each component
+///  defining the pool query parameters defined in the struct above.
+pub fn parse_params(params: &str) -> Result<HashMap<String, PoolQueryParams>, anyhow::Error> {
     let pools: HashMap<String, PoolQueryParams> = params
         .split(PARAMS_SEPERATOR)
         .map(|param| {
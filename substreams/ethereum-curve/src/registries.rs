@@ -0,0 +1,334 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use substreams::{hex, scalar::BigInt};
+use substreams_ethereum::{
+    pb::eth::v2::{Call, Log, TransactionTrace},
+    Event, Function,
+};
+use tycho_substreams::{attributes::json_serialize_address_list, prelude::*};
+
+use crate::{abi, consts::*};
+
+/// Pool metadata decoded from the registry call listing the pool.
+#[derive(Debug, PartialEq)]
+struct RegistryPool {
+    pool: Vec<u8>,
+    pool_type: &'static str,
+    name: String,
+    lp_token: Vec<u8>,
+}
+
+/// `coins(uint256)` and `coins(int128)`, called by the registries on the pool to list its coins.
+const COINS_SELECTORS: [[u8; 4]; 2] = [hex!("c6610657"), hex!("23746eb8")];
+
+/// Returns the pool registered by a `PoolAdded` event of one of the tracked registries.
+pub fn pool_added(log: &Log) -> Option<Vec<u8>> {
+    let registry: [u8; 20] = log.address.as_slice().try_into().ok()?;
+    match registry {
+        MAIN_REGISTRY => abi::main_registry::events::PoolAdded::match_and_decode(log)
+            .map(|pool_added| pool_added.pool),
+        CRYPTO_SWAP_REGISTRY => abi::crypto_swap_registry::events::PoolAdded::match_and_decode(log)
+            .map(|pool_added| pool_added.pool),
+        _ => None,
+    }
+}
+
+/// Builds the `ProtocolComponent` of a pool discovered through a registry `PoolAdded` event.
+///
+/// The pool type, name and LP token are decoded from the `call` to the registry that emitted the
+///  event, and the coins from the `coins(i)` calls the registry makes to the pool to list them.
+///  Registries list pools long after they were deployed, so the balances the pool holds when it is
+///  listed are read once at the end of the block (see `bootstrap_balances`) and tracked from there.
+///
+/// Pools are skipped if they are listed in `overrides` (`params.json` takes precedence).
+pub fn address_map(
+    log: &Log,
+    call: &Call,
+    tx: &TransactionTrace,
+    overrides: &HashSet<String>,
+) -> Option<(ProtocolComponent, Vec<EntityChanges>)> {
+    let pool = pool_added(log)?;
+    if overrides.contains(&hex::encode(&pool)) {
+        return None;
+    }
+
+    let RegistryPool { pool_type, name, lp_token, .. } =
+        registry_pool(call).filter(|registry_pool| registry_pool.pool == pool)?;
+    let tokens = pool_coins(&pool, call, tx);
+    if tokens.is_empty() {
+        substreams::log::info!("Skipping registry pool 0x{}: no coins", hex::encode(&pool));
+        return None;
+    }
+
+    let mut contracts = vec![pool.clone()];
+    let mut static_att = vec![
+        Attribute {
+            name: "pool_type".into(),
+            value: pool_type.into(),
+            change: ChangeType::Creation.into(),
+        },
+        Attribute { name: "name".into(), value: name.into(), change: ChangeType::Creation.into() },
+        Attribute {
+            name: "factory_name".into(),
+            value: "NA".into(),
+            change: ChangeType::Creation.into(),
+        },
+        Attribute {
+            name: "factory".into(),
+            value: format!("0x{}", hex::encode([0u8; 20])).into(),
+            change: ChangeType::Creation.into(),
+        },
+        Attribute {
+            name: "coins".into(),
+            value: json_serialize_address_list(&tokens),
+            change: ChangeType::Creation.into(),
+        },
+    ];
+    // Older pools have a separate LP token contract
+    if lp_token != pool && lp_token != [0; 20] {
+        contracts.push(lp_token.clone());
+        static_att.push(Attribute {
+            name: "lp_token".into(),
+            value: lp_token,
+            change: ChangeType::Creation.into(),
+        });
+    }
+
+    Some((
+        ProtocolComponent {
+            id: hex::encode(&pool),
+            tokens,
+            contracts,
+            static_att,
+            change: ChangeType::Creation.into(),
+            protocol_type: Some(ProtocolType {
+                name: "curve_pool".into(),
+                financial_type: FinancialType::Swap.into(),
+                attribute_schema: Vec::new(),
+                implementation_type: ImplementationType::Vm.into(),
+            }),
+        },
+        vec![],
+    ))
+}
+
+/// Decodes the pool type, name and LP token from the registry call listing the pool.
+fn registry_pool(call: &Call) -> Option<RegistryPool> {
+    let registry: [u8; 20] = call
+        .address
+        .as_slice()
+        .try_into()
+        .ok()?;
+    match registry {
+        MAIN_REGISTRY => {
+            use abi::main_registry::functions;
+
+            if let Some(add) = functions::AddPool::match_and_decode(call) {
+                Some(RegistryPool {
+                    pool: add.pool,
+                    pool_type: "plain_pool",
+                    name: add.name,
+                    lp_token: add.lp_token,
+                })
+            } else if let Some(add) = functions::AddPoolWithoutUnderlying::match_and_decode(call) {
+                Some(RegistryPool {
+                    pool: add.pool,
+                    pool_type: "plain_pool",
+                    name: add.name,
+                    lp_token: add.lp_token,
+                })
+            } else if let Some(add) = functions::AddMetapool1::match_and_decode(call) {
+                Some(RegistryPool {
+                    pool: add.pool,
+                    pool_type: "metapool",
+                    name: add.name,
+                    lp_token: add.lp_token,
+                })
+            } else {
+                functions::AddMetapool2::match_and_decode(call).map(|add| RegistryPool {
+                    pool: add.pool,
+                    pool_type: "metapool",
+                    name: add.name,
+                    lp_token: add.lp_token,
+                })
+            }
+        }
+        CRYPTO_SWAP_REGISTRY => {
+            use abi::crypto_swap_registry::functions;
+
+            functions::AddPool1::match_and_decode(call)
+                .map(|add| (add.pool, add.name, add.lp_token))
+                .or_else(|| {
+                    functions::AddPool2::match_and_decode(call)
+                        .map(|add| (add.pool, add.name, add.lp_token))
+                })
+                .or_else(|| {
+                    functions::AddPool3::match_and_decode(call)
+                        .map(|add| (add.pool, add.name, add.lp_token))
+                })
+                .map(|(pool, name, lp_token)| RegistryPool {
+                    pool,
+                    pool_type: "crypto_pool",
+                    name,
+                    lp_token,
+                })
+        }
+        _ => None,
+    }
+}
+
+/// Returns the coins of `pool`, in order, from the `coins(i)` calls made by the registry call.
+fn pool_coins(pool: &[u8], registry_call: &Call, tx: &TransactionTrace) -> Vec<Vec<u8>> {
+    tx.calls
+        .iter()
+        .filter(|call| {
+            call.parent_index == registry_call.index &&
+                call.address == pool &&
+                !call.state_reverted &&
+                call.input.len() == 36 &&
+                call.return_data.len() == 32 &&
+                COINS_SELECTORS.contains(&call.input[..4].try_into().unwrap())
+        })
+        .map(|call| {
+            (BigInt::from_unsigned_bytes_be(&call.input[4..]), call.return_data[12..].to_vec())
+        })
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .dedup_by(|(a, _), (b, _)| a == b)
+        .map(|(_, coin)| coin)
+        .filter(|coin| *coin != [0; 20])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POOL: [u8; 20] = hex!("bebc44782c7db0a1a60cb6fe97d0b483032ff1c7");
+    const LP_TOKEN: [u8; 20] = hex!("6c3f90f043a72fa612cbac8115ee7e52bde6e490");
+    const DAI: [u8; 20] = hex!("6b175474e89094c44da98b954eedeac495271d0f");
+    const USDC: [u8; 20] = hex!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+
+    fn pool_added_log(registry: [u8; 20], topic: [u8; 32]) -> Log {
+        Log {
+            address: registry.to_vec(),
+            topics: vec![topic.to_vec(), [[0; 12].as_slice(), &POOL].concat()],
+            data: if registry == MAIN_REGISTRY {
+                ethabi::encode(&[ethabi::Token::Bytes(vec![])])
+            } else {
+                vec![]
+            },
+            ..Default::default()
+        }
+    }
+
+    fn coins_call(selector: [u8; 4], i: u8, coin: [u8; 20]) -> Call {
+        let mut index = [0; 32];
+        index[31] = i;
+        Call {
+            index: 2 + i as u32,
+            parent_index: 1,
+            address: POOL.to_vec(),
+            input: [selector.as_slice(), &index].concat(),
+            return_data: [[0; 12].as_slice(), &coin].concat(),
+            ..Default::default()
+        }
+    }
+
+    /// The registry call and the `coins(i)` calls it makes, listed out of order.
+    fn registry_tx(registry: [u8; 20], input: Vec<u8>, selector: [u8; 4]) -> TransactionTrace {
+        let registry_call =
+            Call { index: 1, address: registry.to_vec(), input, ..Default::default() };
+        TransactionTrace {
+            calls: vec![
+                registry_call,
+                coins_call(selector, 1, USDC),
+                coins_call(selector, 0, DAI),
+                Call { state_reverted: true, ..coins_call(selector, 2, [1; 20]) },
+                coins_call(selector, 3, [0; 20]),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn attribute(component: &ProtocolComponent, name: &str) -> Vec<u8> {
+        component
+            .get_attribute_value(name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_address_map_main_registry() {
+        let input = abi::main_registry::functions::AddPool {
+            pool: POOL.to_vec(),
+            n_coins: BigInt::from(2),
+            lp_token: LP_TOKEN.to_vec(),
+            rate_info: [0; 32],
+            decimals: BigInt::zero(),
+            underlying_decimals: BigInt::zero(),
+            has_initial_a: true,
+            is_v1: true,
+            name: "3pool".to_string(),
+        }
+        .encode();
+        // `coins(int128)`, as called for v1 pools
+        let tx = registry_tx(MAIN_REGISTRY, input, COINS_SELECTORS[1]);
+        let log = pool_added_log(
+            MAIN_REGISTRY,
+            hex!("e485c16479ab7092c0b3fc4649843c06be7f072194675261590c84473ab0aea9"),
+        );
+
+        let (component, _) = address_map(&log, &tx.calls[0], &tx, &HashSet::new()).unwrap();
+
+        assert_eq!(component.id, hex::encode(POOL));
+        assert_eq!(component.tokens, vec![DAI.to_vec(), USDC.to_vec()]);
+        assert_eq!(component.contracts, vec![POOL.to_vec(), LP_TOKEN.to_vec()]);
+        assert_eq!(attribute(&component, "pool_type"), b"plain_pool".to_vec());
+        assert_eq!(attribute(&component, "name"), b"3pool".to_vec());
+        assert_eq!(attribute(&component, "lp_token"), LP_TOKEN.to_vec());
+        assert_eq!(
+            attribute(&component, "coins"),
+            json_serialize_address_list(&[DAI.to_vec(), USDC.to_vec()])
+        );
+
+        let overrides = HashSet::from([hex::encode(POOL)]);
+        assert!(address_map(&log, &tx.calls[0], &tx, &overrides).is_none());
+    }
+
+    #[test]
+    fn test_address_map_crypto_swap_registry() {
+        let input = abi::crypto_swap_registry::functions::AddPool1 {
+            pool: POOL.to_vec(),
+            lp_token: POOL.to_vec(),
+            gauge: vec![0; 20],
+            zap: vec![0; 20],
+            n_coins: BigInt::from(2),
+            name: "tricrypto".to_string(),
+        }
+        .encode();
+        let tx = registry_tx(CRYPTO_SWAP_REGISTRY, input, COINS_SELECTORS[0]);
+        let log = pool_added_log(
+            CRYPTO_SWAP_REGISTRY,
+            hex!("73cca62ab1b520c9715bf4e6c71e3e518c754e7148f65102f43289a7df0efea6"),
+        );
+
+        let (component, _) = address_map(&log, &tx.calls[0], &tx, &HashSet::new()).unwrap();
+
+        assert_eq!(component.tokens, vec![DAI.to_vec(), USDC.to_vec()]);
+        // The pool is its own LP token
+        assert_eq!(component.contracts, vec![POOL.to_vec()]);
+        assert_eq!(attribute(&component, "pool_type"), b"crypto_pool".to_vec());
+    }
+
+    #[test]
+    fn test_address_map_unknown_call() {
+        // Pools listed through a call that can't be decoded are skipped
+        let tx = registry_tx(MAIN_REGISTRY, vec![1, 2, 3, 4], COINS_SELECTORS[0]);
+        let log = pool_added_log(
+            MAIN_REGISTRY,
+            hex!("e485c16479ab7092c0b3fc4649843c06be7f072194675261590c84473ab0aea9"),
+        );
+
+        assert!(address_map(&log, &tx.calls[0], &tx, &HashSet::new()).is_none());
+    }
+}
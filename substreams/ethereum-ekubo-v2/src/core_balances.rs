@@ -0,0 +1,158 @@
+use ethabi::Address;
+use substreams::{hex, scalar::BigInt};
+use substreams_ethereum::pb::eth::v2::{BalanceChange, Log, TransactionTrace};
+
+use crate::{
+    deployment_config::DeploymentConfig,
+    pb::ekubo::block_transaction_events::transaction_events::CoreBalanceDelta,
+};
+
+// keccak256("Transfer(address,address,uint256)")
+const TRANSFER_TOPIC: [u8; 32] =
+    hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+/// Changes of the tokens held by the Core contracts in a transaction: the ERC20 transfers from and
+/// to a Core and the changes of its native balance, the native token being the zero address.
+///
+/// These are the contract's actual holdings, which include saved balances and protocol fees on top
+/// of the balances of its pools.
+pub fn core_balance_deltas(
+    trace: &TransactionTrace,
+    config: &DeploymentConfig,
+) -> Vec<CoreBalanceDelta> {
+    let is_core = |address: &[u8]| {
+        config
+            .deployments
+            .iter()
+            .any(|deployment| deployment.core == address)
+    };
+
+    let transfer_deltas = trace
+        .logs_with_calls()
+        .flat_map(|(log, _)| transfer_deltas(log, is_core));
+    let native_deltas = trace
+        .calls
+        .iter()
+        .filter(|call| !call.state_reverted)
+        .flat_map(|call| call.balance_changes.iter())
+        .filter(|change| is_core(&change.address))
+        .filter_map(native_delta);
+
+    let mut deltas: Vec<_> = transfer_deltas
+        .chain(native_deltas)
+        .collect();
+    deltas.sort_by_key(|delta| delta.ordinal);
+    deltas
+}
+
+fn transfer_deltas(log: &Log, is_core: impl Fn(&[u8]) -> bool) -> Vec<CoreBalanceDelta> {
+    if log.topics.len() != 3 || log.topics[0] != TRANSFER_TOPIC || log.data.len() != 32 {
+        return vec![];
+    }
+    let (from, to) = (&log.topics[1][12..], &log.topics[2][12..]);
+    if from == to {
+        return vec![];
+    }
+
+    let value = BigInt::from_unsigned_bytes_be(&log.data);
+    [(from, BigInt::zero() - value.clone()), (to, value)]
+        .into_iter()
+        .filter(|(account, _)| is_core(account))
+        .map(|(core, delta)| CoreBalanceDelta {
+            ordinal: log.ordinal,
+            core: core.to_vec(),
+            token: log.address.clone(),
+            delta: delta.to_signed_bytes_be(),
+        })
+        .collect()
+}
+
+fn native_delta(change: &BalanceChange) -> Option<CoreBalanceDelta> {
+    let value = |value: &Option<substreams_ethereum::pb::eth::v2::BigInt>| {
+        value
+            .as_ref()
+            .map(|value| BigInt::from_unsigned_bytes_be(&value.bytes))
+            .unwrap_or_else(BigInt::zero)
+    };
+    let delta = value(&change.new_value) - value(&change.old_value);
+    if delta.is_zero() {
+        return None;
+    }
+
+    Some(CoreBalanceDelta {
+        ordinal: change.ordinal,
+        core: change.address.clone(),
+        token: Address::zero().as_bytes().to_vec(),
+        delta: delta.to_signed_bytes_be(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORE: [u8; 20] = [0xe0; 20];
+    const TOKEN: [u8; 20] = [0xaa; 20];
+    const USER: [u8; 20] = [0x11; 20];
+
+    fn topic(address: [u8; 20]) -> Vec<u8> {
+        [[0u8; 12].as_slice(), &address].concat()
+    }
+
+    fn transfer(from: [u8; 20], to: [u8; 20], value: u64) -> Log {
+        let mut data = [0u8; 32];
+        data[24..].copy_from_slice(&value.to_be_bytes());
+        Log {
+            address: TOKEN.to_vec(),
+            topics: vec![TRANSFER_TOPIC.to_vec(), topic(from), topic(to)],
+            data: data.to_vec(),
+            ordinal: 7,
+            ..Default::default()
+        }
+    }
+
+    fn is_core(account: &[u8]) -> bool {
+        account == CORE
+    }
+
+    #[test]
+    fn test_transfer_deltas() {
+        let deltas = transfer_deltas(&transfer(USER, CORE, 1000), is_core);
+        assert_eq!(
+            deltas,
+            vec![CoreBalanceDelta {
+                ordinal: 7,
+                core: CORE.to_vec(),
+                token: TOKEN.to_vec(),
+                delta: BigInt::from(1000).to_signed_bytes_be(),
+            }]
+        );
+
+        let deltas = transfer_deltas(&transfer(CORE, USER, 1000), is_core);
+        assert_eq!(deltas[0].delta, BigInt::from(-1000).to_signed_bytes_be());
+
+        assert!(transfer_deltas(&transfer(USER, USER, 1000), is_core).is_empty());
+        assert!(transfer_deltas(&transfer(CORE, CORE, 1000), is_core).is_empty());
+    }
+
+    #[test]
+    fn test_native_delta() {
+        let balance = |value: u64| {
+            Some(substreams_ethereum::pb::eth::v2::BigInt { bytes: value.to_be_bytes().to_vec() })
+        };
+        let change = BalanceChange {
+            address: CORE.to_vec(),
+            old_value: balance(500),
+            new_value: balance(200),
+            ordinal: 3,
+            ..Default::default()
+        };
+
+        let delta = native_delta(&change).unwrap();
+        assert_eq!(delta.token, vec![0; 20]);
+        assert_eq!(delta.delta, BigInt::from(-300).to_signed_bytes_be());
+
+        let change = BalanceChange { old_value: None, new_value: balance(0), ..change };
+        assert!(native_delta(&change).is_none());
+    }
+}
@@ -1,7 +1,18 @@
 use serde::Deserialize;
 
+/// Layout of the raw (topic-less) events emitted by a deployment. Core, TWAMM and Oracle pack
+/// some events by hand to save gas, so their byte layout changes between contract revisions.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutVersion {
+    #[default]
+    V2,
+}
+
 #[derive(Deserialize)]
-pub struct DeploymentConfig {
+pub struct Deployment {
+    #[serde(default)]
+    pub version: LayoutVersion,
     #[serde(with = "hex::serde")]
     pub core: Vec<u8>,
     #[serde(with = "hex::serde")]
@@ -11,3 +22,54 @@ pub struct DeploymentConfig {
     #[serde(with = "hex::serde")]
     pub mev_resist: Vec<u8>,
 }
+
+impl Deployment {
+    fn contains(&self, address: &[u8]) -> bool {
+        [&self.core, &self.oracle, &self.twamm, &self.mev_resist]
+            .into_iter()
+            .any(|contract| contract == address)
+    }
+}
+
+/// All deployments indexed by the package, e.g.
+/// `deployments[0][core]=...&deployments[0][oracle]=...&deployments[0][version]=v2`
+#[derive(Deserialize)]
+pub struct DeploymentConfig {
+    pub deployments: Vec<Deployment>,
+}
+
+impl DeploymentConfig {
+    /// Returns the deployment the given contract address belongs to.
+    pub fn deployment_of(&self, address: &[u8]) -> Option<&Deployment> {
+        self.deployments
+            .iter()
+            .find(|deployment| deployment.contains(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multiple_deployments() {
+        let config: DeploymentConfig = serde_qs::from_str(
+            "deployments[0][core]=e0e0e08A6A4b9Dc7bD67BCB7aadE5cF48157d444&deployments[0][oracle]=51d02A5948496a67827242EaBc5725531342527C&deployments[0][twamm]=D4279c050DA1F5c5B2830558C7A08E57e12b54eC&deployments[0][mev_resist]=553a2EFc570c9e104942cEC6aC1c18118e54C091&deployments[1][version]=v2&deployments[1][core]=0000000000000000000000000000000000000001&deployments[1][oracle]=0000000000000000000000000000000000000002&deployments[1][twamm]=0000000000000000000000000000000000000003&deployments[1][mev_resist]=0000000000000000000000000000000000000004",
+        )
+        .unwrap();
+
+        assert_eq!(config.deployments.len(), 2);
+        assert_eq!(config.deployments[0].version, LayoutVersion::V2);
+
+        let deployment = config
+            .deployment_of(&hex::decode("0000000000000000000000000000000000000003").unwrap())
+            .unwrap();
+        assert_eq!(
+            deployment.core,
+            hex::decode("0000000000000000000000000000000000000001").unwrap()
+        );
+        assert!(config
+            .deployment_of(&hex::decode("0000000000000000000000000000000000000005").unwrap())
+            .is_none());
+    }
+}
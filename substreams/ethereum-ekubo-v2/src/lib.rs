@@ -1,8 +1,12 @@
 mod abi;
+mod core_balances;
 mod deployment_config;
+mod mev_resist;
 mod modules;
+mod oracle;
 mod pb;
 mod pool_key;
+mod raw_events;
 mod sqrt_ratio;
 mod store;
 mod twamm;
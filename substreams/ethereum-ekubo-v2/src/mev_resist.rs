@@ -0,0 +1,40 @@
+use crate::pb::ekubo::block_transaction_events::transaction_events::pool_log::MevResistStateUpdated;
+
+/// Decodes the per-pool state the MEV-resist extension keeps in the storage slot keyed by the
+/// pool id: the time of the last update in the lowest 32 bits followed by the tick at that time.
+pub fn decode_pool_state(value: &[u8; 32]) -> MevResistStateUpdated {
+    MevResistStateUpdated {
+        last_update_time: u32::from_be_bytes(value[28..32].try_into().unwrap()).into(),
+        tick_last: i32::from_be_bytes(value[24..28].try_into().unwrap()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_pool_state() {
+        // tickLast = -887272 (MIN_TICK) and lastUpdateTime = 1742000000
+        let mut value = [0u8; 32];
+        value[24..28].copy_from_slice(&(-887272i32).to_be_bytes());
+        value[28..32].copy_from_slice(&1742000000u32.to_be_bytes());
+
+        let state = decode_pool_state(&value);
+
+        assert_eq!(state.last_update_time, 1742000000);
+        assert_eq!(state.tick_last, -887272);
+    }
+
+    #[test]
+    fn test_decode_pool_state_ignores_high_bits() {
+        let mut value = [0xff; 32];
+        value[24..28].copy_from_slice(&5i32.to_be_bytes());
+        value[28..32].copy_from_slice(&7u32.to_be_bytes());
+
+        let state = decode_pool_state(&value);
+
+        assert_eq!(state.last_update_time, 7);
+        assert_eq!(state.tick_last, 5);
+    }
+}
@@ -1,20 +1,25 @@
+use anyhow::Result;
 use ethabi::Address;
 use itertools::Itertools;
-use substreams::scalar::BigInt;
 use substreams_ethereum::{
-    pb::eth::{self, v2::Log},
+    pb::eth::{
+        self,
+        v2::{Log, StorageChange},
+    },
     Event as _,
 };
 
 use crate::{
     abi::{core::events as core_events, twamm::events as twamm_events},
-    deployment_config::DeploymentConfig,
+    core_balances::core_balance_deltas,
+    deployment_config::{Deployment, DeploymentConfig},
+    mev_resist,
     pb::ekubo::{
         block_transaction_events::{
             transaction_events::{
                 pool_log::{
                     order_updated::OrderKey, pool_initialized::Extension, Event, OrderUpdated,
-                    PoolInitialized, PositionUpdated, Swapped, VirtualOrdersExecuted,
+                    PoolInitialized, PositionUpdated,
                 },
                 PoolLog,
             },
@@ -23,26 +28,59 @@ use crate::{
         BlockTransactionEvents,
     },
     pool_key::{PoolConfig, PoolKey},
+    raw_events,
     sqrt_ratio::float_sqrt_ratio_to_fixed,
 };
 
 #[substreams::handlers::map]
-fn map_events(params: String, block: eth::v2::Block) -> BlockTransactionEvents {
-    let config: DeploymentConfig = serde_qs::from_str(&params).unwrap();
-
-    BlockTransactionEvents {
-        block_transaction_events: block
-            .transactions()
-            .flat_map(|trace| {
-                let pool_logs = trace
-                    .logs_with_calls()
-                    .filter_map(|(log, _)| maybe_pool_log(log, &config))
-                    .collect_vec();
-
-                (!pool_logs.is_empty())
-                    .then(|| TransactionEvents { transaction: Some(trace.into()), pool_logs })
+fn map_events(
+    params: String,
+    block: eth::v2::Block,
+) -> Result<BlockTransactionEvents, substreams::errors::Error> {
+    let config: DeploymentConfig = serde_qs::from_str(&params)?;
+
+    let mut block_transaction_events = vec![];
+
+    for trace in block.transactions() {
+        let logs = trace
+            .logs_with_calls()
+            .map(|(log, _)| maybe_pool_log(log, &config))
+            .filter_map_ok(|pool_log| pool_log)
+            .collect::<Result<Vec<_>>>()?;
+
+        // The extension writes its state when called by Core, so only slots of pools
+        // touched in this transaction can hold pool state
+        let mev_resist_updates = trace
+            .calls
+            .iter()
+            .filter(|call| !call.state_reverted)
+            .flat_map(|call| call.storage_changes.iter())
+            .filter_map(|change| maybe_mev_resist_state_update(change, &config))
+            .filter(|update| {
+                logs.iter()
+                    .any(|log| log.pool_id == update.pool_id)
             })
-            .collect(),
+            .collect_vec();
+
+        let pool_logs = logs
+            .into_iter()
+            .chain(mev_resist_updates)
+            .sorted_unstable_by_key(|pool_log| pool_log.ordinal)
+            .collect_vec();
+
+        let core_balance_deltas = core_balance_deltas(trace, &config);
+
+        if !pool_logs.is_empty() || !core_balance_deltas.is_empty() {
+            block_transaction_events.push(TransactionEvents {
+                transaction: Some(trace.into()),
+                pool_logs,
+                core_balance_deltas,
+            });
+        }
+    }
+
+    Ok(BlockTransactionEvents {
+        block_transaction_events,
         timestamp: block
             .header
             .as_ref()
@@ -53,28 +91,19 @@ fn map_events(params: String, block: eth::v2::Block) -> BlockTransactionEvents {
             .seconds
             .try_into()
             .unwrap(),
-    }
+    })
 }
 
-fn maybe_pool_log(log: &Log, config: &DeploymentConfig) -> Option<PoolLog> {
-    let (pool_id, ev) = if log.address == config.core {
-        if log.topics.is_empty() {
-            let data = &log.data;
+fn maybe_pool_log(log: &Log, config: &DeploymentConfig) -> Result<Option<PoolLog>> {
+    let Some(deployment) = config.deployment_of(&log.address) else {
+        return Ok(None);
+    };
 
-            assert!(data.len() == 116, "swap event data length mismatch");
+    let (pool_id, ev) = if log.address == deployment.core {
+        if log.topics.is_empty() {
+            let (pool_id, ev) = raw_events::decode_swapped(&log.data, deployment.version)?;
 
-            (
-                data[20..52].to_vec(),
-                Event::Swapped(Swapped {
-                    delta0: data[52..68].to_vec(),
-                    delta1: data[68..84].to_vec(),
-                    liquidity_after: data[84..100].to_vec(),
-                    sqrt_ratio_after: float_sqrt_ratio_to_fixed(BigInt::from_unsigned_bytes_be(
-                        &data[100..112],
-                    )),
-                    tick_after: i32::from_be_bytes(data[112..116].try_into().unwrap()),
-                }),
-            )
+            (pool_id, Event::Swapped(ev))
         } else if let Some(ev) = core_events::PositionUpdated::match_and_decode(log) {
             (
                 ev.pool_id.to_vec(),
@@ -89,22 +118,6 @@ fn maybe_pool_log(log: &Log, config: &DeploymentConfig) -> Option<PoolLog> {
         } else if let Some(ev) = core_events::PoolInitialized::match_and_decode(log) {
             let pool_config = PoolConfig::from(ev.pool_key.2);
 
-            let extension = {
-                let extension = pool_config.extension.as_bytes();
-
-                if extension == Address::zero().as_bytes() {
-                    Extension::Base
-                } else if extension == config.oracle {
-                    Extension::Oracle
-                } else if extension == config.twamm {
-                    Extension::Twamm
-                } else if extension == config.mev_resist {
-                    Extension::MevResist
-                } else {
-                    Extension::Unknown
-                }
-            };
-
             (
                 ev.pool_id.to_vec(),
                 Event::PoolInitialized(PoolInitialized {
@@ -113,25 +126,19 @@ fn maybe_pool_log(log: &Log, config: &DeploymentConfig) -> Option<PoolLog> {
                     config: ev.pool_key.2.to_vec(),
                     tick: ev.tick.to_i32(),
                     sqrt_ratio: float_sqrt_ratio_to_fixed(ev.sqrt_ratio),
-                    extension: extension.into(),
+                    extension: extension_of(pool_config.extension.as_bytes(), deployment).into(),
+                    core: deployment.core.clone(),
                 }),
             )
         } else {
-            return None;
+            return Ok(None);
         }
-    } else if log.address == config.twamm {
+    } else if log.address == deployment.twamm {
         if log.topics.is_empty() {
-            let data = &log.data;
+            let (pool_id, ev) =
+                raw_events::decode_virtual_orders_executed(&log.data, deployment.version)?;
 
-            assert!(data.len() == 60, "virtual orders executed event data length mismatch");
-
-            (
-                data[0..32].to_vec(),
-                Event::VirtualOrdersExecuted(VirtualOrdersExecuted {
-                    token0_sale_rate: data[32..46].to_vec(),
-                    token1_sale_rate: data[46..60].to_vec(),
-                }),
-            )
+            (pool_id, Event::VirtualOrdersExecuted(ev))
         } else if let Some(ev) = twamm_events::OrderUpdated::match_and_decode(log) {
             let key = ev.order_key;
 
@@ -149,11 +156,58 @@ fn maybe_pool_log(log: &Log, config: &DeploymentConfig) -> Option<PoolLog> {
                 }),
             )
         } else {
-            return None;
+            return Ok(None);
+        }
+    } else if log.address == deployment.oracle {
+        if log.topics.is_empty() {
+            let ev = raw_events::decode_oracle_snapshot(&log.data, deployment.version)?;
+
+            (
+                PoolKey::from_oracle_token(&ev.token, &log.address).into_pool_id(),
+                Event::OracleSnapshot(ev),
+            )
+        } else {
+            return Ok(None);
         }
     } else {
-        return None;
+        return Ok(None);
     };
 
-    Some(PoolLog { ordinal: log.ordinal, pool_id, event: Some(ev) })
+    Ok(Some(PoolLog { ordinal: log.ordinal, pool_id, event: Some(ev) }))
+}
+
+fn extension_of(extension: &[u8], deployment: &Deployment) -> Extension {
+    if extension == Address::zero().as_bytes() {
+        Extension::Base
+    } else if extension == deployment.oracle {
+        Extension::Oracle
+    } else if extension == deployment.twamm {
+        Extension::Twamm
+    } else if extension == deployment.mev_resist {
+        Extension::MevResist
+    } else {
+        Extension::Unknown
+    }
+}
+
+// The MEV-resist extension emits no events, its per-pool state is read from its storage instead
+fn maybe_mev_resist_state_update(
+    change: &StorageChange,
+    config: &DeploymentConfig,
+) -> Option<PoolLog> {
+    if !config
+        .deployments
+        .iter()
+        .any(|deployment| change.address == deployment.mev_resist)
+    {
+        return None;
+    }
+
+    let value = <&[u8; 32]>::try_from(change.new_value.as_slice()).ok()?;
+
+    Some(PoolLog {
+        ordinal: change.ordinal,
+        pool_id: change.key.clone(),
+        event: Some(Event::MevResistStateUpdated(mev_resist::decode_pool_state(value))),
+    })
 }
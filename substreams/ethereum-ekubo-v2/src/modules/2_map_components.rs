@@ -1,5 +1,6 @@
 use itertools::Itertools;
-use substreams::{hex, scalar::BigInt};
+use serde::Deserialize;
+use substreams::scalar::BigInt;
 use substreams_helper::hex::Hexable;
 use tycho_substreams::models::{
     Attribute, BalanceChange, BlockChanges, ChangeType, EntityChanges, FinancialType,
@@ -9,7 +10,7 @@ use tycho_substreams::models::{
 use crate::{
     pb::ekubo::{
         block_transaction_events::transaction_events::{
-            pool_log::{pool_initialized::Extension, Event},
+            pool_log::{pool_initialized::Extension, Event, PoolInitialized},
             PoolLog,
         },
         BlockTransactionEvents,
@@ -17,9 +18,22 @@ use crate::{
     pool_key::PoolConfig,
 };
 
+#[derive(Deserialize)]
+struct Params {
+    /// Keep emitting the deprecated `balance_owner` attribute until all consumers read the Core
+    /// account balances instead
+    #[serde(default)]
+    emit_balance_owner: bool,
+}
+
 #[substreams::handlers::map]
-fn map_components(block_tx_events: BlockTransactionEvents) -> BlockChanges {
-    BlockChanges {
+fn map_components(
+    params: String,
+    block_tx_events: BlockTransactionEvents,
+) -> Result<BlockChanges, substreams::errors::Error> {
+    let params: Params = serde_qs::from_str(&params)?;
+
+    Ok(BlockChanges {
         block: None,
         changes: block_tx_events
             .block_transaction_events
@@ -28,7 +42,13 @@ fn map_components(block_tx_events: BlockTransactionEvents) -> BlockChanges {
                 let (components, entities, balance_changes): (Vec<_>, Vec<_>, Vec<_>) = tx_events
                     .pool_logs
                     .into_iter()
-                    .filter_map(|log| maybe_create_component(log, block_tx_events.timestamp))
+                    .filter_map(|log| {
+                        maybe_create_component(
+                            log,
+                            block_tx_events.timestamp,
+                            params.emit_balance_owner,
+                        )
+                    })
                     .multiunzip();
 
                 (!components.is_empty()).then(|| TransactionChanges {
@@ -45,36 +65,18 @@ fn map_components(block_tx_events: BlockTransactionEvents) -> BlockChanges {
             })
             .collect(),
         ..Default::default()
-    }
+    })
 }
 
 fn maybe_create_component(
     log: PoolLog,
     timestamp: u64,
+    emit_balance_owner: bool,
 ) -> Option<(ProtocolComponent, EntityChanges, Vec<BalanceChange>)> {
     if let Event::PoolInitialized(pi) = log.event.unwrap() {
-        let entity_attributes = (pi.extension() == Extension::Twamm)
-            .then(|| {
-                [
-                    Attribute {
-                        change: ChangeType::Creation.into(),
-                        name: "token0_sale_rate".to_string(),
-                        value: vec![],
-                    },
-                    Attribute {
-                        change: ChangeType::Creation.into(),
-                        name: "token1_sale_rate".to_string(),
-                        value: vec![],
-                    },
-                    Attribute {
-                        change: ChangeType::Creation.into(),
-                        name: "last_execution_time".to_string(),
-                        value: timestamp.to_be_bytes().to_vec(),
-                    },
-                ]
-            })
+        let supported_extension = pi.extension() != Extension::Unknown;
+        let entity_attributes = extension_attributes(&pi, timestamp)
             .into_iter()
-            .flatten()
             .chain([
                 Attribute {
                     change: ChangeType::Creation.into(),
@@ -91,13 +93,12 @@ fn maybe_create_component(
                     name: "sqrt_ratio".to_string(),
                     value: pi.sqrt_ratio,
                 },
-                Attribute {
-                    change: ChangeType::Creation.into(),
-                    name: "balance_owner".to_string(), /* TODO: We should use AccountBalances
-                                                        * instead */
-                    value: hex!("e0e0e08A6A4b9Dc7bD67BCB7aadE5cF48157d444").to_vec(),
-                },
             ])
+            .chain(emit_balance_owner.then(|| Attribute {
+                change: ChangeType::Creation.into(),
+                name: "balance_owner".to_string(),
+                value: pi.core.clone(),
+            }))
             .collect();
 
         let config = PoolConfig::from(<[u8; 32]>::try_from(pi.config).unwrap());
@@ -153,6 +154,19 @@ fn maybe_create_component(
                         name: "extension_id".to_string(),
                         value: pi.extension.to_be_bytes().to_vec(),
                     },
+                    // Pools with an unknown extension can't be quoted as the extension may alter
+                    // swaps arbitrarily. Consumers should exclude them.
+                    Attribute {
+                        change: ChangeType::Creation.into(),
+                        name: "extension_supported".to_string(),
+                        value: vec![supported_extension as u8],
+                    },
+                    // Holds the pool's tokens, see the Core account balances
+                    Attribute {
+                        change: ChangeType::Creation.into(),
+                        name: "core".to_string(),
+                        value: pi.core,
+                    },
                 ],
             },
             EntityChanges { component_id: component_id.clone(), attributes: entity_attributes },
@@ -173,3 +187,56 @@ fn maybe_create_component(
 
     None
 }
+
+fn extension_attributes(pi: &PoolInitialized, timestamp: u64) -> Vec<Attribute> {
+    match pi.extension() {
+        Extension::Twamm => vec![
+            Attribute {
+                change: ChangeType::Creation.into(),
+                name: "token0_sale_rate".to_string(),
+                value: vec![],
+            },
+            Attribute {
+                change: ChangeType::Creation.into(),
+                name: "token1_sale_rate".to_string(),
+                value: vec![],
+            },
+            Attribute {
+                change: ChangeType::Creation.into(),
+                name: "last_execution_time".to_string(),
+                value: timestamp.to_be_bytes().to_vec(),
+            },
+        ],
+        // The first snapshot is inserted when the pool is initialized
+        Extension::Oracle => vec![
+            Attribute {
+                change: ChangeType::Creation.into(),
+                name: "oracle/last_snapshot_time".to_string(),
+                value: timestamp.to_be_bytes().to_vec(),
+            },
+            Attribute {
+                change: ChangeType::Creation.into(),
+                name: "oracle/seconds_per_liquidity_cumulative".to_string(),
+                value: vec![],
+            },
+            Attribute {
+                change: ChangeType::Creation.into(),
+                name: "oracle/tick_cumulative".to_string(),
+                value: 0_i64.to_be_bytes().to_vec(),
+            },
+        ],
+        Extension::MevResist => vec![
+            Attribute {
+                change: ChangeType::Creation.into(),
+                name: "mev_resist/last_update_time".to_string(),
+                value: timestamp.to_be_bytes().to_vec(),
+            },
+            Attribute {
+                change: ChangeType::Creation.into(),
+                name: "mev_resist/tick_last".to_string(),
+                value: pi.tick.to_be_bytes().to_vec(),
+            },
+        ],
+        Extension::Base | Extension::Unknown => vec![],
+    }
+}
@@ -20,7 +20,7 @@ pub fn map_order_sale_rate_deltas(block_tx_events: BlockTransactionEvents) -> Or
                     .flat_map(move |log| {
                         let tx = tx.clone();
 
-                        order_sale_rate_deltas(log.event.unwrap())
+                        order_sale_rate_deltas(log.event.unwrap(), block_tx_events.timestamp)
                             .into_iter()
                             .map(move |partial| OrderSaleRateDelta {
                                 pool_id: log.pool_id.clone(),
@@ -42,7 +42,7 @@ struct PartialOrderSaleRateDelta {
     is_token1: bool,
 }
 
-fn order_sale_rate_deltas(ev: Event) -> Vec<PartialOrderSaleRateDelta> {
+fn order_sale_rate_deltas(ev: Event, timestamp: u64) -> Vec<PartialOrderSaleRateDelta> {
     match ev {
         Event::OrderUpdated(ev) => {
             let key = ev.order_key.unwrap();
@@ -64,6 +64,11 @@ fn order_sale_rate_deltas(ev: Event) -> Vec<PartialOrderSaleRateDelta> {
                     is_token1,
                 },
             ]
+            .into_iter()
+            // A virtual order execution always happens before an order update, so deltas at past
+            // times are applied to the active sale rates directly
+            .filter(|delta| delta.time > timestamp)
+            .collect()
         }
         _ => vec![],
     }
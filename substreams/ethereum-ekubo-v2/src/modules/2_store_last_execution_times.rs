@@ -0,0 +1,30 @@
+use substreams::store::{StoreNew, StoreSet, StoreSetInt64};
+use substreams_helper::hex::Hexable;
+
+use crate::pb::ekubo::{
+    block_transaction_events::transaction_events::pool_log::{pool_initialized::Extension, Event},
+    BlockTransactionEvents,
+};
+
+// Virtual orders of a TWAMM pool are executed up to the current block's timestamp. The pool's
+// initialization counts as the first execution.
+#[substreams::handlers::store]
+pub fn store_last_execution_times(block_tx_events: BlockTransactionEvents, store: StoreSetInt64) {
+    let timestamp: i64 = block_tx_events
+        .timestamp
+        .try_into()
+        .unwrap();
+
+    block_tx_events
+        .block_transaction_events
+        .into_iter()
+        .flat_map(|tx_events| tx_events.pool_logs)
+        .filter(|log| match log.event.as_ref().unwrap() {
+            Event::PoolInitialized(pi) => pi.extension() == Extension::Twamm,
+            Event::VirtualOrdersExecuted(_) => true,
+            _ => false,
+        })
+        .for_each(|log| {
+            store.set(log.ordinal, format!("pool:{}", log.pool_id.to_hex()), &timestamp)
+        });
+}
@@ -0,0 +1,28 @@
+use substreams::store::{StoreAppend, StoreNew};
+use substreams_helper::hex::Hexable;
+
+use crate::{pb::ekubo::OrderSaleRateDeltas, twamm::bucket_times_period};
+
+// Substreams stores can't be iterated, so the times of the pending sale rate deltas of a pool are
+// kept in a list per period to find the ones crossed by a virtual order execution. Periods that
+// were executed past are never read again.
+#[substreams::handlers::store]
+pub fn store_order_bucket_times(
+    order_sale_rate_deltas: OrderSaleRateDeltas,
+    store: StoreAppend<String>,
+) {
+    order_sale_rate_deltas
+        .deltas
+        .into_iter()
+        .for_each(|delta| {
+            store.append(
+                delta.ordinal,
+                format!(
+                    "pool:{}:period:{}",
+                    delta.pool_id.to_hex(),
+                    bucket_times_period(delta.time)
+                ),
+                delta.time.to_string(),
+            )
+        });
+}
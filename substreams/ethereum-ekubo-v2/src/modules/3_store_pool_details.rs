@@ -24,6 +24,7 @@ fn store_pool_details(changes: BlockChanges, store: StoreSetIfNotExistsProto<Poo
                         .try_into()
                         .unwrap(),
                 ),
+                core: attrs[7].value.clone(),
             };
 
             store.set_if_not_exists(0, component.id, &pool_details);
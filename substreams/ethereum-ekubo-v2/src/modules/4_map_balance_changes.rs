@@ -28,6 +28,9 @@ fn map_balance_changes(
                 tx_events
                     .pool_logs
                     .into_iter()
+                    .filter(|log| {
+                        matches!(log.event, Some(Event::Swapped(_) | Event::PositionUpdated(_)))
+                    })
                     .flat_map(move |log| {
                         let component_id = log.pool_id.to_hex();
                         let pool_details = get_pool_details(store, &component_id);
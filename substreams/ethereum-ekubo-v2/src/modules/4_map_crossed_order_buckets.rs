@@ -0,0 +1,78 @@
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+use substreams::store::{StoreGet, StoreGetArray, StoreGetBigInt, StoreGetInt64};
+use substreams_helper::hex::Hexable;
+
+use crate::{
+    pb::ekubo::{
+        block_transaction_events::transaction_events::pool_log::Event, BlockTransactionEvents,
+        CrossedOrderBucket, CrossedOrderBuckets,
+    },
+    twamm,
+};
+
+#[substreams::handlers::map]
+pub fn map_crossed_order_buckets(
+    block_tx_events: BlockTransactionEvents,
+    last_execution_time_store: StoreGetInt64,
+    order_bucket_times_store: StoreGetArray<String>,
+    order_sale_rate_store: StoreGetBigInt,
+) -> Result<CrossedOrderBuckets, substreams::errors::Error> {
+    let execution_time = block_tx_events.timestamp;
+
+    let mut buckets = vec![];
+
+    for tx_events in block_tx_events.block_transaction_events {
+        let tx = tx_events.transaction;
+
+        for log in tx_events
+            .pool_logs
+            .into_iter()
+            .filter(|log| matches!(log.event, Some(Event::VirtualOrdersExecuted(_))))
+        {
+            let pool_id = log.pool_id.to_hex();
+
+            // The store already contains this execution at the log's ordinal
+            let last_execution_time: u64 = last_execution_time_store
+                .get_at(log.ordinal - 1, format!("pool:{pool_id}"))
+                .ok_or_else(|| anyhow!("TWAMM pool {pool_id} has no last execution time"))?
+                .try_into()?;
+
+            let crossed_times =
+                twamm::crossed_bucket_times(last_execution_time, execution_time, |period| {
+                    order_bucket_times_store
+                        .get_last(format!("pool:{pool_id}:period:{period}"))
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|time| {
+                            time.parse()
+                                .with_context(|| format!("invalid order bucket time {time}"))
+                        })
+                        .collect()
+                })?;
+
+            buckets.extend(
+                crossed_times
+                    .into_iter()
+                    .cartesian_product([false, true])
+                    .filter(|(time, is_token1)| {
+                        let token = if *is_token1 { "token1" } else { "token0" };
+
+                        // Buckets whose deltas cancelled out were already deleted
+                        order_sale_rate_store
+                            .get_last(format!("pool:{pool_id}:{token}:time:{time}:"))
+                            .is_some_and(|sale_rate_delta| !sale_rate_delta.is_zero())
+                    })
+                    .map(|(time, is_token1)| CrossedOrderBucket {
+                        pool_id: log.pool_id.clone(),
+                        time,
+                        is_token1,
+                        ordinal: log.ordinal,
+                        transaction: tx.clone(),
+                    }),
+            );
+        }
+    }
+
+    Ok(CrossedOrderBuckets { buckets })
+}
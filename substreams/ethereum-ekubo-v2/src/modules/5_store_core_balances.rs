@@ -0,0 +1,23 @@
+use substreams::{
+    scalar::BigInt,
+    store::{StoreAdd, StoreAddBigInt, StoreNew},
+};
+
+use crate::pb::ekubo::BlockTransactionEvents;
+
+// The account balances of each Core, tracked from its token transfers so they include saved
+// balances and protocol fees, which aren't part of any pool balance
+#[substreams::handlers::store]
+fn store_core_balances(block_tx_events: BlockTransactionEvents, store: StoreAddBigInt) {
+    block_tx_events
+        .block_transaction_events
+        .into_iter()
+        .flat_map(|tx_events| tx_events.core_balance_deltas)
+        .for_each(|delta| {
+            store.add(
+                delta.ordinal,
+                format!("{}:{}", hex::encode(delta.core), hex::encode(delta.token)),
+                BigInt::from_signed_bytes_be(&delta.delta),
+            );
+        });
+}
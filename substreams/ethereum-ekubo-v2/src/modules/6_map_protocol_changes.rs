@@ -8,13 +8,13 @@ use tycho_substreams::{
     balances::aggregate_balances_changes,
     models::{
         Attribute, BlockBalanceDeltas, BlockChanges, ChangeType, EntityChanges,
-        TransactionChangesBuilder,
+        InterimContractChange, TransactionChangesBuilder,
     },
 };
 
 use crate::pb::ekubo::{
     block_transaction_events::transaction_events::pool_log::Event, BlockTransactionEvents,
-    LiquidityChanges, OrderSaleRateDeltas, SaleRateChanges, TickDeltas,
+    CrossedOrderBuckets, LiquidityChanges, OrderSaleRateDeltas, SaleRateChanges, TickDeltas,
 };
 
 /// Aggregates protocol components and balance changes by transaction.
@@ -28,10 +28,12 @@ fn map_protocol_changes(
     block_tx_events: BlockTransactionEvents,
     balances_map_deltas: BlockBalanceDeltas,
     balances_store_deltas: StoreDeltas,
+    core_balances_store_deltas: StoreDeltas,
     ticks_map_deltas: TickDeltas,
     ticks_store_deltas: StoreDeltas,
     order_sale_rate_map_deltas: OrderSaleRateDeltas,
     order_sale_rate_store_deltas: StoreDeltas,
+    crossed_order_buckets: CrossedOrderBuckets,
     liquidity_changes: LiquidityChanges,
     liquidity_store_deltas: StoreDeltas,
     sale_rate_changes: SaleRateChanges,
@@ -71,6 +73,34 @@ fn map_protocol_changes(
                 });
         });
 
+    // Core account balances
+    core_balances_store_deltas
+        .deltas
+        .into_iter()
+        .zip(
+            block_tx_events
+                .block_transaction_events
+                .iter()
+                .flat_map(|tx_events| {
+                    tx_events
+                        .core_balance_deltas
+                        .iter()
+                        .map(move |delta| (tx_events.transaction.as_ref().unwrap(), delta))
+                }),
+        )
+        .for_each(|(store_delta, (tx, core_delta))| {
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx.clone().into()));
+
+            let mut core_change = InterimContractChange::new(&core_delta.core, false);
+            core_change.upsert_token_balance(
+                &core_delta.token,
+                &BigInt::from_store_bytes(&store_delta.new_value).to_signed_bytes_be(),
+            );
+            builder.add_contract_changes(&core_change);
+        });
+
     // Component balances
     aggregate_balances_changes(balances_store_deltas, balances_map_deltas)
         .into_iter()
@@ -142,6 +172,28 @@ fn map_protocol_changes(
             });
         });
 
+    // TWAMM order sale rate deltas applied by virtual order executions
+    crossed_order_buckets
+        .buckets
+        .into_iter()
+        .for_each(|bucket| {
+            let tx = bucket.transaction.unwrap();
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx.into()));
+
+            let token = if bucket.is_token1 { "token1" } else { "token0" };
+
+            builder.add_entity_change(&EntityChanges {
+                component_id: bucket.pool_id.to_hex(),
+                attributes: vec![Attribute {
+                    name: format!("orders/{}/{}", token, bucket.time),
+                    value: vec![],
+                    change: ChangeType::Deletion.into(),
+                }],
+            });
+        });
+
     // Pool liquidities
     liquidity_store_deltas
         .deltas
@@ -260,6 +312,41 @@ fn maybe_attribute_updates(ev: Event, timestamp: u64) -> Option<Vec<Attribute>>
             value: timestamp.to_be_bytes().to_vec(),
             change: ChangeType::Update.into(),
         }]),
+        Event::OracleSnapshot(ev) => Some(vec![
+            Attribute {
+                name: "oracle/last_snapshot_time".to_string(),
+                value: ev.timestamp.to_be_bytes().to_vec(),
+                change: ChangeType::Update.into(),
+            },
+            Attribute {
+                name: "oracle/seconds_per_liquidity_cumulative".to_string(),
+                value: ev.seconds_per_liquidity_cumulative,
+                change: ChangeType::Update.into(),
+            },
+            Attribute {
+                name: "oracle/tick_cumulative".to_string(),
+                value: ev
+                    .tick_cumulative
+                    .to_be_bytes()
+                    .to_vec(),
+                change: ChangeType::Update.into(),
+            },
+        ]),
+        Event::MevResistStateUpdated(ev) => Some(vec![
+            Attribute {
+                name: "mev_resist/last_update_time".to_string(),
+                value: ev
+                    .last_update_time
+                    .to_be_bytes()
+                    .to_vec(),
+                change: ChangeType::Update.into(),
+            },
+            Attribute {
+                name: "mev_resist/tick_last".to_string(),
+                value: ev.tick_last.to_be_bytes().to_vec(),
+                change: ChangeType::Update.into(),
+            },
+        ]),
         _ => None,
     }
 }
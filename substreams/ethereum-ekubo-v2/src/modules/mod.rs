@@ -15,11 +15,15 @@ mod map_sale_rate_changes;
 mod map_tick_deltas;
 #[path = "2_store_active_ticks.rs"]
 mod store_active_ticks;
+#[path = "2_store_last_execution_times.rs"]
+mod store_last_execution_times;
 
 #[path = "3_map_liquidity_changes.rs"]
 mod map_liquidity_changes;
 #[path = "3_store_active_sale_rates.rs"]
 mod store_active_sale_rates;
+#[path = "3_store_order_bucket_times.rs"]
+mod store_order_bucket_times;
 #[path = "3_store_order_sale_rates.rs"]
 mod store_order_sale_rates;
 #[path = "3_store_pool_details.rs"]
@@ -29,11 +33,15 @@ mod store_tick_liquidities;
 
 #[path = "4_map_balance_changes.rs"]
 mod map_balance_changes;
+#[path = "4_map_crossed_order_buckets.rs"]
+mod map_crossed_order_buckets;
 #[path = "4_store_active_liquidities.rs"]
 mod store_active_liquidities;
 
 #[path = "5_store_balance_changes.rs"]
 mod store_balance_changes;
+#[path = "5_store_core_balances.rs"]
+mod store_core_balances;
 
 #[path = "6_map_protocol_changes.rs"]
 mod map_protocol_changes;
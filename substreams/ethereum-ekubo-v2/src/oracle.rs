@@ -0,0 +1,55 @@
+use ethabi::Address;
+
+use crate::pool_key::{PoolConfig, PoolKey};
+
+impl PoolKey {
+    /// Oracle pools always pair native ETH with the token they track, charge no fees and only
+    /// allow full range liquidity.
+    pub fn from_oracle_token(token: &[u8], oracle_address: &[u8]) -> Self {
+        Self {
+            token0: Address::zero(),
+            token1: <&[u8; 20]>::try_from(token)
+                .unwrap()
+                .into(),
+            config: PoolConfig {
+                fee: 0,
+                tick_spacing: 0,
+                extension: <&[u8; 20]>::try_from(oracle_address)
+                    .unwrap()
+                    .into(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use substreams_helper::hex::Hexable;
+
+    use super::*;
+
+    #[test]
+    fn test_oracle_pool_id() {
+        let usdc = hex::decode("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+        let oracle = hex::decode("51d02a5948496a67827242eabc5725531342527c").unwrap();
+
+        // ETH/USDC pool of the mainnet oracle extension
+        assert_eq!(
+            PoolKey::from_oracle_token(&usdc, &oracle)
+                .into_pool_id()
+                .to_hex(),
+            "0xebe3c0c1e03ed8208fb05c09d6c0126f25232affb264e8bc9270d7a0b985fd10"
+        );
+
+        // The first pool initialized on Core is a fee-less full range ETH/USDC pool keyed the same
+        // way, with another extension
+        // https://etherscan.io/tx/0x7c2e697e73dc1f114a5473d1015c411f10585b2b671bee0bd6d5706895e16b27
+        let extension = hex::decode("514d5de68852628af2f1236f780866989660ada6").unwrap();
+        assert_eq!(
+            PoolKey::from_oracle_token(&usdc, &extension)
+                .into_pool_id()
+                .to_hex(),
+            "0x3565d037d3f435ba5dc6381a9f4dc03d3db8d96c0100f7271b4bbeba3b6b2c14"
+        );
+    }
+}
@@ -59,6 +59,28 @@ pub struct OrderSaleRateDeltas {
     #[prost(message, repeated, tag="1")]
     pub deltas: ::prost::alloc::vec::Vec<OrderSaleRateDelta>,
 }
+/// A time at which pending order sale rate deltas got applied to the active sale rates
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CrossedOrderBucket {
+    /// bytes32
+    #[prost(bytes="vec", tag="1")]
+    pub pool_id: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint64, tag="2")]
+    pub time: u64,
+    #[prost(bool, tag="3")]
+    pub is_token1: bool,
+    #[prost(uint64, tag="4")]
+    pub ordinal: u64,
+    #[prost(message, optional, tag="5")]
+    pub transaction: ::core::option::Option<Transaction>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CrossedOrderBuckets {
+    #[prost(message, repeated, tag="1")]
+    pub buckets: ::prost::alloc::vec::Vec<CrossedOrderBucket>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct LiquidityChange {
@@ -117,6 +139,9 @@ pub struct PoolDetails {
     pub token1: ::prost::alloc::vec::Vec<u8>,
     #[prost(fixed64, tag="3")]
     pub fee: u64,
+    /// address
+    #[prost(bytes="vec", tag="4")]
+    pub core: ::prost::alloc::vec::Vec<u8>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -136,9 +161,27 @@ pub mod block_transaction_events {
         pub transaction: ::core::option::Option<super::Transaction>,
         #[prost(message, repeated, tag="2")]
         pub pool_logs: ::prost::alloc::vec::Vec<transaction_events::PoolLog>,
+        #[prost(message, repeated, tag="3")]
+        pub core_balance_deltas: ::prost::alloc::vec::Vec<transaction_events::CoreBalanceDelta>,
     }
     /// Nested message and enum types in `TransactionEvents`.
     pub mod transaction_events {
+        /// A change of the tokens held by a Core contract
+        #[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct CoreBalanceDelta {
+            #[prost(uint64, tag="1")]
+            pub ordinal: u64,
+            /// address
+            #[prost(bytes="vec", tag="2")]
+            pub core: ::prost::alloc::vec::Vec<u8>,
+            /// address, zero for the native token
+            #[prost(bytes="vec", tag="3")]
+            pub token: ::prost::alloc::vec::Vec<u8>,
+            /// int256
+            #[prost(bytes="vec", tag="4")]
+            pub delta: ::prost::alloc::vec::Vec<u8>,
+        }
         #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
         pub struct PoolLog {
@@ -147,7 +190,7 @@ pub mod block_transaction_events {
             /// bytes32
             #[prost(bytes="vec", tag="2")]
             pub pool_id: ::prost::alloc::vec::Vec<u8>,
-            #[prost(oneof="pool_log::Event", tags="3, 4, 5, 6, 7")]
+            #[prost(oneof="pool_log::Event", tags="3, 4, 5, 6, 7, 8, 9")]
             pub event: ::core::option::Option<pool_log::Event>,
         }
         /// Nested message and enum types in `PoolLog`.
@@ -210,6 +253,9 @@ pub mod block_transaction_events {
                 pub sqrt_ratio: ::prost::alloc::vec::Vec<u8>,
                 #[prost(enumeration="pool_initialized::Extension", tag="6")]
                 pub extension: i32,
+                /// address
+                #[prost(bytes="vec", tag="7")]
+                pub core: ::prost::alloc::vec::Vec<u8>,
             }
             /// Nested message and enum types in `PoolInitialized`.
             pub mod pool_initialized {
@@ -290,6 +336,32 @@ pub mod block_transaction_events {
                 }
             }
             #[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+            pub struct OracleSnapshot {
+                /// address
+                #[prost(bytes="vec", tag="1")]
+                pub token: ::prost::alloc::vec::Vec<u8>,
+                /// block timestamp
+                #[prost(uint64, tag="2")]
+                pub timestamp: u64,
+                /// uint160
+                #[prost(bytes="vec", tag="3")]
+                pub seconds_per_liquidity_cumulative: ::prost::alloc::vec::Vec<u8>,
+                /// int64
+                #[prost(sint64, tag="4")]
+                pub tick_cumulative: i64,
+            }
+            #[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+            pub struct MevResistStateUpdated {
+                /// block timestamp
+                #[prost(uint64, tag="1")]
+                pub last_update_time: u64,
+                /// int32
+                #[prost(sint32, tag="2")]
+                pub tick_last: i32,
+            }
+            #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Oneof)]
             pub enum Event {
                 #[prost(message, tag="3")]
@@ -302,6 +374,10 @@ pub mod block_transaction_events {
                 VirtualOrdersExecuted(VirtualOrdersExecuted),
                 #[prost(message, tag="7")]
                 OrderUpdated(OrderUpdated),
+                #[prost(message, tag="8")]
+                OracleSnapshot(OracleSnapshot),
+                #[prost(message, tag="9")]
+                MevResistStateUpdated(MevResistStateUpdated),
             }
         }
     }
@@ -0,0 +1,124 @@
+use anyhow::{ensure, Result};
+use substreams::scalar::BigInt;
+
+use crate::{
+    deployment_config::LayoutVersion,
+    pb::ekubo::block_transaction_events::transaction_events::pool_log::{
+        OracleSnapshot, Swapped, VirtualOrdersExecuted,
+    },
+    sqrt_ratio::float_sqrt_ratio_to_fixed,
+};
+
+fn ensure_length(name: &str, data: &[u8], version: LayoutVersion, expected: usize) -> Result<()> {
+    ensure!(
+        data.len() == expected,
+        "{name} event data length mismatch for layout {version:?}: expected {expected}, got {}",
+        data.len()
+    );
+    Ok(())
+}
+
+/// Decodes Core's `Swapped` event into the pool id and the event.
+pub fn decode_swapped(data: &[u8], version: LayoutVersion) -> Result<(Vec<u8>, Swapped)> {
+    match version {
+        LayoutVersion::V2 => {
+            ensure_length("swap", data, version, 116)?;
+
+            Ok((
+                data[20..52].to_vec(),
+                Swapped {
+                    delta0: data[52..68].to_vec(),
+                    delta1: data[68..84].to_vec(),
+                    liquidity_after: data[84..100].to_vec(),
+                    sqrt_ratio_after: float_sqrt_ratio_to_fixed(BigInt::from_unsigned_bytes_be(
+                        &data[100..112],
+                    )),
+                    tick_after: i32::from_be_bytes(data[112..116].try_into()?),
+                },
+            ))
+        }
+    }
+}
+
+/// Decodes TWAMM's `VirtualOrdersExecuted` event into the pool id and the event.
+pub fn decode_virtual_orders_executed(
+    data: &[u8],
+    version: LayoutVersion,
+) -> Result<(Vec<u8>, VirtualOrdersExecuted)> {
+    match version {
+        LayoutVersion::V2 => {
+            ensure_length("virtual orders executed", data, version, 60)?;
+
+            Ok((
+                data[0..32].to_vec(),
+                VirtualOrdersExecuted {
+                    token0_sale_rate: data[32..46].to_vec(),
+                    token1_sale_rate: data[46..60].to_vec(),
+                },
+            ))
+        }
+    }
+}
+
+/// Decodes the Oracle's snapshot event.
+pub fn decode_oracle_snapshot(data: &[u8], version: LayoutVersion) -> Result<OracleSnapshot> {
+    match version {
+        LayoutVersion::V2 => {
+            ensure_length("snapshot", data, version, 52)?;
+
+            Ok(OracleSnapshot {
+                token: data[0..20].to_vec(),
+                timestamp: u32::from_be_bytes(data[20..24].try_into()?).into(),
+                seconds_per_liquidity_cumulative: data[24..44].to_vec(),
+                tick_cumulative: i64::from_be_bytes(data[44..52].try_into()?),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_mismatch_is_an_error() {
+        assert!(decode_swapped(&[0; 115], LayoutVersion::V2).is_err());
+        assert!(decode_virtual_orders_executed(&[0; 61], LayoutVersion::V2).is_err());
+        assert!(decode_oracle_snapshot(&[], LayoutVersion::V2).is_err());
+    }
+
+    #[test]
+    fn test_decode_oracle_snapshot() {
+        let token = hex::decode("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+        let mut data = token.clone();
+        data.extend(1742000000u32.to_be_bytes());
+        data.extend([0; 19]);
+        data.push(42);
+        data.extend((-123456789i64).to_be_bytes());
+
+        let ev = decode_oracle_snapshot(&data, LayoutVersion::V2).unwrap();
+
+        assert_eq!(ev.token, token);
+        assert_eq!(ev.timestamp, 1742000000);
+        assert_eq!(
+            BigInt::from_unsigned_bytes_be(&ev.seconds_per_liquidity_cumulative),
+            BigInt::from(42)
+        );
+        assert_eq!(ev.tick_cumulative, -123456789);
+    }
+
+    #[test]
+    fn test_decode_virtual_orders_executed() {
+        let mut data = vec![0xab; 32];
+        data.extend([0; 13]);
+        data.push(1);
+        data.extend([0; 13]);
+        data.push(2);
+
+        let (pool_id, ev) = decode_virtual_orders_executed(&data, LayoutVersion::V2).unwrap();
+
+        assert_eq!(pool_id, vec![0xab; 32]);
+        assert_eq!(BigInt::from_unsigned_bytes_be(&ev.token0_sale_rate), BigInt::from(1));
+        assert_eq!(BigInt::from_unsigned_bytes_be(&ev.token1_sale_rate), BigInt::from(2));
+    }
+}
@@ -1,3 +1,5 @@
+use anyhow::Result;
+use itertools::Itertools;
 use substreams::scalar::BigInt;
 
 use crate::pool_key::{PoolConfig, PoolKey};
@@ -25,3 +27,81 @@ impl PoolKey {
         }
     }
 }
+
+/// Order bucket times are stored by period of `2^16` seconds, so that a virtual order execution
+/// only reads the periods elapsed since the previous one.
+const BUCKET_TIMES_PERIOD_BITS: u32 = 16;
+
+pub fn bucket_times_period(time: u64) -> u64 {
+    time >> BUCKET_TIMES_PERIOD_BITS
+}
+
+/// Returns the order bucket times crossed by a virtual order execution, i.e. the times in
+/// `(last_execution_time, execution_time]`, given the bucket times stored for each period.
+pub fn crossed_bucket_times(
+    last_execution_time: u64,
+    execution_time: u64,
+    period_bucket_times: impl Fn(u64) -> Result<Vec<u64>>,
+) -> Result<Vec<u64>> {
+    if execution_time <= last_execution_time {
+        return Ok(vec![]);
+    }
+
+    let bucket_times = (bucket_times_period(last_execution_time)..=
+        bucket_times_period(execution_time))
+        .map(period_bucket_times)
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(bucket_times
+        .into_iter()
+        .flatten()
+        .filter(|time| *time > last_execution_time && *time <= execution_time)
+        .sorted_unstable()
+        .dedup()
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crossed_bucket_times() {
+        let period = 1 << BUCKET_TIMES_PERIOD_BITS;
+        // Buckets of the first three periods, the one at `2 * period` stored twice
+        let period_bucket_times = |p: u64| {
+            Ok(match p {
+                0 => vec![16, 4096],
+                1 => vec![2 * period - 16, period],
+                2 => vec![2 * period, 2 * period, 2 * period + 256],
+                _ => vec![],
+            })
+        };
+
+        assert_eq!(
+            crossed_bucket_times(16, 2 * period, period_bucket_times).unwrap(),
+            vec![4096, period, 2 * period - 16, 2 * period]
+        );
+        assert_eq!(crossed_bucket_times(period, period + 16, period_bucket_times).unwrap(), vec![]);
+        assert_eq!(
+            crossed_bucket_times(2 * period, 2 * period, period_bucket_times).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_crossed_bucket_times_only_reads_elapsed_periods() {
+        let read = std::cell::RefCell::new(vec![]);
+        crossed_bucket_times(
+            5 << BUCKET_TIMES_PERIOD_BITS,
+            (7 << BUCKET_TIMES_PERIOD_BITS) + 1,
+            |p| {
+                read.borrow_mut().push(p);
+                Ok(vec![])
+            },
+        )
+        .unwrap();
+
+        assert_eq!(read.into_inner(), vec![5, 6, 7]);
+    }
+}
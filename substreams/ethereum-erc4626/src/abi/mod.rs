@@ -0,0 +1,4 @@
+#![allow(clippy::all)]
+pub mod erc4626;
+pub mod linear_rewards_erc4626;
+pub mod xerc4626;
@@ -0,0 +1,8 @@
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+mod abi;
+mod modules;
+mod params;
+mod pb;
+mod yield_models;
+
+pub use modules::*;
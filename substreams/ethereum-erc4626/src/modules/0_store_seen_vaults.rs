@@ -0,0 +1,14 @@
+use super::map_vaults::{seen_vault_key, sightings};
+use crate::params::Params;
+use substreams::store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsInt64};
+use substreams_ethereum::pb::eth::v2::Block;
+
+/// Records the block each candidate vault is first seen in, so that `map_vaults` only reads a
+/// vault once.
+#[substreams::handlers::store]
+pub fn store_seen_vaults(params: String, block: Block, store: StoreSetIfNotExistsInt64) {
+    let params = Params::parse_from_query(&params).expect("Failed to parse params");
+    for (_, address, _) in sightings(&params, &block) {
+        store.set_if_not_exists(0, seen_vault_key(&address), &(block.number as i64));
+    }
+}
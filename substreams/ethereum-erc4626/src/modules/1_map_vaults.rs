@@ -0,0 +1,90 @@
+use crate::{
+    abi::erc4626::{
+        events::{Deposit, Withdraw},
+        functions::Asset,
+    },
+    params::Params,
+    pb::erc4626::v1::{Vault, Vaults},
+    yield_models::YieldModel,
+};
+use anyhow::Result;
+use std::collections::HashSet;
+use substreams::store::{StoreGet, StoreGetInt64};
+use substreams_ethereum::{
+    pb::eth::v2::{Block, CallType, TransactionTrace},
+    Event,
+};
+
+/// Finds the vaults seen for the first time in this block: deployments of listed vaults or by a
+/// factory, and deposits into or withdrawals from listed vaults, so that vaults deployed before the
+/// start block are indexed as well. A vault is found at most once.
+///
+/// Contracts deployed by a factory without an `asset()` function are ignored.
+#[substreams::handlers::map]
+pub fn map_vaults(
+    params: String,
+    block: Block,
+    seen_vaults_store: StoreGetInt64,
+) -> Result<Vaults> {
+    let params = Params::parse_from_query(&params)?;
+    let mut found: HashSet<Vec<u8>> = HashSet::new();
+    let mut vaults = vec![];
+
+    for (tx, address, yield_model) in sightings(&params, &block) {
+        // Only the first sighting of a vault is read, later ones are already indexed
+        if seen_vaults_store.get_last(seen_vault_key(&address)) != Some(block.number as i64) ||
+            !found.insert(address.clone())
+        {
+            continue;
+        }
+        let Some(asset) = Asset {}.call(address.clone()) else {
+            substreams::log::info!("Skipping 0x{}: no asset", hex::encode(&address));
+            continue;
+        };
+        vaults.push(Vault {
+            address,
+            asset,
+            yield_model: yield_model.to_string(),
+            tx_hash: tx.hash.clone(),
+            tx_index: tx.index.into(),
+        });
+    }
+
+    Ok(Vaults { vaults })
+}
+
+/// The candidate vaults seen in a block, in block order, along with the transaction they are seen
+/// in and their yield model.
+pub(super) fn sightings<'a>(
+    params: &'a Params,
+    block: &'a Block,
+) -> impl Iterator<Item = (&'a TransactionTrace, Vec<u8>, YieldModel)> + 'a {
+    block
+        .transactions()
+        .flat_map(move |tx| {
+            tx.calls
+                .iter()
+                .filter(|call| !call.state_reverted)
+                .flat_map(move |call| {
+                    let deployed = (call.call_type() == CallType::Create)
+                        .then(|| params.deployed_vault(&call.address, &call.caller))
+                        .flatten()
+                        .map(|yield_model| (call.address.clone(), yield_model));
+                    let used = call
+                        .logs
+                        .iter()
+                        .filter(|log| Deposit::match_log(log) || Withdraw::match_log(log))
+                        .filter_map(move |log| {
+                            Some((log.address.clone(), params.listed_vault(&log.address)?))
+                        });
+                    deployed
+                        .into_iter()
+                        .chain(used)
+                        .map(move |(address, yield_model)| (tx, address, yield_model))
+                })
+        })
+}
+
+pub(super) fn seen_vault_key(address: &[u8]) -> String {
+    format!("vault:0x{}", hex::encode(address))
+}
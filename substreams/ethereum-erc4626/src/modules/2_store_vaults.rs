@@ -0,0 +1,13 @@
+use crate::pb::erc4626::v1::{Vault, Vaults};
+use substreams::{
+    prelude::{StoreSetIfNotExists, StoreSetIfNotExistsProto},
+    store::StoreNew,
+};
+
+/// Records vaults the first time they are seen.
+#[substreams::handlers::store]
+pub fn store_vaults(map: Vaults, store: StoreSetIfNotExistsProto<Vault>) {
+    for vault in map.vaults {
+        store.set_if_not_exists(0, format!("vault:0x{}", hex::encode(&vault.address)), &vault);
+    }
+}
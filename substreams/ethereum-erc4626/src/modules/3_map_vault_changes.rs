@@ -0,0 +1,226 @@
+use crate::{
+    abi::erc4626::{
+        events::{Deposit, Transfer, Withdraw},
+        functions::TotalSupply,
+    },
+    pb::erc4626::v1::{Vault, VaultChange, VaultChanges, Vaults, YieldAttribute},
+    yield_models::{YieldChange, YieldModel},
+};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use substreams::{
+    scalar::BigInt,
+    store::{StoreGet, StoreGetProto},
+};
+use substreams_ethereum::{
+    pb::eth::v2::{Block, Log},
+    Event,
+};
+
+/// Decodes the changes of the indexed vaults in this block: `Deposit` and `Withdraw` change the
+/// assets, the share mints and burns the share supply, and the yield events of the vault model its
+/// assets and attributes.
+///
+/// Vaults seen for the first time are read once at the end of the block instead, and their events
+/// of the block are skipped as they are part of the read state. The read fails the block if the
+/// vault does not implement its yield model.
+#[substreams::handlers::map]
+pub fn map_vault_changes(
+    block: Block,
+    vaults: Vaults,
+    vaults_store: StoreGetProto<Vault>,
+) -> Result<VaultChanges> {
+    let timestamp = block.timestamp_seconds();
+    let new_vaults: HashMap<Vec<u8>, Vault> = vaults
+        .vaults
+        .into_iter()
+        .filter(|vault| {
+            vaults_store
+                .get_last(vault_key(&vault.address))
+                .is_some_and(|stored| stored.tx_hash == vault.tx_hash)
+        })
+        .map(|vault| (vault.address.clone(), vault))
+        .collect();
+    let mut snapshot_tx: HashMap<Vec<u8>, u64> = new_vaults
+        .values()
+        .map(|vault| (vault.address.clone(), vault.tx_index))
+        .collect();
+
+    let mut changes = vec![];
+    for tx in block.transactions() {
+        for (log, _) in tx
+            .logs_with_calls()
+            .filter(|(_, call)| !call.call.state_reverted)
+        {
+            if let Some(last_tx) = snapshot_tx.get_mut(&log.address) {
+                *last_tx = tx.index.into();
+                continue;
+            }
+            let Some(vault) = vaults_store.get_last(vault_key(&log.address)) else { continue };
+            let yield_model: YieldModel = vault.yield_model.parse()?;
+            let Some((assets, shares, attributes)) = decode_change(log, yield_model, timestamp)
+            else {
+                continue;
+            };
+            changes.push(vault_change(
+                &log.address,
+                tx.index.into(),
+                log.ordinal,
+                (assets, attributes),
+                shares,
+            ));
+        }
+    }
+
+    let mut snapshots = snapshot_tx
+        .into_iter()
+        .collect::<Vec<_>>();
+    snapshots.sort_unstable();
+    for (address, tx_index) in snapshots {
+        let yield_model: YieldModel = new_vaults[&address]
+            .yield_model
+            .parse()?;
+        let (Some(total_supply), Some(snapshot)) =
+            (TotalSupply {}.call(address.clone()), yield_model.snapshot(&address, timestamp))
+        else {
+            return Err(anyhow!(
+                "Failed to read the state of 0x{}, does it implement the {yield_model} yield model?",
+                hex::encode(&address)
+            ));
+        };
+        changes.push(vault_change(&address, tx_index, 0, snapshot, total_supply));
+    }
+
+    Ok(VaultChanges { changes })
+}
+
+/// Decodes a log of a vault into the changes of its accounted assets and share supply, and the
+/// yield model attributes it sets.
+///
+/// The share supply follows the `Transfer`s from and to the zero address rather than `Deposit`
+/// and `Withdraw`, as shares may also be minted or burnt outside of them, e.g. as fees.
+fn decode_change(
+    log: &Log,
+    yield_model: YieldModel,
+    timestamp: u64,
+) -> Option<(BigInt, BigInt, Vec<(&'static str, BigInt)>)> {
+    if let Some(deposit) = Deposit::match_and_decode(log) {
+        Some((deposit.assets, BigInt::zero(), vec![]))
+    } else if let Some(withdraw) = Withdraw::match_and_decode(log) {
+        Some((BigInt::zero() - withdraw.assets, BigInt::zero(), vec![]))
+    } else if let Some(transfer) = Transfer::match_and_decode(log) {
+        let zero = [0u8; 20];
+        let minted = if transfer.from == zero { transfer.value.clone() } else { BigInt::zero() };
+        let burnt = if transfer.to == zero { transfer.value } else { BigInt::zero() };
+        (transfer.from == zero || transfer.to == zero)
+            .then(|| (BigInt::zero(), minted - burnt, vec![]))
+    } else {
+        let (assets, attributes) = yield_model.decode(log, timestamp)?;
+        Some((assets, BigInt::zero(), attributes))
+    }
+}
+
+fn vault_change(
+    address: &[u8],
+    tx_index: u64,
+    ordinal: u64,
+    (assets, attributes): YieldChange,
+    shares: BigInt,
+) -> VaultChange {
+    VaultChange {
+        vault: address.to_vec(),
+        tx_index,
+        ordinal,
+        assets: assets.to_signed_bytes_be(),
+        shares: shares.to_signed_bytes_be(),
+        attributes: attributes
+            .into_iter()
+            .map(|(name, value)| YieldAttribute {
+                name: name.to_string(),
+                value: value.to_signed_bytes_be(),
+            })
+            .collect(),
+    }
+}
+
+fn vault_key(address: &[u8]) -> String {
+    format!("vault:0x{}", hex::encode(address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(topic: &str, indexed: &[u8], words: &[u64]) -> Log {
+        Log {
+            topics: std::iter::once(hex::decode(topic).unwrap())
+                .chain(
+                    indexed
+                        .iter()
+                        .map(|byte| [vec![0u8; 12], vec![*byte; 20]].concat()),
+                )
+                .collect(),
+            data: words
+                .iter()
+                .flat_map(|word| {
+                    let mut encoded = [0u8; 32];
+                    encoded[24..].copy_from_slice(&word.to_be_bytes());
+                    encoded
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_change() {
+        let deposit = log(
+            "dcbc1c05240f31ff3ad067ef1ee35ce4997762752e3a095284754544f4c709d7",
+            &[1, 1],
+            &[100, 90],
+        );
+        assert_eq!(
+            decode_change(&deposit, YieldModel::Static, 0),
+            Some((BigInt::from(100), BigInt::zero(), vec![]))
+        );
+
+        let withdraw = log(
+            "fbde797d201c681b91056529119e0b02407c7bb96a4a2c75c01fc9667232c8db",
+            &[1, 1, 1],
+            &[100, 90],
+        );
+        assert_eq!(
+            decode_change(&withdraw, YieldModel::RewardsCycleData, 0),
+            Some((BigInt::from(-100), BigInt::zero(), vec![]))
+        );
+
+        let transfer = |from, to| {
+            log(
+                "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                &[from, to],
+                &[90],
+            )
+        };
+        assert_eq!(
+            decode_change(&transfer(0, 1), YieldModel::Static, 0),
+            Some((BigInt::zero(), BigInt::from(90), vec![]))
+        );
+        assert_eq!(
+            decode_change(&transfer(1, 0), YieldModel::Static, 0),
+            Some((BigInt::zero(), BigInt::from(-90), vec![]))
+        );
+        assert_eq!(decode_change(&transfer(1, 2), YieldModel::Static, 0), None);
+
+        let distribution =
+            log("b9d196a585c1a894f648393ec7d52cc59ff6d94191579d073ba32b0a74d7f7a6", &[], &[42]);
+        assert_eq!(
+            decode_change(&distribution, YieldModel::RewardsCycleData, 7),
+            Some((
+                BigInt::from(42),
+                BigInt::zero(),
+                vec![("last_rewards_distribution", BigInt::from(7))]
+            ))
+        );
+        assert_eq!(decode_change(&distribution, YieldModel::Static, 7), None);
+    }
+}
@@ -0,0 +1,24 @@
+use crate::pb::erc4626::v1::VaultChanges;
+use substreams::{
+    scalar::BigInt,
+    store::{StoreAdd, StoreAddBigInt, StoreNew},
+};
+
+/// Sums the changes of the assets accounted by the yield model (`vault:0x..:assets`) and of the
+/// share supply (`vault:0x..:shares`) of each vault.
+#[substreams::handlers::store]
+pub fn store_vault_totals(changes: VaultChanges, store: StoreAddBigInt) {
+    for change in changes.changes {
+        let vault = hex::encode(&change.vault);
+        store.add(
+            change.ordinal,
+            format!("vault:0x{vault}:assets"),
+            BigInt::from_signed_bytes_be(&change.assets),
+        );
+        store.add(
+            change.ordinal,
+            format!("vault:0x{vault}:shares"),
+            BigInt::from_signed_bytes_be(&change.shares),
+        );
+    }
+}
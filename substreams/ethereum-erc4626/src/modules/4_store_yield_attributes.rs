@@ -0,0 +1,20 @@
+use crate::pb::erc4626::v1::VaultChanges;
+use substreams::{
+    scalar::BigInt,
+    store::{StoreNew, StoreSet, StoreSetBigInt},
+};
+
+/// Stores the last value of the yield model attributes of each vault, as `vault:0x..:{name}`.
+#[substreams::handlers::store]
+pub fn store_yield_attributes(changes: VaultChanges, store: StoreSetBigInt) {
+    for change in changes.changes {
+        let vault = hex::encode(&change.vault);
+        for attribute in change.attributes {
+            store.set(
+                change.ordinal,
+                format!("vault:0x{vault}:{}", attribute.name),
+                &BigInt::from_signed_bytes_be(&attribute.value),
+            );
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    pb::erc4626::v1::{Vault, VaultChanges, Vaults},
+    yield_models::YieldModel,
+};
+use anyhow::Result;
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+use substreams::{
+    scalar::BigInt,
+    store::{StoreGet, StoreGetBigInt, StoreGetProto},
+};
+use substreams_ethereum::pb::eth::v2::Block;
+use tycho_substreams::prelude::*;
+
+/// Emits the components of new vaults, and the state of the vaults changed in this block: the
+/// assets accounted by their yield model, `total_supply` and the attributes of the model. The
+/// state is attached to the last transaction changing the vault, and is emitted as `Creation`
+/// the first time.
+///
+/// Balances are `totalAssets` at the block timestamp for the asset and `totalSupply` for the
+/// vault share.
+#[substreams::handlers::map]
+pub fn map_protocol_changes(
+    block: Block,
+    vaults: Vaults,
+    vaults_store: StoreGetProto<Vault>,
+    vault_changes: VaultChanges,
+    totals_store: StoreGetBigInt,
+    yield_attributes_store: StoreGetBigInt,
+) -> Result<BlockChanges> {
+    let transactions: HashMap<u64, Transaction> = block
+        .transactions()
+        .map(|tx| (tx.index.into(), tx.into()))
+        .collect();
+    let mut transaction_changes: HashMap<u64, TransactionChangesBuilder> = HashMap::new();
+    let mut new_vaults: HashSet<Vec<u8>> = HashSet::new();
+
+    // Vaults are new if this block is the first time they were seen
+    for vault in vaults.vaults {
+        let Some(stored) = vaults_store.get_last(vault_key(&vault.address)) else { continue };
+        if stored.tx_hash != vault.tx_hash {
+            continue;
+        }
+        let yield_model: YieldModel = vault.yield_model.parse()?;
+        let component = ProtocolComponent::new(&format!("0x{}", hex::encode(&vault.address)))
+            .with_tokens(&[&vault.asset, &vault.address])
+            .with_attributes(&[("yield_model", yield_model.to_string())])
+            .as_swap_type("erc4626_vault", ImplementationType::Custom);
+        transaction_changes
+            .entry(vault.tx_index)
+            .or_insert_with(|| TransactionChangesBuilder::new(&transactions[&vault.tx_index]))
+            .add_protocol_component(&component);
+        new_vaults.insert(vault.address);
+    }
+
+    let mut changed_vaults: HashMap<Vec<u8>, u64> = HashMap::new();
+    for change in vault_changes.changes {
+        let last_tx = changed_vaults
+            .entry(change.vault)
+            .or_default();
+        *last_tx = (*last_tx).max(change.tx_index);
+    }
+
+    for (address, tx_index) in changed_vaults
+        .into_iter()
+        .sorted_unstable_by(|(a, _), (b, _)| a.cmp(b))
+    {
+        let Some(vault) = vaults_store.get_last(vault_key(&address)) else { continue };
+        let yield_model: YieldModel = vault.yield_model.parse()?;
+        let key = vault_key(&address);
+        let assets = totals_store
+            .get_last(format!("{key}:assets"))
+            .unwrap_or_else(BigInt::zero);
+        let total_supply = totals_store
+            .get_last(format!("{key}:shares"))
+            .unwrap_or_else(BigInt::zero);
+        let yield_attributes: HashMap<&str, BigInt> = yield_model
+            .attribute_names()
+            .iter()
+            .filter_map(|name| {
+                Some((*name, yield_attributes_store.get_last(format!("{key}:{name}"))?))
+            })
+            .collect();
+        let total_assets =
+            yield_model.total_assets(&assets, &yield_attributes, block.timestamp_seconds());
+
+        let change =
+            if new_vaults.contains(&address) { ChangeType::Creation } else { ChangeType::Update };
+        let attributes =
+            [(yield_model.assets_attribute(), &assets), ("total_supply", &total_supply)]
+                .into_iter()
+                .chain(
+                    yield_model
+                        .attribute_names()
+                        .iter()
+                        .filter_map(|name| Some((*name, yield_attributes.get(name)?))),
+                )
+                .map(|(name, value)| Attribute {
+                    name: name.to_string(),
+                    value: value.to_signed_bytes_be(),
+                    change: change.into(),
+                })
+                .collect();
+
+        let component_id = format!("0x{}", hex::encode(&address));
+        let builder = transaction_changes
+            .entry(tx_index)
+            .or_insert_with(|| TransactionChangesBuilder::new(&transactions[&tx_index]));
+        builder
+            .add_entity_change(&EntityChanges { component_id: component_id.clone(), attributes });
+        builder.add_balance_change(&BalanceChange {
+            token: vault.asset,
+            balance: total_assets.to_signed_bytes_be(),
+            component_id: component_id.clone().into_bytes(),
+        });
+        builder.add_balance_change(&BalanceChange {
+            token: address,
+            balance: total_supply.to_signed_bytes_be(),
+            component_id: component_id.into_bytes(),
+        });
+    }
+
+    Ok(BlockChanges {
+        block: Some((&block).into()),
+        changes: transaction_changes
+            .drain()
+            .sorted_unstable_by_key(|(index, _)| *index)
+            .filter_map(|(_, builder)| builder.build())
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn vault_key(address: &[u8]) -> String {
+    format!("vault:0x{}", hex::encode(address))
+}
@@ -0,0 +1,28 @@
+pub use map_protocol_changes::map_protocol_changes;
+pub use map_vault_changes::map_vault_changes;
+pub use map_vaults::map_vaults;
+pub use store_seen_vaults::store_seen_vaults;
+pub use store_vault_totals::store_vault_totals;
+pub use store_vaults::store_vaults;
+pub use store_yield_attributes::store_yield_attributes;
+
+#[path = "0_store_seen_vaults.rs"]
+mod store_seen_vaults;
+
+#[path = "1_map_vaults.rs"]
+mod map_vaults;
+
+#[path = "2_store_vaults.rs"]
+mod store_vaults;
+
+#[path = "3_map_vault_changes.rs"]
+mod map_vault_changes;
+
+#[path = "4_store_vault_totals.rs"]
+mod store_vault_totals;
+
+#[path = "4_store_yield_attributes.rs"]
+mod store_yield_attributes;
+
+#[path = "5_map_protocol_changes.rs"]
+mod map_protocol_changes;
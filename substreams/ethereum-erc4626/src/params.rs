@@ -0,0 +1,71 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+use crate::yield_models::YieldModel;
+
+/// A vault, or a factory all deployments of which are vaults to index, along with the yield model
+/// of the vaults.
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VaultSource {
+    /// Address without the 0x prefix.
+    #[serde(with = "hex::serde")]
+    pub address: Vec<u8>,
+    #[serde(default)]
+    pub yield_model: YieldModel,
+}
+
+/// The vaults indexed by the package, e.g.
+/// `vaults[0][address]=...&vaults[0][yield_model]=linear_rewards_cycle&factories[0][address]=...`
+#[derive(Deserialize, Debug, Default)]
+pub struct Params {
+    #[serde(default)]
+    pub vaults: Vec<VaultSource>,
+    #[serde(default)]
+    pub factories: Vec<VaultSource>,
+}
+
+impl Params {
+    pub fn parse_from_query(input: &str) -> Result<Self> {
+        serde_qs::from_str(input).map_err(|e| anyhow!("Failed to parse query params: {}", e))
+    }
+
+    /// The yield model of `address` if it is one of the listed vaults.
+    pub fn listed_vault(&self, address: &[u8]) -> Option<YieldModel> {
+        self.vaults
+            .iter()
+            .find(|vault| vault.address == address)
+            .map(|vault| vault.yield_model)
+    }
+
+    /// The yield model of a contract deployed by `deployer`, if it is one of the listed vaults or
+    /// `deployer` is one of the factories.
+    pub fn deployed_vault(&self, address: &[u8], deployer: &[u8]) -> Option<YieldModel> {
+        self.listed_vault(address).or_else(|| {
+            self.factories
+                .iter()
+                .find(|factory| factory.address == deployer)
+                .map(|factory| factory.yield_model)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_params() {
+        let params = Params::parse_from_query(
+            "vaults[0][address]=ac3e018457b222d93114458476f3e3416abbe38f&vaults[0][yield_model]=linear_rewards_cycle&vaults[1][address]=a663b02cf0a4b149d2ad41910cb81e23e1c41c32&vaults[1][yield_model]=rewards_cycle_data&vaults[2][address]=0202020202020202020202020202020202020202&factories[0][address]=0101010101010101010101010101010101010101",
+        )
+        .unwrap();
+
+        let sfrxeth = hex::decode("ac3e018457b222d93114458476f3e3416abbe38f").unwrap();
+        let sfrax = hex::decode("a663b02cf0a4b149d2ad41910cb81e23e1c41c32").unwrap();
+        assert_eq!(params.listed_vault(&sfrxeth), Some(YieldModel::LinearRewardsCycle));
+        assert_eq!(params.listed_vault(&sfrax), Some(YieldModel::RewardsCycleData));
+        assert_eq!(params.listed_vault(&[2; 20]), Some(YieldModel::Static));
+        assert_eq!(params.deployed_vault(&[4; 20], &[1; 20]), Some(YieldModel::Static));
+        assert_eq!(params.deployed_vault(&[4; 20], &[3; 20]), None);
+    }
+}
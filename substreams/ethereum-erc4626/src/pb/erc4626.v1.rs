@@ -0,0 +1,62 @@
+// @generated
+/// A vault indexed by the package, recorded the first time it is seen.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Vault {
+    #[prost(bytes="vec", tag="1")]
+    pub address: ::prost::alloc::vec::Vec<u8>,
+    /// The underlying token, as returned by `asset()`.
+    #[prost(bytes="vec", tag="2")]
+    pub asset: ::prost::alloc::vec::Vec<u8>,
+    /// Name of the yield model of the vault, see `YieldModel`.
+    #[prost(string, tag="3")]
+    pub yield_model: ::prost::alloc::string::String,
+    /// The transaction the vault was first seen in: its deployment, or its first deposit or
+    /// withdrawal if it was deployed before the start block.
+    #[prost(bytes="vec", tag="4")]
+    pub tx_hash: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint64, tag="5")]
+    pub tx_index: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Vaults {
+    #[prost(message, repeated, tag="1")]
+    pub vaults: ::prost::alloc::vec::Vec<Vault>,
+}
+/// A change of the state of a vault, decoded from one of its events or read once when the vault is
+/// first seen.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VaultChange {
+    #[prost(bytes="vec", tag="1")]
+    pub vault: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint64, tag="2")]
+    pub tx_index: u64,
+    #[prost(uint64, tag="3")]
+    pub ordinal: u64,
+    /// Signed changes of the assets accounted by the yield model and of the share supply. For a
+    /// snapshot, their values when the vault is first seen.
+    #[prost(bytes="vec", tag="4")]
+    pub assets: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes="vec", tag="5")]
+    pub shares: ::prost::alloc::vec::Vec<u8>,
+    /// The yield model attributes set by the change.
+    #[prost(message, repeated, tag="6")]
+    pub attributes: ::prost::alloc::vec::Vec<YieldAttribute>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct YieldAttribute {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(bytes="vec", tag="2")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VaultChanges {
+    #[prost(message, repeated, tag="1")]
+    pub changes: ::prost::alloc::vec::Vec<VaultChange>,
+}
+// @@protoc_insertion_point(module)
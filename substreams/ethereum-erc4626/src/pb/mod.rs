@@ -0,0 +1,8 @@
+// @generated
+pub mod erc4626 {
+    // @@protoc_insertion_point(attribute:erc4626.v1)
+    pub mod v1 {
+        include!("erc4626.v1.rs");
+        // @@protoc_insertion_point(erc4626.v1)
+    }
+}
@@ -0,0 +1,420 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use serde::Deserialize;
+use substreams::scalar::BigInt;
+use substreams_ethereum::{pb::eth::v2::Log, Event};
+
+use crate::abi::{
+    erc4626::functions::TotalAssets,
+    linear_rewards_erc4626::{
+        events::{DistributeRewards, SetMaxDistributionPerSecondPerAsset, SyncRewards},
+        functions::{
+            LastRewardsDistribution, MaxDistributionPerSecondPerAsset, RewardsCycleData,
+            StoredTotalAssets,
+        },
+    },
+    xerc4626::{
+        events::NewRewardsCycle,
+        functions::{LastRewardAmount, LastSync, RewardsCycleEnd},
+    },
+};
+
+/// Scale of `maxDistributionPerSecondPerAsset` in `LinearRewardsErc4626`.
+const PRECISION: u64 = 1_000_000_000_000_000_000;
+
+/// The assets and the yield model attributes of a vault, decoded from an event or read when the
+/// vault is first seen.
+pub type YieldChange = (BigInt, Vec<(&'static str, BigInt)>);
+
+/// How the assets of a vault grow between its events. The assets accounted by the model change
+/// with `Deposit`, `Withdraw` and the yield events decoded by [`YieldModel::decode`], and the
+/// attributes of the model allow computing `totalAssets` at any timestamp.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum YieldModel {
+    /// `totalAssets` only changes through deposits and withdrawals.
+    #[default]
+    Static,
+    /// xERC4626 vaults (e.g. sfrxETH): the rewards of the current cycle vest linearly from
+    /// `lastSync` to `rewardsCycleEnd`. A `NewRewardsCycle` event starts a new cycle.
+    LinearRewardsCycle,
+    /// `LinearRewardsErc4626` vaults (e.g. sFRAX): the rewards of the cycle in `rewardsCycleData`
+    /// are distributed linearly, capped by `maxDistributionPerSecondPerAsset`, and added to
+    /// `storedTotalAssets` by `DistributeRewards`.
+    RewardsCycleData,
+}
+
+impl YieldModel {
+    /// Name of the attribute holding the assets accounted by the model:
+    ///  - `static`: `total_assets`
+    ///  - `linear_rewards_cycle`: `total_assets_at_cycle_end`, the stored assets plus the rewards
+    ///    of the current cycle, i.e. `totalAssets` once the cycle ended
+    ///  - `rewards_cycle_data`: `stored_total_assets`
+    pub fn assets_attribute(&self) -> &'static str {
+        match self {
+            YieldModel::Static => "total_assets",
+            YieldModel::LinearRewardsCycle => "total_assets_at_cycle_end",
+            YieldModel::RewardsCycleData => "stored_total_assets",
+        }
+    }
+
+    /// Names of the attributes set by the yield events of the model.
+    pub fn attribute_names(&self) -> &'static [&'static str] {
+        match self {
+            YieldModel::Static => &[],
+            YieldModel::LinearRewardsCycle => {
+                &["last_reward_amount", "last_sync", "rewards_cycle_end"]
+            }
+            YieldModel::RewardsCycleData => &[
+                "cycle_end",
+                "last_sync",
+                "reward_cycle_amount",
+                "last_rewards_distribution",
+                "max_distribution_per_second_per_asset",
+            ],
+        }
+    }
+
+    /// Decodes a yield event emitted by a vault in a block at `timestamp`, returning the change of
+    /// the accounted assets and the attributes it sets:
+    ///  - `NewRewardsCycle` adds the new rewards to `total_assets_at_cycle_end`, as the rewards of
+    ///    the previous cycle are added to the stored assets
+    ///  - `DistributeRewards` adds the distributed rewards to `stored_total_assets`
+    ///  - `SyncRewards` and `SetMaxDistributionPerSecondPerAsset` only set attributes
+    pub fn decode(&self, log: &Log, timestamp: u64) -> Option<YieldChange> {
+        match self {
+            YieldModel::Static => None,
+            YieldModel::LinearRewardsCycle => {
+                let cycle = NewRewardsCycle::match_and_decode(log)?;
+                Some((
+                    cycle.reward_amount.clone(),
+                    vec![
+                        ("last_reward_amount", cycle.reward_amount),
+                        ("last_sync", BigInt::from(timestamp)),
+                        ("rewards_cycle_end", cycle.cycle_end),
+                    ],
+                ))
+            }
+            YieldModel::RewardsCycleData => {
+                if let Some(distribution) = DistributeRewards::match_and_decode(log) {
+                    Some((
+                        distribution.rewards_to_distribute,
+                        vec![("last_rewards_distribution", BigInt::from(timestamp))],
+                    ))
+                } else if let Some(sync) = SyncRewards::match_and_decode(log) {
+                    Some((
+                        BigInt::zero(),
+                        vec![
+                            ("cycle_end", sync.cycle_end),
+                            ("last_sync", sync.last_sync),
+                            ("reward_cycle_amount", sync.reward_cycle_amount),
+                        ],
+                    ))
+                } else {
+                    SetMaxDistributionPerSecondPerAsset::match_and_decode(log).map(|max| {
+                        (
+                            BigInt::zero(),
+                            vec![("max_distribution_per_second_per_asset", max.new_max)],
+                        )
+                    })
+                }
+            }
+        }
+    }
+
+    /// Reads the accounted assets and the attributes of `vault` at the end of the block it is
+    /// first seen in, at `timestamp`. Later changes are decoded from its events.
+    pub fn snapshot(&self, vault: &[u8], timestamp: u64) -> Option<YieldChange> {
+        let vault = vault.to_vec();
+        match self {
+            YieldModel::Static => Some((TotalAssets {}.call(vault)?, vec![])),
+            YieldModel::LinearRewardsCycle => {
+                let total_assets = TotalAssets {}.call(vault.clone())?;
+                let attributes = vec![
+                    ("last_reward_amount", LastRewardAmount {}.call(vault.clone())?),
+                    ("last_sync", LastSync {}.call(vault.clone())?),
+                    ("rewards_cycle_end", RewardsCycleEnd {}.call(vault)?),
+                ];
+                // `totalAssets` includes the rewards unlocked so far, the assets at the end of the
+                // cycle include all of them
+                let cycle: HashMap<_, _> = attributes.iter().cloned().collect();
+                let locked_rewards =
+                    cycle["last_reward_amount"].clone() - unlocked_rewards(&cycle, timestamp);
+                Some((total_assets + locked_rewards, attributes))
+            }
+            YieldModel::RewardsCycleData => {
+                let (cycle_end, last_sync, reward_cycle_amount) =
+                    RewardsCycleData {}.call(vault.clone())?;
+                Some((
+                    StoredTotalAssets {}.call(vault.clone())?,
+                    vec![
+                        ("cycle_end", cycle_end),
+                        ("last_sync", last_sync),
+                        ("reward_cycle_amount", reward_cycle_amount),
+                        (
+                            "last_rewards_distribution",
+                            LastRewardsDistribution {}.call(vault.clone())?,
+                        ),
+                        (
+                            "max_distribution_per_second_per_asset",
+                            MaxDistributionPerSecondPerAsset {}.call(vault)?,
+                        ),
+                    ],
+                ))
+            }
+        }
+    }
+
+    /// Computes `totalAssets` at `timestamp` from the accounted `assets` and the `attributes` of
+    /// the model, mirroring the vault implementation. Missing attributes are taken as zero.
+    pub fn total_assets(
+        &self,
+        assets: &BigInt,
+        attributes: &HashMap<&str, BigInt>,
+        timestamp: u64,
+    ) -> BigInt {
+        match self {
+            YieldModel::Static => assets.clone(),
+            YieldModel::LinearRewardsCycle => {
+                let last_reward_amount = attribute(attributes, "last_reward_amount");
+                assets.clone() - last_reward_amount + unlocked_rewards(attributes, timestamp)
+            }
+            YieldModel::RewardsCycleData => {
+                assets.clone() + distributable_rewards(assets, attributes, timestamp)
+            }
+        }
+    }
+}
+
+/// Mirrors `xERC4626.totalAssets`: the rewards of the cycle vest linearly until its end.
+fn unlocked_rewards(attributes: &HashMap<&str, BigInt>, timestamp: u64) -> BigInt {
+    let last_reward_amount = attribute(attributes, "last_reward_amount");
+    let last_sync = attribute(attributes, "last_sync");
+    let cycle_end = attribute(attributes, "rewards_cycle_end");
+    let timestamp = BigInt::from(timestamp);
+    if timestamp >= cycle_end {
+        last_reward_amount
+    } else {
+        last_reward_amount * (timestamp - last_sync.clone()) / (cycle_end - last_sync)
+    }
+}
+
+/// Mirrors `previewDistributeRewards` of sFRAX: the rewards since the last distribution, capped
+/// by `maxDistributionPerSecondPerAsset`.
+fn distributable_rewards(
+    stored_total_assets: &BigInt,
+    attributes: &HashMap<&str, BigInt>,
+    timestamp: u64,
+) -> BigInt {
+    let cycle_end = attribute(attributes, "cycle_end");
+    let last_sync = attribute(attributes, "last_sync");
+    let last_distribution = attribute(attributes, "last_rewards_distribution");
+    let timestamp = BigInt::from(timestamp);
+
+    let delta_time = if timestamp > cycle_end {
+        if cycle_end > last_distribution {
+            cycle_end.clone() - last_distribution
+        } else {
+            BigInt::zero()
+        }
+    } else {
+        timestamp - last_distribution
+    };
+    if cycle_end == last_sync {
+        return BigInt::zero();
+    }
+
+    let rewards =
+        attribute(attributes, "reward_cycle_amount") * delta_time.clone() / (cycle_end - last_sync);
+    let max_rewards = attribute(attributes, "max_distribution_per_second_per_asset") *
+        delta_time *
+        stored_total_assets.clone() /
+        BigInt::from(PRECISION);
+    if rewards > max_rewards {
+        max_rewards
+    } else {
+        rewards
+    }
+}
+
+fn attribute(attributes: &HashMap<&str, BigInt>, name: &str) -> BigInt {
+    attributes
+        .get(name)
+        .cloned()
+        .unwrap_or_else(BigInt::zero)
+}
+
+impl std::fmt::Display for YieldModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            YieldModel::Static => write!(f, "static"),
+            YieldModel::LinearRewardsCycle => write!(f, "linear_rewards_cycle"),
+            YieldModel::RewardsCycleData => write!(f, "rewards_cycle_data"),
+        }
+    }
+}
+
+impl std::str::FromStr for YieldModel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "static" => Ok(YieldModel::Static),
+            "linear_rewards_cycle" => Ok(YieldModel::LinearRewardsCycle),
+            "rewards_cycle_data" => Ok(YieldModel::RewardsCycleData),
+            _ => Err(anyhow!("Invalid yield model: {s}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(topic: &str, words: &[u64]) -> Log {
+        Log {
+            topics: vec![hex::decode(topic).unwrap()],
+            data: words
+                .iter()
+                .flat_map(|word| {
+                    let mut encoded = [0u8; 32];
+                    encoded[24..].copy_from_slice(&word.to_be_bytes());
+                    encoded
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn attributes(values: &[(&'static str, u64)]) -> HashMap<&'static str, BigInt> {
+        values
+            .iter()
+            .map(|(name, value)| (*name, BigInt::from(*value)))
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_new_rewards_cycle() {
+        let mut new_cycle =
+            log("2fa39aac60d1c94cda4ab0e86ae9c0ffab5b926e5b827a4ccba1d9b5b2ef596e", &[500]);
+        let mut cycle_end = [0u8; 32];
+        cycle_end[28..].copy_from_slice(&1_700_000_000u32.to_be_bytes());
+        new_cycle
+            .topics
+            .push(cycle_end.to_vec());
+
+        assert_eq!(
+            YieldModel::LinearRewardsCycle.decode(&new_cycle, 1_699_990_000),
+            Some((
+                BigInt::from(500),
+                vec![
+                    ("last_reward_amount", BigInt::from(500)),
+                    ("last_sync", BigInt::from(1_699_990_000)),
+                    ("rewards_cycle_end", BigInt::from(1_700_000_000)),
+                ]
+            ))
+        );
+        assert_eq!(YieldModel::Static.decode(&new_cycle, 1_699_990_000), None);
+        assert_eq!(YieldModel::RewardsCycleData.decode(&new_cycle, 1_699_990_000), None);
+    }
+
+    #[test]
+    fn test_decode_rewards_cycle_data() {
+        let model = YieldModel::RewardsCycleData;
+
+        let distribution =
+            log("b9d196a585c1a894f648393ec7d52cc59ff6d94191579d073ba32b0a74d7f7a6", &[42]);
+        assert_eq!(
+            model.decode(&distribution, 100),
+            Some((BigInt::from(42), vec![("last_rewards_distribution", BigInt::from(100))]))
+        );
+
+        let sync =
+            log("25fad6026c7eaed7508799768642cd3708b71f85d3a322da4b6051df097f8ffe", &[200, 100, 7]);
+        assert_eq!(
+            model.decode(&sync, 100),
+            Some((
+                BigInt::zero(),
+                vec![
+                    ("cycle_end", BigInt::from(200)),
+                    ("last_sync", BigInt::from(100)),
+                    ("reward_cycle_amount", BigInt::from(7)),
+                ]
+            ))
+        );
+
+        let set_max =
+            log("05d530f0fd6974b7d995fd3b71870f5301bb9fe086180bdd0bd36526728f5c6b", &[1, 2]);
+        assert_eq!(
+            model.decode(&set_max, 100),
+            Some((
+                BigInt::zero(),
+                vec![("max_distribution_per_second_per_asset", BigInt::from(2))]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_linear_rewards_cycle_total_assets() {
+        let model = YieldModel::LinearRewardsCycle;
+        let cycle = attributes(&[
+            ("last_reward_amount", 1_000),
+            ("last_sync", 100),
+            ("rewards_cycle_end", 200),
+        ]);
+        let assets = BigInt::from(11_000);
+
+        assert_eq!(model.total_assets(&assets, &cycle, 100), BigInt::from(10_000));
+        assert_eq!(model.total_assets(&assets, &cycle, 150), BigInt::from(10_500));
+        assert_eq!(model.total_assets(&assets, &cycle, 250), BigInt::from(11_000));
+    }
+
+    #[test]
+    fn test_rewards_cycle_data_total_assets() {
+        let model = YieldModel::RewardsCycleData;
+        let stored_total_assets = BigInt::from(1_000_000);
+        let mut cycle = attributes(&[
+            ("cycle_end", 200),
+            ("last_sync", 100),
+            ("reward_cycle_amount", 1_000),
+            ("last_rewards_distribution", 120),
+            ("max_distribution_per_second_per_asset", PRECISION),
+        ]);
+
+        // 30s of a 100s cycle
+        assert_eq!(model.total_assets(&stored_total_assets, &cycle, 150), BigInt::from(1_000_300));
+        // Rewards stop at the cycle end
+        assert_eq!(model.total_assets(&stored_total_assets, &cycle, 300), BigInt::from(1_000_800));
+
+        // Capped to 1e-6 of the assets per second
+        cycle.insert("max_distribution_per_second_per_asset", BigInt::from(PRECISION / 1_000_000));
+        assert_eq!(model.total_assets(&stored_total_assets, &cycle, 150), BigInt::from(1_000_030));
+    }
+
+    #[test]
+    fn test_static_total_assets() {
+        assert_eq!(
+            YieldModel::Static.total_assets(&BigInt::from(5), &HashMap::new(), 100),
+            BigInt::from(5)
+        );
+    }
+
+    #[test]
+    fn test_parse_yield_model() {
+        for model in
+            [YieldModel::Static, YieldModel::LinearRewardsCycle, YieldModel::RewardsCycleData]
+        {
+            assert_eq!(
+                model
+                    .to_string()
+                    .parse::<YieldModel>()
+                    .unwrap(),
+                model
+            );
+        }
+        assert!("compound"
+            .parse::<YieldModel>()
+            .is_err());
+    }
+}
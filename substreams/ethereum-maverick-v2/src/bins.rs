@@ -0,0 +1,284 @@
+use anyhow::{anyhow, Result};
+use ethabi::ethereum_types::U256;
+use substreams::scalar::BigInt;
+use tiny_keccak::{Hasher, Keccak};
+use tycho_substreams::prelude::*;
+
+// Storage slots of the state declared in `MaverickV2Pool.sol`: the `State` struct, spanning two
+// words, and the `Tick.Instance` and `Bin.Instance` mappings
+const STATE_SLOT: u64 = 0;
+const TICKS_MAP_SLOT: u64 = 3;
+const BINS_MAP_SLOT: u64 = 4;
+
+/// The second word of the pool `State`, holding everything but the reserves, which are tracked as
+/// component balances.
+pub fn state_slot() -> [u8; 32] {
+    let mut slot = [0u8; 32];
+    U256::from(STATE_SLOT + 1).to_big_endian(&mut slot);
+    slot
+}
+
+/// Decodes the second word of the pool `State`:
+/// `int64 lastTwaD8; int64 lastLogPriceD8; uint40 lastTimestamp; int32 activeTick; bool isLocked;
+/// uint32 binCounter; uint8 protocolFeeRatioD3`
+pub struct State {
+    pub last_twa_d8: i64,
+    pub last_log_price_d8: i64,
+    pub last_timestamp: u64,
+    pub active_tick: i32,
+    pub bin_counter: u32,
+    pub protocol_fee_ratio_d3: u8,
+}
+
+impl State {
+    pub fn decode(value: &[u8]) -> Self {
+        let value = word(value);
+        Self {
+            last_twa_d8: i64::from_be_bytes(value[24..32].try_into().unwrap()),
+            last_log_price_d8: i64::from_be_bytes(value[16..24].try_into().unwrap()),
+            last_timestamp: BigInt::from_unsigned_bytes_be(&value[11..16]).to_u64(),
+            active_tick: i32::from_be_bytes(value[7..11].try_into().unwrap()),
+            bin_counter: u32::from_be_bytes(value[2..6].try_into().unwrap()),
+            protocol_fee_ratio_d3: value[1],
+        }
+    }
+
+    /// `active_tick`, `last_twa_d8`, `last_log_price_d8`, `last_timestamp` and
+    /// `protocol_fee_ratio_d3`.
+    pub fn attributes(&self) -> Vec<Attribute> {
+        vec![
+            update("active_tick", BigInt::from(self.active_tick).to_signed_bytes_be()),
+            update("last_twa_d8", BigInt::from(self.last_twa_d8).to_signed_bytes_be()),
+            update("last_log_price_d8", BigInt::from(self.last_log_price_d8).to_signed_bytes_be()),
+            update("last_timestamp", BigInt::from(self.last_timestamp).to_signed_bytes_be()),
+            update(
+                "protocol_fee_ratio_d3",
+                BigInt::from(self.protocol_fee_ratio_d3 as u32).to_signed_bytes_be(),
+            ),
+        ]
+    }
+}
+
+/// A storage slot of a natively indexed pool holding tick or bin state we decode into attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PoolSlot {
+    /// One of the first two words of a `Tick.Instance`.
+    Tick { tick: i32, word: u8 },
+    /// One of the first two words of a `Bin.Instance`, the third one being its LP balances.
+    Bin { bin_id: u32, word: u8 },
+}
+
+impl PoolSlot {
+    /// The words of the `Tick.Instance` of a tick, as `(slot, PoolSlot)`.
+    pub fn tick(tick: i32) -> [([u8; 32], PoolSlot); 2] {
+        let key = BigInt::from(tick).to_signed_bytes_be();
+        let fill = if tick < 0 { 0xff } else { 0 };
+        let mut padded_key = [fill; 32];
+        padded_key[32 - key.len()..].copy_from_slice(&key);
+        struct_slots(&padded_key, TICKS_MAP_SLOT)
+            .map(|(slot, word)| (slot, PoolSlot::Tick { tick, word }))
+    }
+
+    /// The words of the `Bin.Instance` of a bin, as `(slot, PoolSlot)`.
+    pub fn bin(bin_id: u32) -> [([u8; 32], PoolSlot); 2] {
+        let mut padded_key = [0u8; 32];
+        padded_key[28..].copy_from_slice(&bin_id.to_be_bytes());
+        struct_slots(&padded_key, BINS_MAP_SLOT)
+            .map(|(slot, word)| (slot, PoolSlot::Bin { bin_id, word }))
+    }
+
+    /// Decodes the new value of the slot into entity attributes:
+    ///  - `ticks/{tick}/reserve_a` and `ticks/{tick}/reserve_b` from the first tick word
+    ///  - `ticks/{tick}/total_supply` from the second tick word
+    ///  - `bins/{id}/merge_bin_balance` and `bins/{id}/tick_balance` from the first bin word
+    ///  - `bins/{id}/total_supply`, `bins/{id}/kind`, `bins/{id}/tick` and `bins/{id}/merge_id`
+    ///    from the second bin word
+    pub fn attributes(&self, new_value: &[u8]) -> Vec<Attribute> {
+        let value = word(new_value);
+        let uint = |range: std::ops::Range<usize>| {
+            BigInt::from_unsigned_bytes_be(&value[range]).to_signed_bytes_be()
+        };
+
+        match self {
+            PoolSlot::Tick { tick, word: 0 } => vec![
+                update(&format!("ticks/{tick}/reserve_a"), uint(16..32)),
+                update(&format!("ticks/{tick}/reserve_b"), uint(0..16)),
+            ],
+            PoolSlot::Tick { tick, .. } => {
+                vec![update(&format!("ticks/{tick}/total_supply"), uint(16..32))]
+            }
+            PoolSlot::Bin { bin_id, word: 0 } => vec![
+                update(&format!("bins/{bin_id}/merge_bin_balance"), uint(16..32)),
+                update(&format!("bins/{bin_id}/tick_balance"), uint(0..16)),
+            ],
+            PoolSlot::Bin { bin_id, .. } => {
+                let tick = i32::from_be_bytes(value[11..15].try_into().unwrap());
+                vec![
+                    update(&format!("bins/{bin_id}/total_supply"), uint(16..32)),
+                    update(&format!("bins/{bin_id}/kind"), uint(15..16)),
+                    update(&format!("bins/{bin_id}/tick"), BigInt::from(tick).to_signed_bytes_be()),
+                    update(&format!("bins/{bin_id}/merge_id"), uint(7..11)),
+                ]
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for PoolSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolSlot::Tick { tick, word } => write!(f, "tick:{tick}:{word}"),
+            PoolSlot::Bin { bin_id, word } => write!(f, "bin:{bin_id}:{word}"),
+        }
+    }
+}
+
+impl std::str::FromStr for PoolSlot {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s
+            .split(':')
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            ["tick", tick, word] => Ok(PoolSlot::Tick { tick: tick.parse()?, word: word.parse()? }),
+            ["bin", bin_id, word] => {
+                Ok(PoolSlot::Bin { bin_id: bin_id.parse()?, word: word.parse()? })
+            }
+            _ => Err(anyhow!("Invalid pool slot: {s}")),
+        }
+    }
+}
+
+/// Storage slots of the first two words of the struct stored under `key` in a mapping declared at
+/// `slot`, with the index of the word.
+fn struct_slots(key: &[u8; 32], slot: u64) -> [([u8; 32], u8); 2] {
+    let mut preimage = [0u8; 64];
+    preimage[..32].copy_from_slice(key);
+    U256::from(slot).to_big_endian(&mut preimage[32..]);
+    let mut base = [0u8; 32];
+    let mut hasher = Keccak::v256();
+    hasher.update(&preimage);
+    hasher.finalize(&mut base);
+
+    let base = U256::from_big_endian(&base);
+    [0u8, 1u8].map(|word| {
+        let mut slot = [0u8; 32];
+        (base + U256::from(word)).to_big_endian(&mut slot);
+        (slot, word)
+    })
+}
+
+/// Storage values are left trimmed, pad them back to a full word.
+fn word(value: &[u8]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[32 - value.len()..].copy_from_slice(value);
+    word
+}
+
+fn update(name: &str, value: Vec<u8>) -> Attribute {
+    Attribute { name: name.to_string(), value, change: ChangeType::Update.into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn names(attributes: &[Attribute]) -> Vec<&str> {
+        attributes
+            .iter()
+            .map(|attribute| attribute.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_state_decode() {
+        let mut value = [0u8; 32];
+        value[24..32].copy_from_slice(&(-1200i64).to_be_bytes());
+        value[16..24].copy_from_slice(&(-1100i64).to_be_bytes());
+        value[11..16].copy_from_slice(&[0, 0x66, 0x70, 0x00, 0x00]);
+        value[7..11].copy_from_slice(&(-3i32).to_be_bytes());
+        value[6] = 1;
+        value[2..6].copy_from_slice(&42u32.to_be_bytes());
+        value[1] = 50;
+
+        let state = State::decode(&value);
+        assert_eq!(state.last_twa_d8, -1200);
+        assert_eq!(state.last_log_price_d8, -1100);
+        assert_eq!(state.last_timestamp, 0x6670_0000);
+        assert_eq!(state.active_tick, -3);
+        assert_eq!(state.bin_counter, 42);
+        assert_eq!(state.protocol_fee_ratio_d3, 50);
+        assert_eq!(
+            names(&state.attributes()),
+            vec![
+                "active_tick",
+                "last_twa_d8",
+                "last_log_price_d8",
+                "last_timestamp",
+                "protocol_fee_ratio_d3"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tick_attributes() {
+        let mut value = [0u8; 32];
+        value[16..32].copy_from_slice(&1000u128.to_be_bytes());
+        value[0..16].copy_from_slice(&3000u128.to_be_bytes());
+
+        let attributes = PoolSlot::Tick { tick: -3, word: 0 }.attributes(&value);
+        assert_eq!(names(&attributes), vec!["ticks/-3/reserve_a", "ticks/-3/reserve_b"]);
+        assert_eq!(attributes[0].value, BigInt::from(1000).to_signed_bytes_be());
+        assert_eq!(attributes[1].value, BigInt::from(3000).to_signed_bytes_be());
+
+        // Trimmed values only hold the low bytes
+        let attributes = PoolSlot::Tick { tick: -3, word: 1 }.attributes(&[0x01, 0x90]);
+        assert_eq!(names(&attributes), vec!["ticks/-3/total_supply"]);
+        assert_eq!(attributes[0].value, BigInt::from(400).to_signed_bytes_be());
+    }
+
+    #[test]
+    fn test_bin_attributes() {
+        let mut value = [0u8; 32];
+        value[16..32].copy_from_slice(&50u128.to_be_bytes());
+        value[15] = 2;
+        value[11..15].copy_from_slice(&(-3i32).to_be_bytes());
+        value[7..11].copy_from_slice(&7u32.to_be_bytes());
+
+        let attributes = PoolSlot::Bin { bin_id: 9, word: 1 }.attributes(&value);
+        assert_eq!(
+            names(&attributes),
+            vec!["bins/9/total_supply", "bins/9/kind", "bins/9/tick", "bins/9/merge_id"]
+        );
+        assert_eq!(attributes[0].value, BigInt::from(50).to_signed_bytes_be());
+        assert_eq!(attributes[1].value, BigInt::from(2).to_signed_bytes_be());
+        assert_eq!(attributes[2].value, BigInt::from(-3).to_signed_bytes_be());
+        assert_eq!(attributes[3].value, BigInt::from(7).to_signed_bytes_be());
+
+        let attributes = PoolSlot::Bin { bin_id: 9, word: 0 }.attributes(&[]);
+        assert_eq!(names(&attributes), vec!["bins/9/merge_bin_balance", "bins/9/tick_balance"]);
+    }
+
+    #[test]
+    fn test_struct_slots() {
+        let [(first, _), (second, _)] = PoolSlot::bin(1);
+        assert_eq!(U256::from_big_endian(&second), U256::from_big_endian(&first) + U256::one());
+
+        // Negative ticks are sign extended
+        let [(slot, _), _] = PoolSlot::tick(-1);
+        assert_eq!(slot, struct_slots(&[0xff; 32], TICKS_MAP_SLOT)[0].0);
+        assert_ne!(PoolSlot::tick(1)[0].0, PoolSlot::bin(1)[0].0);
+    }
+
+    #[test]
+    fn test_pool_slot_round_trip() {
+        for pool_slot in
+            [PoolSlot::Tick { tick: -3, word: 1 }, PoolSlot::Bin { bin_id: 9, word: 0 }]
+        {
+            assert_eq!(PoolSlot::from_str(&pool_slot.to_string()).unwrap(), pool_slot);
+        }
+    }
+}
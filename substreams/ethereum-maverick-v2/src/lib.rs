@@ -1,5 +1,6 @@
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 mod abi;
+mod bins;
 mod events;
 mod modules;
 mod pb;
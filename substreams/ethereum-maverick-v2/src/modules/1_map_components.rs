@@ -3,7 +3,10 @@ use ethabi::ethereum_types::Address;
 use substreams_ethereum::pb::eth::v2::{Block, Log, TransactionTrace};
 use tycho_substreams::prelude::*;
 
-use crate::{abi::factory::events::PoolCreated, modules::utils::Params};
+use crate::{
+    abi::factory::events::PoolCreated,
+    modules::utils::{Params, NATIVE_POOL_TYPE},
+};
 use substreams_helper::{event_handler::EventHandler, hex::Hexable};
 
 #[substreams::handlers::map]
@@ -29,16 +32,26 @@ fn get_new_pools(
             factory_address.as_slice(),
             quoter_address.as_slice(),
         ];
+        let mut attributes = vec![
+            ("fee_a_in", event.fee_a_in.to_signed_bytes_be()),
+            ("fee_b_in", event.fee_b_in.to_signed_bytes_be()),
+            ("tick_spacing", event.tick_spacing.to_signed_bytes_be()),
+            ("kinds", event.kinds.to_signed_bytes_be()),
+        ];
         let new_pool_component = ProtocolComponent::new(&event.pool_address.to_hex())
-            .with_tokens(&[event.token_a.as_slice(), event.token_b.as_slice()])
-            .with_contracts(&contracts)
-            .with_attributes(&[
-                ("fee_a_in", &event.fee_a_in.to_signed_bytes_be()),
-                ("fee_b_in", &event.fee_b_in.to_signed_bytes_be()),
-                ("tick_spacing", &event.tick_spacing.to_signed_bytes_be()),
-                ("kinds", &event.kinds.to_signed_bytes_be()),
-            ])
-            .as_swap_type("maverick_v2_pool", ImplementationType::Vm);
+            .with_tokens(&[event.token_a.as_slice(), event.token_b.as_slice()]);
+        // Native pools are simulated from their attributes, so no contract needs to be indexed
+        let new_pool_component = if params.native {
+            attributes.push(("lookback", event.lookback.to_signed_bytes_be()));
+            new_pool_component
+                .with_attributes(&attributes)
+                .as_swap_type(NATIVE_POOL_TYPE, ImplementationType::Custom)
+        } else {
+            new_pool_component
+                .with_attributes(&attributes)
+                .with_contracts(&contracts)
+                .as_swap_type("maverick_v2_pool", ImplementationType::Vm)
+        };
 
         new_pools.push(TransactionProtocolComponents {
             tx: Some(tycho_tx.clone()),
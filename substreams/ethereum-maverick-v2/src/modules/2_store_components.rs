@@ -1,4 +1,4 @@
-use crate::pb::maverick::v2::Pool;
+use crate::{modules::utils::NATIVE_POOL_TYPE, pb::maverick::v2::Pool};
 use substreams::{
     prelude::{StoreSetIfNotExists, StoreSetIfNotExistsProto},
     store::StoreNew,
@@ -18,6 +18,10 @@ pub fn store_components(
                 token_a: pc.tokens[0].clone(),
                 token_b: pc.tokens[1].clone(),
                 created_tx_hash: tx_pc.tx.as_ref().unwrap().hash.clone(),
+                native: pc
+                    .protocol_type
+                    .as_ref()
+                    .is_some_and(|protocol_type| protocol_type.name == NATIVE_POOL_TYPE),
             };
             store.set_if_not_exists(0, format!("Pool:{pool_address}"), &pool);
         }
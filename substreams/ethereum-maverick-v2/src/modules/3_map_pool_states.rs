@@ -0,0 +1,116 @@
+use crate::{
+    abi::{
+        factory::events::PoolCreated,
+        pool::events::{PoolAddLiquidity, PoolSetVariableFee},
+    },
+    bins::{state_slot, State},
+    pb::maverick::v2::{Pool, PoolState, PoolStates},
+};
+use anyhow::Result;
+use itertools::Itertools;
+use std::collections::HashMap;
+use substreams::{prelude::StoreGetProto, store::StoreGet};
+use substreams_ethereum::{pb::eth::v2::Block, Event};
+use substreams_helper::hex::Hexable;
+
+/// Decodes the changes of the natively indexed pools created or interacted with in this block: the
+/// fees from `PoolCreated` and `PoolSetVariableFee`, the ticks from `PoolAddLiquidity`, and from
+/// the changes of the pool `State` the ticks the pool moved to and the bins it created.
+///
+/// The pool, tick and bin state itself is decoded from the storage changes of the pool, these are
+/// only the ticks and bins whose storage slots to track.
+#[substreams::handlers::map]
+pub fn map_pool_states(block: Block, pools_store: StoreGetProto<Pool>) -> Result<PoolStates> {
+    let mut touched_pools: HashMap<Vec<u8>, PoolState> = HashMap::new();
+    let state_slot = state_slot();
+
+    for trx in block.transactions() {
+        let calls = trx
+            .calls
+            .iter()
+            .filter(|call| !call.state_reverted);
+
+        for log in calls
+            .clone()
+            .flat_map(|call| &call.logs)
+        {
+            // Factory events are only matched for the pools the factory created
+            if let Some(event) = PoolCreated::match_and_decode(log) {
+                if !is_native(&pools_store, &event.pool_address) {
+                    continue;
+                }
+                let state = touch(&mut touched_pools, &event.pool_address);
+                state.tx_index = trx.index.into();
+                state.fee_a_in = event.fee_a_in.to_signed_bytes_be();
+                state.fee_b_in = event.fee_b_in.to_signed_bytes_be();
+                continue;
+            }
+
+            if !is_native(&pools_store, &log.address) {
+                continue;
+            }
+            // Every interaction with a pool emits an event, touched pools are the ones whose
+            // storage changes are decoded
+            let state = touch(&mut touched_pools, &log.address);
+            if let Some(event) = PoolSetVariableFee::match_and_decode(log) {
+                state.tx_index = trx.index.into();
+                state.fee_a_in = event.new_fee_a_in.to_signed_bytes_be();
+                state.fee_b_in = event.new_fee_b_in.to_signed_bytes_be();
+            } else if let Some(event) = PoolAddLiquidity::match_and_decode(log) {
+                state.ticks.extend(
+                    event
+                        .params
+                        .1
+                        .iter()
+                        .map(|tick| tick.to_i32()),
+                );
+            }
+        }
+
+        // Swaps move the movable bins along with the price, creating bins in the new active tick
+        for change in calls
+            .flat_map(|call| &call.storage_changes)
+            .filter(|change| change.key == state_slot)
+        {
+            let Some(state) = touched_pools.get_mut(&change.address) else {
+                continue;
+            };
+            let old_state = State::decode(&change.old_value);
+            let new_state = State::decode(&change.new_value);
+            state.ticks.push(new_state.active_tick);
+            state
+                .bin_ids
+                .extend(old_state.bin_counter + 1..=new_state.bin_counter);
+        }
+    }
+
+    let pools = touched_pools
+        .into_values()
+        .sorted_unstable_by(|a, b| a.address.cmp(&b.address))
+        .map(|mut state| {
+            state.ticks = state
+                .ticks
+                .into_iter()
+                .unique()
+                .collect();
+            state
+        })
+        .collect();
+
+    Ok(PoolStates { pools })
+}
+
+fn is_native(pools_store: &StoreGetProto<Pool>, address: &[u8]) -> bool {
+    pools_store
+        .get_last(format!("Pool:{}", address.to_hex()))
+        .is_some_and(|pool| pool.native)
+}
+
+fn touch<'a>(
+    touched_pools: &'a mut HashMap<Vec<u8>, PoolState>,
+    address: &[u8],
+) -> &'a mut PoolState {
+    touched_pools
+        .entry(address.to_vec())
+        .or_insert_with(|| PoolState { address: address.to_vec(), ..Default::default() })
+}
@@ -0,0 +1,27 @@
+use crate::{bins::PoolSlot, pb::maverick::v2::PoolStates};
+use substreams::store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsString};
+use substreams_helper::hex::Hexable;
+
+/// Maps the storage slots of natively indexed pools holding tick and bin state to the tick or bin
+/// they hold. Ticks and bins are only ever added, so each slot is registered once.
+#[substreams::handlers::store]
+pub fn store_pool_slots(pool_states: PoolStates, slots_store: StoreSetIfNotExistsString) {
+    for state in pool_states.pools {
+        let pool = state.address.to_hex();
+        let tick_slots = state
+            .ticks
+            .into_iter()
+            .flat_map(PoolSlot::tick);
+        let bin_slots = state
+            .bin_ids
+            .into_iter()
+            .flat_map(PoolSlot::bin);
+        for (slot, pool_slot) in tick_slots.chain(bin_slots) {
+            slots_store.set_if_not_exists(
+                0,
+                format!("Pool:{pool}:slot:{}", hex::encode(slot)),
+                &pool_slot.to_string(),
+            );
+        }
+    }
+}
@@ -1,8 +1,19 @@
-use crate::{modules::utils::Params, pb::maverick::v2::Pool};
+use crate::{
+    bins::{state_slot, PoolSlot, State},
+    modules::utils::{Params, NATIVE_POOL_TYPE},
+    pb::maverick::v2::{Pool, PoolStates},
+};
 use anyhow::Result;
 use itertools::Itertools;
-use std::collections::HashMap;
-use substreams::{pb::substreams::StoreDeltas, prelude::StoreGetProto, store::StoreGet};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    str::FromStr,
+};
+use substreams::{
+    pb::substreams::StoreDeltas,
+    prelude::StoreGetProto,
+    store::{StoreGet, StoreGetString},
+};
 use substreams_ethereum::pb::eth::v2::Block;
 use substreams_helper::hex::Hexable;
 use tycho_substreams::{
@@ -17,6 +28,8 @@ pub fn map_protocol_changes(
     balance_deltas: BlockBalanceDeltas,
     pool_store: StoreGetProto<Pool>,
     balance_store: StoreDeltas,
+    pool_states: PoolStates,
+    pool_slots_store: StoreGetString,
 ) -> Result<BlockChanges> {
     let params = Params::parse_from_query(&params)?;
     let (factory_address, quoter_address) = params.decode_addresses()?;
@@ -54,12 +67,26 @@ pub fn map_protocol_changes(
                 });
         });
 
+    // Only native pools have a state
+    add_native_changes(&block, pool_states, &pool_slots_store, &mut transaction_changes)?;
+
+    // VM pools track the storage of the factory, which only changes when it creates a pool
+    let created_vm_pool = protocol_components
+        .tx_components
+        .iter()
+        .flat_map(|tx_component| &tx_component.components)
+        .any(|component| {
+            component
+                .protocol_type
+                .as_ref()
+                .is_some_and(|protocol_type| protocol_type.name != NATIVE_POOL_TYPE)
+        });
     extract_contract_changes_builder(
         &block,
         |addr| {
             pool_store
                 .get_last(format!("Pool:0x{}", hex::encode(addr)))
-                .is_some() ||
+                .is_some_and(|pool| !pool.native) ||
                 addr.eq(factory_address.as_slice())
         },
         &mut transaction_changes,
@@ -116,3 +143,93 @@ pub fn map_protocol_changes(
             .collect::<Vec<_>>(),
     })
 }
+
+/// Emits the state of natively indexed pools as entity attributes: the fees set by their events,
+/// and their pool `State` and registered tick and bin slots decoded from their storage changes.
+fn add_native_changes(
+    block: &Block,
+    pool_states: PoolStates,
+    pool_slots_store: &StoreGetString,
+    transaction_changes: &mut HashMap<u64, TransactionChangesBuilder>,
+) -> Result<()> {
+    let state_slot = state_slot();
+    let touched_pools: HashSet<&[u8]> = pool_states
+        .pools
+        .iter()
+        .map(|state| state.address.as_slice())
+        .collect();
+
+    for trx in block.transactions() {
+        // The last value of each slot of the touched pools in the transaction
+        let slot_values: BTreeMap<(&[u8], &[u8]), &[u8]> = trx
+            .calls
+            .iter()
+            .filter(|call| !call.state_reverted)
+            .flat_map(|call| &call.storage_changes)
+            .filter(|change| touched_pools.contains(change.address.as_slice()))
+            .sorted_unstable_by_key(|change| change.ordinal)
+            .map(|change| {
+                ((change.address.as_slice(), change.key.as_slice()), change.new_value.as_slice())
+            })
+            .collect();
+
+        let mut attributes: BTreeMap<String, Vec<Attribute>> = BTreeMap::new();
+        for ((address, slot), value) in slot_values {
+            let pool = address.to_hex();
+            let slot_attributes = if slot == state_slot {
+                State::decode(value).attributes()
+            } else if let Some(pool_slot) =
+                pool_slots_store.get_last(format!("Pool:{pool}:slot:{}", hex::encode(slot)))
+            {
+                PoolSlot::from_str(&pool_slot)?.attributes(value)
+            } else {
+                continue;
+            };
+            attributes
+                .entry(pool)
+                .or_default()
+                .extend(slot_attributes);
+        }
+
+        if attributes.is_empty() {
+            continue;
+        }
+        let builder = transaction_changes
+            .entry(trx.index.into())
+            .or_insert_with(|| TransactionChangesBuilder::new(&trx.into()));
+        for (component_id, attributes) in attributes {
+            builder.add_entity_change(&EntityChanges { component_id, attributes });
+        }
+    }
+
+    for state in pool_states
+        .pools
+        .into_iter()
+        .filter(|state| !state.fee_a_in.is_empty())
+    {
+        let tx = block
+            .transactions()
+            .find(|tx| u64::from(tx.index) == state.tx_index)
+            .expect("pool fees should be set by a transaction of the block");
+        transaction_changes
+            .entry(state.tx_index)
+            .or_insert_with(|| TransactionChangesBuilder::new(&tx.into()))
+            .add_entity_change(&EntityChanges {
+                component_id: state.address.to_hex(),
+                attributes: vec![
+                    Attribute {
+                        name: "fee_a_in".to_string(),
+                        value: state.fee_a_in,
+                        change: ChangeType::Update.into(),
+                    },
+                    Attribute {
+                        name: "fee_b_in".to_string(),
+                        value: state.fee_b_in,
+                        change: ChangeType::Update.into(),
+                    },
+                ],
+            });
+    }
+
+    Ok(())
+}
@@ -1,8 +1,10 @@
 pub use map_components::map_components;
+pub use map_pool_states::map_pool_states;
 pub use map_protocol_changes::map_protocol_changes;
 pub use map_relative_balances::map_relative_balances;
 pub use store_balances::store_balances;
 pub use store_components::store_components;
+pub use store_pool_slots::store_pool_slots;
 
 #[path = "1_map_components.rs"]
 mod map_components;
@@ -13,9 +15,15 @@ mod store_components;
 #[path = "3_map_relative_balances.rs"]
 mod map_relative_balances;
 
+#[path = "3_map_pool_states.rs"]
+mod map_pool_states;
+
 #[path = "4_store_balances.rs"]
 mod store_balances;
 
+#[path = "4_store_pool_slots.rs"]
+mod store_pool_slots;
+
 #[path = "5_map_protocol_changes.rs"]
 mod map_protocol_changes;
 mod utils;
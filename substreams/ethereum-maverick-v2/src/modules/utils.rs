@@ -1,10 +1,17 @@
 use anyhow::{anyhow, Result};
 use serde::Deserialize;
 
+/// Protocol type name of the pools indexed natively.
+pub const NATIVE_POOL_TYPE: &str = "maverick_v2_native_pool";
+
 #[derive(Debug, Deserialize)]
 pub struct Params {
     pub factory: String,
     pub quoter: String,
+    /// Index pools natively, with their bins as entity attributes, instead of as VM components.
+    /// Only read by `map_components`, the other modules tell native pools from their component.
+    #[serde(default)]
+    pub native: bool,
 }
 
 impl Params {
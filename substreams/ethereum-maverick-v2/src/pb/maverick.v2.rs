@@ -10,5 +10,39 @@ pub struct Pool {
     pub token_b: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes="vec", tag="4")]
     pub created_tx_hash: ::prost::alloc::vec::Vec<u8>,
+    /// Whether the pool is indexed natively, with its bins as entity attributes.
+    #[prost(bool, tag="5")]
+    pub native: bool,
+}
+/// Changes of a natively indexed pool created or interacted with in a block, decoded from its
+/// events. Its pool, tick and bin state is decoded from its storage changes, these are the ticks
+/// and bins whose slots to track.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PoolState {
+    #[prost(bytes="vec", tag="1")]
+    pub address: ::prost::alloc::vec::Vec<u8>,
+    /// Index of the last transaction of the block setting the pool fees.
+    #[prost(uint64, tag="2")]
+    pub tx_index: u64,
+    /// Fees set by the creation or the last `PoolSetVariableFee` event of the block, empty if
+    /// unchanged.
+    #[prost(bytes="vec", tag="3")]
+    pub fee_a_in: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes="vec", tag="4")]
+    pub fee_b_in: ::prost::alloc::vec::Vec<u8>,
+    /// Ticks liquidity was added to or the pool moved to in the block, the latter receiving the bins
+    /// moved along with the price.
+    #[prost(int32, repeated, tag="5")]
+    pub ticks: ::prost::alloc::vec::Vec<i32>,
+    /// Bins created in the block.
+    #[prost(uint32, repeated, tag="6")]
+    pub bin_ids: ::prost::alloc::vec::Vec<u32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PoolStates {
+    #[prost(message, repeated, tag="1")]
+    pub pools: ::prost::alloc::vec::Vec<PoolState>,
 }
 // @@protoc_insertion_point(module)
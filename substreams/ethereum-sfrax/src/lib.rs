@@ -1,3 +1,4 @@
 mod abi;
 mod modules;
+mod params;
 pub mod pb;
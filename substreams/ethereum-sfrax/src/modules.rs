@@ -1,11 +1,19 @@
-use crate::abi;
-use anyhow::Result;
+use crate::{
+    abi::{
+        self,
+        stakedfrax_contract::functions::{StoredTotalAssets, TotalSupply},
+    },
+    params::Params,
+};
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
 use substreams::{
-    hex,
     pb::substreams::StoreDeltas,
-    store::{StoreAdd, StoreAddBigInt, StoreAddInt64, StoreGet, StoreGetInt64, StoreNew},
+    store::{
+        StoreAddBigInt, StoreGet, StoreGetInt64, StoreGetString, StoreNew, StoreSet,
+        StoreSetIfNotExists, StoreSetIfNotExistsInt64, StoreSetString,
+    },
 };
 use substreams_ethereum::{
     pb::eth::{self},
@@ -15,13 +23,32 @@ use tycho_substreams::{
     balances::aggregate_balances_changes, contract::extract_contract_changes, prelude::*,
 };
 
+const START_BLOCK_KEY: &str = "start_block";
+
+/// Stores the first block processed by the modules, i.e. their `initialBlock`, at which a vault
+/// deployed before it is bootstrapped.
+#[substreams::handlers::store]
+pub fn store_start_block(block: eth::v2::Block, store: StoreSetIfNotExistsInt64) {
+    store.set_if_not_exists(0, START_BLOCK_KEY, &(block.number as i64));
+}
+
 #[substreams::handlers::map]
 pub fn map_components(
     params: String,
     block: eth::v2::Block,
+    start_block_store: StoreGetInt64,
 ) -> Result<BlockTransactionProtocolComponents, anyhow::Error> {
-    let vault_address = hex::decode(params).unwrap();
-    let locked_asset = find_deployed_underlying_address(&vault_address).unwrap();
+    let params = Params::parse_from_query(&params)?;
+    let is_start_block = start_block_store.get_last(START_BLOCK_KEY) == Some(block.number as i64);
+    if let Some(tx) = bootstrap_transaction(&params, &block, is_start_block)? {
+        return Ok(BlockTransactionProtocolComponents {
+            tx_components: vec![TransactionProtocolComponents {
+                tx: Some(tx.into()),
+                components: vec![vault_component(&params)],
+            }],
+        });
+    }
+
     // We store these as a hashmap by tx hash since we need to agg by tx hash later
     Ok(BlockTransactionProtocolComponents {
         tx_components: block
@@ -33,15 +60,8 @@ pub fn map_components(
                     .filter_map(|_| {
                         // address doesn't exist before contract deployment, hence the first tx with
                         // a log.address = vault_address is the deployment tx
-                        if is_deployment_tx(tx, &vault_address) {
-                            Some(
-                                ProtocolComponent::at_contract(&vault_address)
-                                    .with_tokens(&[
-                                        locked_asset.as_slice(),
-                                        vault_address.as_slice(),
-                                    ])
-                                    .as_swap_type("sfrax_vault", ImplementationType::Vm),
-                            )
+                        if is_deployment_tx(tx, &params.address) {
+                            Some(vault_component(&params))
                         } else {
                             None
                         }
@@ -58,45 +78,48 @@ pub fn map_components(
     })
 }
 
+/// Stores the underlying token of the vaults, hex encoded, with the pool id as the key
 #[substreams::handlers::store]
-pub fn store_components(map: BlockTransactionProtocolComponents, store: StoreAddInt64) {
-    store.add_many(
-        0,
-        &map.tx_components
-            .iter()
-            .flat_map(|tx_components| &tx_components.components)
-            .map(|component| format!("pool:{0}", component.id))
-            .collect::<Vec<_>>(),
-        1,
-    );
+pub fn store_components(map: BlockTransactionProtocolComponents, store: StoreSetString) {
+    map.tx_components
+        .iter()
+        .flat_map(|tx_components| &tx_components.components)
+        .for_each(|component| {
+            store.set(0, format!("pool:{0}", component.id), &hex::encode(&component.tokens[0]))
+        });
 }
 
 #[substreams::handlers::map]
 pub fn map_relative_balances(
     block: eth::v2::Block,
-    store: StoreGetInt64,
+    components: BlockTransactionProtocolComponents,
+    store: StoreGetString,
 ) -> Result<BlockBalanceDeltas, anyhow::Error> {
-    let balance_deltas = block
-        .logs()
-        .flat_map(|vault_log| {
-            let mut deltas = Vec::new();
+    // The balances of bootstrapped vaults are snapshotted at the end of the block, so they already
+    // account for the events of the block.
+    let (bootstrapped, mut balance_deltas) = bootstrap_balances(&block, &components)?;
 
-            if let Some(ev) =
-                abi::stakedfrax_contract::events::Withdraw::match_and_decode(vault_log.log)
-            {
+    balance_deltas.extend(
+        block
+            .logs()
+            .filter(|vault_log| !bootstrapped.contains(vault_log.address()))
+            .filter_map(|vault_log| {
+                let address_hex = format!("0x{}", hex::encode(vault_log.address()));
+                let underlying = store.get_last(format!("pool:{address_hex}"))?;
+                Some((vault_log, address_hex, hex::decode(underlying).ok()?))
+            })
+            .flat_map(|(vault_log, address_hex, underlying)| {
+                let mut deltas = Vec::new();
                 let address_bytes_be = vault_log.address();
-                let address_hex = format!("0x{}", hex::encode(address_bytes_be));
-                if store
-                    .get_last(format!("pool:{address_hex}"))
-                    .is_some()
+
+                if let Some(ev) =
+                    abi::stakedfrax_contract::events::Withdraw::match_and_decode(vault_log.log)
                 {
                     deltas.extend_from_slice(&[
                         BalanceDelta {
                             ord: vault_log.ordinal(),
                             tx: Some(vault_log.receipt.transaction.into()),
-                            token: find_deployed_underlying_address(address_bytes_be)
-                                .unwrap()
-                                .to_vec(),
+                            token: underlying,
                             delta: ev.assets.neg().to_signed_bytes_be(),
                             component_id: address_hex.as_bytes().to_vec(),
                         },
@@ -114,24 +137,14 @@ pub fn map_relative_balances(
                         ev.assets,
                         ev.shares
                     );
-                }
-            } else if let Some(ev) =
-                abi::stakedfrax_contract::events::Deposit::match_and_decode(vault_log.log)
-            {
-                let address_bytes_be = vault_log.address();
-                let address_hex = format!("0x{}", hex::encode(address_bytes_be));
-
-                if store
-                    .get_last(format!("pool:{address_hex}"))
-                    .is_some()
+                } else if let Some(ev) =
+                    abi::stakedfrax_contract::events::Deposit::match_and_decode(vault_log.log)
                 {
                     deltas.extend_from_slice(&[
                         BalanceDelta {
                             ord: vault_log.ordinal(),
                             tx: Some(vault_log.receipt.transaction.into()),
-                            token: find_deployed_underlying_address(address_bytes_be)
-                                .unwrap()
-                                .to_vec(),
+                            token: underlying,
                             delta: ev.assets.to_signed_bytes_be(),
                             component_id: address_hex.as_bytes().to_vec(),
                         },
@@ -149,26 +162,21 @@ pub fn map_relative_balances(
                         ev.assets,
                         ev.shares
                     );
-                }
-            } else if let Some(ev) =
-                abi::stakedfrax_contract::events::DistributeRewards::match_and_decode(vault_log.log)
-            {
-                let address_bytes_be = vault_log.address();
-                let address_hex = format!("0x{}", hex::encode(address_bytes_be));
-
-                if store
-                    .get_last(format!("pool:{address_hex}"))
-                    .is_some()
+                } else if let Some(ev) =
+                    abi::stakedfrax_contract::events::DistributeRewards::match_and_decode(
+                        vault_log.log,
+                    )
                 {
-                    deltas.extend_from_slice(&[BalanceDelta {
+                    // Distributed rewards are added to the `storedTotalAssets` of the vault
+                    deltas.push(BalanceDelta {
                         ord: vault_log.ordinal(),
                         tx: Some(vault_log.receipt.transaction.into()),
-                        token: address_bytes_be.to_vec(),
+                        token: underlying,
                         delta: ev
                             .rewards_to_distribute
                             .to_signed_bytes_be(),
                         component_id: address_hex.as_bytes().to_vec(),
-                    }]);
+                    });
                     // Log token and amount without encoding
                     substreams::log::debug!(
                         "DistributeRewards: vault: {}, frax:+ {}",
@@ -176,10 +184,9 @@ pub fn map_relative_balances(
                         ev.rewards_to_distribute
                     );
                 }
-            }
-            deltas
-        })
-        .collect::<Vec<_>>();
+                deltas
+            }),
+    );
 
     Ok(BlockBalanceDeltas { balance_deltas })
 }
@@ -194,7 +201,7 @@ pub fn map_protocol_changes(
     block: eth::v2::Block,
     grouped_components: BlockTransactionProtocolComponents,
     deltas: BlockBalanceDeltas,
-    components_store: StoreGetInt64,
+    components_store: StoreGetString,
     balance_store: StoreDeltas,
 ) -> Result<BlockChanges, anyhow::Error> {
     let mut transaction_contract: HashMap<u64, TransactionChanges> = HashMap::new();
@@ -256,6 +263,87 @@ pub fn map_protocol_changes(
     })
 }
 
+/// Returns the transaction the vault is bootstrapped in, if `block` is the start block and the
+/// vault was deployed before it. This is the last transaction of the block, as the balances of the
+/// vault are snapshotted at the end of the block.
+fn bootstrap_transaction<'a>(
+    params: &Params,
+    block: &'a eth::v2::Block,
+    is_start_block: bool,
+) -> Result<Option<&'a eth::v2::TransactionTrace>> {
+    let deployed_before = is_start_block &&
+        !block
+            .transactions()
+            .any(|tx| is_deployment_tx(tx, &params.address)) &&
+        TotalSupply {}
+            .call(params.address.clone())
+            .is_some();
+    if !deployed_before {
+        return Ok(None);
+    }
+    block
+        .transactions()
+        .last()
+        .map(Some)
+        .ok_or_else(|| {
+            anyhow!(
+                "Block {} has no transaction to bootstrap the vault in, start at another block",
+                block.number
+            )
+        })
+}
+
+/// Returns the vaults bootstrapped in this block, i.e. the new components that weren't deployed
+/// in their transaction, and the snapshot of their balances at the end of the block: their
+/// `storedTotalAssets` of the underlying and the `totalSupply` of their shares.
+///
+/// These are the balances `map_relative_balances` tracks afterwards: deposits, withdrawals and
+/// distributed rewards move `storedTotalAssets`, and deposits and withdrawals mint and burn the
+/// shares. The rewards not distributed yet are not part of the balances.
+fn bootstrap_balances(
+    block: &eth::v2::Block,
+    components: &BlockTransactionProtocolComponents,
+) -> Result<(HashSet<Vec<u8>>, Vec<BalanceDelta>)> {
+    let mut bootstrapped = HashSet::new();
+    let mut deltas = Vec::new();
+    for tx_components in &components.tx_components {
+        let tx = tx_components.tx.as_ref().unwrap();
+        let Some(trace) = block
+            .transactions()
+            .find(|trace| trace.hash == tx.hash)
+        else {
+            continue;
+        };
+        for component in &tx_components.components {
+            let address = hex::decode(component.id.trim_start_matches("0x"))?;
+            if is_deployment_tx(trace, &address) {
+                continue;
+            }
+            let (Some(stored_total_assets), Some(total_supply)) =
+                (StoredTotalAssets {}.call(address.clone()), TotalSupply {}.call(address.clone()))
+            else {
+                return Err(anyhow!("Failed to snapshot the balances of {}", component.id));
+            };
+            deltas.extend(
+                [
+                    (component.tokens[0].clone(), stored_total_assets),
+                    (address.clone(), total_supply),
+                ]
+                .into_iter()
+                .map(|(token, balance)| BalanceDelta {
+                    ord: trace.end_ordinal,
+                    tx: Some(tx.clone()),
+                    token,
+                    delta: balance.to_signed_bytes_be(),
+                    component_id: component.id.as_bytes().to_vec(),
+                }),
+            );
+            bootstrapped.insert(address);
+        }
+    }
+    Ok((bootstrapped, deltas))
+}
+
 fn is_deployment_tx(tx: &eth::v2::TransactionTrace, vault_address: &[u8]) -> bool {
     let created_accounts = tx
         .calls
@@ -267,11 +355,8 @@ fn is_deployment_tx(tx: &eth::v2::TransactionTrace, vault_address: &[u8]) -> boo
     created_accounts.contains(vault_address)
 }
 
-fn find_deployed_underlying_address(vault_address: &[u8]) -> Option<[u8; 20]> {
-    match vault_address {
-        hex!("A663B02CF0a4b149d2aD41910CB81e23e1c41c32") => {
-            Some(hex!("853d955aCEf822Db058eb8505911ED77F175b99e"))
-        }
-        _ => None,
-    }
+fn vault_component(params: &Params) -> ProtocolComponent {
+    ProtocolComponent::at_contract(&params.address)
+        .with_tokens(&[params.underlying.as_slice(), params.address.as_slice()])
+        .as_swap_type("sfrax_vault", ImplementationType::Vm)
 }
@@ -0,0 +1,35 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+/// The vault indexed by the package, e.g. `address=...&underlying=...`
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The vault, without the 0x prefix.
+    #[serde(with = "hex::serde")]
+    pub address: Vec<u8>,
+    /// The token deposited into the vault, without the 0x prefix.
+    #[serde(with = "hex::serde")]
+    pub underlying: Vec<u8>,
+}
+
+impl Params {
+    pub fn parse_from_query(input: &str) -> Result<Self> {
+        serde_qs::from_str(input).map_err(|e| anyhow!("Failed to parse query params: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_from_query() {
+        let params = Params::parse_from_query(
+            "address=a663b02cf0a4b149d2ad41910cb81e23e1c41c32&underlying=853d955acef822db058eb8505911ed77f175b99e",
+        )
+        .unwrap();
+
+        assert_eq!(hex::encode(params.address), "a663b02cf0a4b149d2ad41910cb81e23e1c41c32");
+        assert_eq!(hex::encode(params.underlying), "853d955acef822db058eb8505911ed77f175b99e");
+    }
+}
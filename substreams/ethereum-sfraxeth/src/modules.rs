@@ -1,16 +1,17 @@
 use crate::{
     abi,
+    params::Params,
     pb::contract::v1::{BlockRewardCycles, RewardCycle},
 };
+use anyhow::anyhow;
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use substreams::{
-    hex,
     pb::substreams::StoreDeltas,
     scalar::BigInt,
     store::{
-        StoreAdd, StoreAddBigInt, StoreAddInt64, StoreGet, StoreGetInt64, StoreGetString, StoreNew,
-        StoreSet, StoreSetRaw,
+        StoreAddBigInt, StoreGet, StoreGetInt64, StoreGetString, StoreNew, StoreSet,
+        StoreSetIfNotExists, StoreSetIfNotExistsInt64, StoreSetRaw, StoreSetString,
     },
 };
 use substreams_ethereum::{pb::eth, Event};
@@ -18,13 +19,32 @@ use tycho_substreams::{
     balances::aggregate_balances_changes, contract::extract_contract_changes_builder, prelude::*,
 };
 
+const START_BLOCK_KEY: &str = "start_block";
+
+/// Stores the first block processed by the modules, i.e. their `initialBlock`, at which a vault
+/// deployed before it is bootstrapped.
+#[substreams::handlers::store]
+pub fn store_start_block(block: eth::v2::Block, store: StoreSetIfNotExistsInt64) {
+    store.set_if_not_exists(0, START_BLOCK_KEY, &(block.number as i64));
+}
+
 #[substreams::handlers::map]
 pub fn map_components(
     params: String,
     block: eth::v2::Block,
+    start_block_store: StoreGetInt64,
 ) -> Result<BlockTransactionProtocolComponents, anyhow::Error> {
-    let vault_address = hex::decode(params).unwrap();
-    let locked_asset = map_vault_to_locked_asset(&vault_address).unwrap();
+    let params = Params::parse_from_query(&params)?;
+    let is_start_block = start_block_store.get_last(START_BLOCK_KEY) == Some(block.number as i64);
+    if let Some(tx) = bootstrap_transaction(&params, &block, is_start_block)? {
+        return Ok(BlockTransactionProtocolComponents {
+            tx_components: vec![TransactionProtocolComponents {
+                tx: Some(tx.into()),
+                components: vec![vault_component(&params)],
+            }],
+        });
+    }
+
     // We store these as a hashmap by tx hash since we need to agg by tx hash later
     Ok(BlockTransactionProtocolComponents {
         tx_components: block
@@ -36,15 +56,8 @@ pub fn map_components(
                     .filter_map(|_| {
                         // address doesn't exist before contract deployment, hence the first tx with
                         // a log.address = vault_address is the deployment tx
-                        if is_deployment_tx(tx, &vault_address) {
-                            Some(
-                                ProtocolComponent::at_contract(&vault_address)
-                                    .with_tokens(&[
-                                        locked_asset.as_slice(),
-                                        vault_address.as_slice(),
-                                    ])
-                                    .as_swap_type("sfraxeth_vault", ImplementationType::Vm),
-                            )
+                        if is_deployment_tx(tx, &params.address) {
+                            Some(vault_component(&params))
                         } else {
                             None
                         }
@@ -61,52 +74,69 @@ pub fn map_components(
     })
 }
 
-/// Simply stores the `ProtocolComponent`s with the pool id as the key
+/// Stores the underlying token of the vaults, hex encoded, with the pool id as the key
 #[substreams::handlers::store]
-pub fn store_components(map: BlockTransactionProtocolComponents, store: StoreAddInt64) {
-    store.add_many(
-        0,
-        &map.tx_components
-            .iter()
-            .flat_map(|tx_components| &tx_components.components)
-            .map(|component| format!("pool:{0}", component.id))
-            .collect::<Vec<_>>(),
-        1,
-    );
+pub fn store_components(map: BlockTransactionProtocolComponents, store: StoreSetString) {
+    map.tx_components
+        .iter()
+        .flat_map(|tx_components| &tx_components.components)
+        .for_each(|component| {
+            store.set(0, format!("pool:{0}", component.id), &hex::encode(&component.tokens[0]))
+        });
 }
 
 // updates the reward rate to be accounted for at each block for the totalAsset locked in the vault
 #[substreams::handlers::map]
 pub fn map_reward_cycles(
     block: eth::v2::Block,
+    components: BlockTransactionProtocolComponents,
     components_store: StoreGetString,
 ) -> Result<BlockRewardCycles, anyhow::Error> {
-    let reward_cycles = block
-        .logs()
-        .filter(|vault_log| {
-            components_store
-                .get_last(format!("pool:0x{}", hex::encode(vault_log.address())))
-                .is_some()
-        })
-        .filter_map(|vault_log| {
-            if let Some(ev) =
-                abi::sfraxeth_contract::events::NewRewardsCycle::match_and_decode(vault_log.log)
-            {
-                substreams::log::info!(
-                    "New rewards cycle: end={}, next rewards={}",
-                    ev.cycle_end,
-                    ev.reward_amount,
-                );
-                Some(RewardCycle {
-                    ord: vault_log.ordinal(),
-                    next_reward_amount: ev.reward_amount.to_signed_bytes_be(),
-                    vault_address: vault_log.address().to_vec(), // be bytes
-                })
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
+    // Bootstrapped vaults start with the reward of their current cycle, which is added to the
+    // assets when the next cycle starts. It is read at the end of the block, so it already
+    // accounts for the events of the block.
+    let mut bootstrapped = HashSet::new();
+    let mut reward_cycles = Vec::new();
+    for (trace, _, vault) in bootstrapped_vaults(&block, &components)? {
+        let last_reward_amount = abi::sfraxeth_contract::functions::LastRewardAmount {}
+            .call(vault.clone())
+            .ok_or_else(|| anyhow!("Failed to read the reward of 0x{}", hex::encode(&vault)))?;
+        reward_cycles.push(RewardCycle {
+            ord: trace.end_ordinal,
+            next_reward_amount: last_reward_amount.to_signed_bytes_be(),
+            vault_address: vault.clone(),
+        });
+        bootstrapped.insert(vault);
+    }
+
+    reward_cycles.extend(
+        block
+            .logs()
+            .filter(|vault_log| {
+                !bootstrapped.contains(vault_log.address()) &&
+                    components_store
+                        .get_last(format!("pool:0x{}", hex::encode(vault_log.address())))
+                        .is_some()
+            })
+            .filter_map(|vault_log| {
+                if let Some(ev) =
+                    abi::sfraxeth_contract::events::NewRewardsCycle::match_and_decode(vault_log.log)
+                {
+                    substreams::log::info!(
+                        "New rewards cycle: end={}, next rewards={}",
+                        ev.cycle_end,
+                        ev.reward_amount,
+                    );
+                    Some(RewardCycle {
+                        ord: vault_log.ordinal(),
+                        next_reward_amount: ev.reward_amount.to_signed_bytes_be(),
+                        vault_address: vault_log.address().to_vec(), // be bytes
+                    })
+                } else {
+                    None
+                }
+            }),
+    );
 
     Ok(BlockRewardCycles { reward_cycles })
 }
@@ -128,123 +158,158 @@ pub fn store_reward_cycles(block_reward_cycles: BlockRewardCycles, store: StoreS
 #[substreams::handlers::map]
 pub fn map_relative_balances(
     block: eth::v2::Block,
-    store: StoreGetInt64,
+    components: BlockTransactionProtocolComponents,
+    store: StoreGetString,
     reward_store: StoreDeltas,
 ) -> Result<BlockBalanceDeltas, anyhow::Error> {
-    let balance_deltas = block
-        .logs()
-        .filter(|log| map_vault_to_locked_asset(log.address()).is_some())
-        .flat_map(|vault_log| {
-            let mut deltas = Vec::new();
-
-            if let Some(ev) =
-                abi::sfraxeth_contract::events::Withdraw::match_and_decode(vault_log.log)
-            {
-                let address_bytes_be = vault_log.address();
-                let address_hex = format!("0x{}", hex::encode(address_bytes_be));
-
-                if store
-                    .get_last(format!("pool:{address_hex}"))
-                    .is_some()
+    // The balances of bootstrapped vaults are snapshotted at the end of the block, so they already
+    // account for the events of the block. These are the `storedTotalAssets` tracked below, the
+    // rewards of the current cycle being added when the next cycle starts.
+    let mut bootstrapped = HashSet::new();
+    let mut balance_deltas = Vec::new();
+    for (trace, tx, vault) in bootstrapped_vaults(&block, &components)? {
+        let component_id = format!("0x{}", hex::encode(&vault));
+        let underlying = store
+            .get_last(format!("pool:{component_id}"))
+            .and_then(|underlying| hex::decode(underlying).ok())
+            .ok_or_else(|| anyhow!("Unknown underlying token of {component_id}"))?;
+        let (Some(stored_total_assets), Some(total_supply)) = (
+            stored_total_assets(&vault, block.timestamp_seconds()),
+            abi::sfraxeth_contract::functions::TotalSupply {}.call(vault.clone()),
+        ) else {
+            return Err(anyhow!("Failed to snapshot the balances of {component_id}"));
+        };
+        balance_deltas.extend(
+            [(underlying, stored_total_assets), (vault.clone(), total_supply)]
+                .into_iter()
+                .map(|(token, balance)| BalanceDelta {
+                    ord: trace.end_ordinal,
+                    tx: Some(tx.clone()),
+                    token,
+                    delta: balance.to_signed_bytes_be(),
+                    component_id: component_id.as_bytes().to_vec(),
+                }),
+        );
+        bootstrapped.insert(vault);
+    }
+
+    balance_deltas.extend(
+        block
+            .logs()
+            .filter(|vault_log| !bootstrapped.contains(vault_log.address()))
+            .filter_map(|vault_log| {
+                let underlying =
+                    store.get_last(format!("pool:0x{}", hex::encode(vault_log.address())))?;
+                Some((vault_log, hex::decode(underlying).ok()?))
+            })
+            .flat_map(|(vault_log, underlying)| {
+                let mut deltas = Vec::new();
+
+                if let Some(ev) =
+                    abi::sfraxeth_contract::events::Withdraw::match_and_decode(vault_log.log)
                 {
-                    substreams::log::info!(
-                        "Withdraw: -fraxEth {} -sfraxEth {}",
-                        ev.assets,
-                        ev.shares
-                    );
-                    deltas.extend_from_slice(&[
-                        BalanceDelta {
-                            ord: vault_log.ordinal(),
-                            tx: Some(vault_log.receipt.transaction.into()),
-                            token: map_vault_to_locked_asset(address_bytes_be)
-                                .unwrap()
-                                .to_vec(),
-                            delta: ev.assets.neg().to_signed_bytes_be(),
-                            component_id: address_hex.as_bytes().to_vec(),
-                        },
-                        BalanceDelta {
-                            ord: vault_log.ordinal(),
-                            tx: Some(vault_log.receipt.transaction.into()),
-                            token: address_bytes_be.to_vec(),
-                            delta: ev.shares.neg().to_signed_bytes_be(),
-                            component_id: address_hex.as_bytes().to_vec(),
-                        },
-                    ])
-                }
-            } else if let Some(ev) =
-                abi::sfraxeth_contract::events::Deposit::match_and_decode(vault_log.log)
-            {
-                let address_bytes_be = vault_log.address();
-                let address_hex = format!("0x{}", hex::encode(address_bytes_be));
-                if store
-                    .get_last(format!("pool:{address_hex}"))
-                    .is_some()
+                    let address_bytes_be = vault_log.address();
+                    let address_hex = format!("0x{}", hex::encode(address_bytes_be));
+
+                    if store
+                        .get_last(format!("pool:{address_hex}"))
+                        .is_some()
+                    {
+                        substreams::log::info!(
+                            "Withdraw: -fraxEth {} -sfraxEth {}",
+                            ev.assets,
+                            ev.shares
+                        );
+                        deltas.extend_from_slice(&[
+                            BalanceDelta {
+                                ord: vault_log.ordinal(),
+                                tx: Some(vault_log.receipt.transaction.into()),
+                                token: underlying.clone(),
+                                delta: ev.assets.neg().to_signed_bytes_be(),
+                                component_id: address_hex.as_bytes().to_vec(),
+                            },
+                            BalanceDelta {
+                                ord: vault_log.ordinal(),
+                                tx: Some(vault_log.receipt.transaction.into()),
+                                token: address_bytes_be.to_vec(),
+                                delta: ev.shares.neg().to_signed_bytes_be(),
+                                component_id: address_hex.as_bytes().to_vec(),
+                            },
+                        ])
+                    }
+                } else if let Some(ev) =
+                    abi::sfraxeth_contract::events::Deposit::match_and_decode(vault_log.log)
                 {
-                    deltas.extend_from_slice(&[
-                        BalanceDelta {
-                            ord: vault_log.ordinal(),
-                            tx: Some(vault_log.receipt.transaction.into()),
-                            token: map_vault_to_locked_asset(address_bytes_be)
-                                .unwrap()
-                                .to_vec(),
-                            delta: ev.assets.to_signed_bytes_be(),
-                            component_id: address_hex.as_bytes().to_vec(),
-                        },
-                        BalanceDelta {
-                            ord: vault_log.ordinal(),
-                            tx: Some(vault_log.receipt.transaction.into()),
-                            token: address_bytes_be.to_vec(),
-                            delta: ev.shares.to_signed_bytes_be(),
-                            component_id: address_hex.as_bytes().to_vec(),
-                        },
-                    ]);
-                    substreams::log::info!("Deposit: {:?}", deltas);
-                }
-            } else if abi::sfraxeth_contract::events::NewRewardsCycle::match_and_decode(vault_log)
+                    let address_bytes_be = vault_log.address();
+                    let address_hex = format!("0x{}", hex::encode(address_bytes_be));
+                    if store
+                        .get_last(format!("pool:{address_hex}"))
+                        .is_some()
+                    {
+                        deltas.extend_from_slice(&[
+                            BalanceDelta {
+                                ord: vault_log.ordinal(),
+                                tx: Some(vault_log.receipt.transaction.into()),
+                                token: underlying.clone(),
+                                delta: ev.assets.to_signed_bytes_be(),
+                                component_id: address_hex.as_bytes().to_vec(),
+                            },
+                            BalanceDelta {
+                                ord: vault_log.ordinal(),
+                                tx: Some(vault_log.receipt.transaction.into()),
+                                token: address_bytes_be.to_vec(),
+                                delta: ev.shares.to_signed_bytes_be(),
+                                component_id: address_hex.as_bytes().to_vec(),
+                            },
+                        ]);
+                        substreams::log::info!("Deposit: {:?}", deltas);
+                    }
+                } else if abi::sfraxeth_contract::events::NewRewardsCycle::match_and_decode(
+                    vault_log,
+                )
                 .is_some()
-            {
-                let address_bytes_be = vault_log.address();
-                let address_hex = format!("0x{}", hex::encode(address_bytes_be));
-                if store
-                    .get_last(format!("pool:{address_hex}"))
-                    .is_some()
                 {
-                    // When the NextRewardsCycle event is emitted:
-                    // 1. `lastRewardAmount` is read from storage
-                    // 2. `storedTotalAssets` is incremented by the `lastRewardAmount` in the event
-                    // 3. `lastRewardAmount` is update with the `nextReward` (2nd parameter) in the
-                    //    event
-                    // Hence the reward_store at key `reward_cycle:{address_hex}` will is
-                    // updated in this block. We want to use the first value of
-                    // the record at the beginning of the block (before the store_reward_cycles
-                    // writes to that key) ref: https://github.com/FraxFinance/frax-solidity/blob/85039d4dff2fb24d8a1ba6efc1ebf7e464df9dcf/src/hardhat/contracts/FraxETH/sfrxETH.sol.old#L984
-                    if let Some(last_reward_amount) = reward_store
-                        .deltas
-                        .iter()
-                        .find(|el| el.key == format!("reward_cycle:{address_hex}"))
-                        .map(|el| el.old_value.clone())
+                    let address_bytes_be = vault_log.address();
+                    let address_hex = format!("0x{}", hex::encode(address_bytes_be));
+                    if store
+                        .get_last(format!("pool:{address_hex}"))
+                        .is_some()
                     {
-                        substreams::log::info!(
-                            "Reward cycle balance change: address {}, sfraxEth amount {}",
-                            address_hex,
-                            BigInt::from_signed_bytes_be(&last_reward_amount)
-                        );
-                        deltas.push(BalanceDelta {
-                            ord: vault_log.ordinal(),
-                            tx: Some(vault_log.receipt.transaction.into()),
-                            token: map_vault_to_locked_asset(address_bytes_be)
-                                .unwrap()
-                                .to_vec(),
-                            delta: last_reward_amount,
-                            component_id: address_hex.as_bytes().to_vec(),
-                        });
+                        // When the NextRewardsCycle event is emitted:
+                        // 1. `lastRewardAmount` is read from storage
+                        // 2. `storedTotalAssets` is incremented by the `lastRewardAmount` in the
+                        //    event
+                        // 3. `lastRewardAmount` is update with the `nextReward` (2nd parameter) in
+                        //    the event
+                        // Hence the reward_store at key `reward_cycle:{address_hex}` will is
+                        // updated in this block. We want to use the first value of
+                        // the record at the beginning of the block (before the store_reward_cycles
+                        // writes to that key) ref: https://github.com/FraxFinance/frax-solidity/blob/85039d4dff2fb24d8a1ba6efc1ebf7e464df9dcf/src/hardhat/contracts/FraxETH/sfrxETH.sol.old#L984
+                        if let Some(last_reward_amount) = reward_store
+                            .deltas
+                            .iter()
+                            .find(|el| el.key == format!("reward_cycle:{address_hex}"))
+                            .map(|el| el.old_value.clone())
+                        {
+                            substreams::log::info!(
+                                "Reward cycle balance change: address {}, sfraxEth amount {}",
+                                address_hex,
+                                BigInt::from_signed_bytes_be(&last_reward_amount)
+                            );
+                            deltas.push(BalanceDelta {
+                                ord: vault_log.ordinal(),
+                                tx: Some(vault_log.receipt.transaction.into()),
+                                token: underlying.clone(),
+                                delta: last_reward_amount,
+                                component_id: address_hex.as_bytes().to_vec(),
+                            });
+                        }
                     }
                 }
-            }
 
-            deltas
-        })
-        .collect::<Vec<_>>();
+                deltas
+            }),
+    );
 
     Ok(BlockBalanceDeltas { balance_deltas })
 }
@@ -344,72 +409,170 @@ pub fn map_protocol_changes(
     Ok(block_changes)
 }
 
-fn is_deployment_tx(tx: &eth::v2::TransactionTrace, vault_address: &[u8]) -> bool {
-    match vault_address {
-        hex!("95aB45875cFFdba1E5f451B950bC2E42c0053f39") => {
-            // Arbitrum
-            tx.hash == hex!("ad86e67a2d511576f802dca2f65b6dfbec1d050c63f55878f80272a5fcafcadf")
-        }
-        hex!("3Cd55356433C89E50DC51aB07EE0fa0A95623D53") => {
-            // BSC
-            tx.hash == hex!("c043ba8c30eeed718514b7d1d1d4654521eca2f7aa5e5a7ae1e2c212ca869997")
-        }
-        hex!("ac3E018457B222d93114458476f3E3416Abbe38F") => {
-            // Ethereum
-            tx.hash == hex!("d78dbe6cba652eb844de5aa473636c202fb6366c1bfc5ff8d5a26c1a24b37b07")
-        }
-        hex!("b90CCD563918fF900928dc529aA01046795ccb4A") => {
-            // Fantom
-            tx.hash == hex!("749c9ffb6022d5e6a8f3470499bfc2e9cf3bf122f75e2a5925930407d2a9e02c")
-        }
-        hex!("ecf91116348aF1cfFe335e9807f0051332BE128D") => {
-            // Moonbeam
-            tx.hash == hex!("3545822fb0695bec2d3e9860b22073cc79845a0bb3cfccf401241dc7fe0eb86b")
-        }
-        hex!("484c2D6e3cDd945a8B2DF735e079178C1036578c") => {
-            // Optimism
-            tx.hash == hex!("e2e4c7173ae6ac0d78cacb1d48004c2aea7e1ce4ae0110a128d40bdcdc4d51b0")
-        }
-        hex!("6d1FdBB266fCc09A16a22016369210A15bb95761") => {
-            // Polygon
-            tx.hash == hex!("ada03ce824bac4a811d0b1bb60f9f26dbdd921bcd5034b1b4b973026a04ad9ea")
-        }
-        _ => false,
+/// Returns the transaction the vault is bootstrapped in, if `block` is the start block and the
+/// vault was deployed before it. This is the last transaction of the block, as the state of the
+/// vault is snapshotted at the end of the block.
+fn bootstrap_transaction<'a>(
+    params: &Params,
+    block: &'a eth::v2::Block,
+    is_start_block: bool,
+) -> Result<Option<&'a eth::v2::TransactionTrace>, anyhow::Error> {
+    let deployed_before = is_start_block &&
+        !block
+            .transactions()
+            .any(|tx| is_deployment_tx(tx, &params.address)) &&
+        abi::sfraxeth_contract::functions::TotalSupply {}
+            .call(params.address.clone())
+            .is_some();
+    if !deployed_before {
+        return Ok(None);
     }
+    block
+        .transactions()
+        .last()
+        .map(Some)
+        .ok_or_else(|| {
+            anyhow!(
+                "Block {} has no transaction to bootstrap the vault in, start at another block",
+                block.number
+            )
+        })
 }
 
-// ref: https://docs.frax.finance/smart-contracts/frxeth-and-sfrxeth-contract-addresses
-fn map_vault_to_locked_asset(address_bytes: &[u8]) -> Option<[u8; 20]> {
-    // basedo on ADDRESS_MAP create a match condition to return the locked_asset
-    match address_bytes {
-        hex!("95aB45875cFFdba1E5f451B950bC2E42c0053f39") => {
-            // Arbitrum
-            Some(hex!("178412e79c25968a32e89b11f63B33F733770c2A"))
-        }
-        hex!("3Cd55356433C89E50DC51aB07EE0fa0A95623D53") => {
-            // BSC
-            Some(hex!("64048A7eEcF3a2F1BA9e144aAc3D7dB6e58F555e"))
-        }
-        hex!("ac3E018457B222d93114458476f3E3416Abbe38F") => {
-            // Ethereum
-            Some(hex!("5e8422345238f34275888049021821e8e08caa1f"))
-        }
-        hex!("b90CCD563918fF900928dc529aA01046795ccb4A") => {
-            // Fantom
-            Some(hex!("9E73F99EE061C8807F69f9c6CCc44ea3d8c373ee"))
-        }
-        hex!("ecf91116348aF1cfFe335e9807f0051332BE128D") => {
-            // Moonbeam
-            Some(hex!("82bbd1b6f6De2B7bb63D3e1546e6b1553508BE99"))
-        }
-        hex!("484c2D6e3cDd945a8B2DF735e079178C1036578c") => {
-            // Optimism
-            Some(hex!("6806411765Af15Bddd26f8f544A34cC40cb9838B"))
+/// Returns the vaults bootstrapped in this block, i.e. the new components that weren't deployed
+/// in their transaction, along with that transaction.
+fn bootstrapped_vaults<'a>(
+    block: &'a eth::v2::Block,
+    components: &'a BlockTransactionProtocolComponents,
+) -> Result<Vec<(&'a eth::v2::TransactionTrace, &'a Transaction, Vec<u8>)>, anyhow::Error> {
+    let mut vaults = Vec::new();
+    for tx_components in &components.tx_components {
+        let tx = tx_components.tx.as_ref().unwrap();
+        let Some(trace) = block
+            .transactions()
+            .find(|trace| trace.hash == tx.hash)
+        else {
+            continue;
+        };
+        for component in &tx_components.components {
+            let vault = hex::decode(component.id.trim_start_matches("0x"))?;
+            if !is_deployment_tx(trace, &vault) {
+                vaults.push((trace, tx, vault));
+            }
         }
-        hex!("6d1FdBB266fCc09A16a22016369210A15bb95761") => {
-            // Polygon
-            Some(hex!("Ee327F889d5947c1dc1934Bb208a1E792F953E96"))
+    }
+    Ok(vaults)
+}
+
+/// Reads the `storedTotalAssets` of a vault at the end of the block, at `timestamp`. It isn't
+/// public, so it is derived from `totalAssets`, which adds the rewards of the current cycle
+/// unlocked so far.
+fn stored_total_assets(vault: &[u8], timestamp: u64) -> Option<BigInt> {
+    use abi::sfraxeth_contract::functions::{
+        LastRewardAmount, LastSync, RewardsCycleEnd, TotalAssets,
+    };
+
+    let total_assets = TotalAssets {}.call(vault.to_vec())?;
+    Some(
+        total_assets -
+            unlocked_rewards(
+                BigInt::from(timestamp),
+                LastRewardAmount {}.call(vault.to_vec())?,
+                LastSync {}.call(vault.to_vec())?,
+                RewardsCycleEnd {}.call(vault.to_vec())?,
+            ),
+    )
+}
+
+/// The rewards of the current cycle `totalAssets` adds to `storedTotalAssets`: they unlock
+/// linearly from the last sync to the end of the cycle.
+fn unlocked_rewards(
+    timestamp: BigInt,
+    last_reward_amount: BigInt,
+    last_sync: BigInt,
+    rewards_cycle_end: BigInt,
+) -> BigInt {
+    if timestamp >= rewards_cycle_end {
+        return last_reward_amount;
+    }
+    last_reward_amount * (timestamp - last_sync.clone()) / (rewards_cycle_end - last_sync)
+}
+
+fn is_deployment_tx(tx: &eth::v2::TransactionTrace, vault_address: &[u8]) -> bool {
+    tx.calls
+        .iter()
+        .any(|call| call.call_type() == eth::v2::CallType::Create && call.address == vault_address)
+}
+
+fn vault_component(params: &Params) -> ProtocolComponent {
+    ProtocolComponent::at_contract(&params.address)
+        .with_tokens(&[params.underlying.as_slice(), params.address.as_slice()])
+        .as_swap_type("sfraxeth_vault", ImplementationType::Vm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(index: u32, created: Option<&[u8]>) -> eth::v2::TransactionTrace {
+        eth::v2::TransactionTrace {
+            hash: vec![index as u8; 32],
+            index,
+            status: eth::v2::TransactionTraceStatus::Succeeded.into(),
+            calls: created
+                .map(|address| eth::v2::Call {
+                    call_type: eth::v2::CallType::Create.into(),
+                    address: address.to_vec(),
+                    ..Default::default()
+                })
+                .into_iter()
+                .collect(),
+            ..Default::default()
         }
-        _ => None,
+    }
+
+    #[test]
+    fn test_unlocked_rewards() {
+        let unlocked = |timestamp: u64| {
+            unlocked_rewards(
+                BigInt::from(timestamp),
+                BigInt::from(1_000),
+                BigInt::from(100),
+                BigInt::from(200),
+            )
+        };
+
+        assert_eq!(unlocked(100), BigInt::zero());
+        assert_eq!(unlocked(125), BigInt::from(250));
+        // The whole reward is unlocked once the cycle ended
+        assert_eq!(unlocked(200), BigInt::from(1_000));
+        assert_eq!(unlocked(300), BigInt::from(1_000));
+    }
+
+    #[test]
+    fn test_bootstrapped_vaults() {
+        let deployed = [1u8; 20];
+        let bootstrapped = [2u8; 20];
+        let block = eth::v2::Block {
+            transaction_traces: vec![transaction(0, Some(&deployed)), transaction(1, None)],
+            ..Default::default()
+        };
+        let components = BlockTransactionProtocolComponents {
+            tx_components: block
+                .transaction_traces
+                .iter()
+                .zip([deployed, bootstrapped])
+                .map(|(tx, vault)| TransactionProtocolComponents {
+                    tx: Some(tx.into()),
+                    components: vec![ProtocolComponent::at_contract(&vault)],
+                })
+                .collect(),
+        };
+
+        let vaults = bootstrapped_vaults(&block, &components).unwrap();
+
+        assert_eq!(vaults.len(), 1);
+        assert_eq!(vaults[0].0.index, 1);
+        assert_eq!(vaults[0].2, bootstrapped.to_vec());
     }
 }
@@ -0,0 +1,19 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+/// The vault indexed by the package, e.g. `address=...&underlying=...`
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The vault, without the 0x prefix.
+    #[serde(with = "hex::serde")]
+    pub address: Vec<u8>,
+    /// The token deposited into the vault, without the 0x prefix.
+    #[serde(with = "hex::serde")]
+    pub underlying: Vec<u8>,
+}
+
+impl Params {
+    pub fn parse_from_query(input: &str) -> Result<Self> {
+        serde_qs::from_str(input).map_err(|e| anyhow!("Failed to parse query params: {}", e))
+    }
+}
@@ -1,4 +1,5 @@
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 mod abi;
 mod modules;
+mod params;
 mod pb;
@@ -1,12 +1,14 @@
-use std::str::FromStr;
-
 use ethabi::ethereum_types::Address;
 use substreams::scalar::BigInt;
 use substreams_ethereum::pb::eth::v2::{self as eth};
 
 use substreams_helper::{event_handler::EventHandler, hex::Hexable};
 
-use crate::abi::pool_manager::events::Initialize;
+use crate::{
+    abi::pool_manager::events::Initialize,
+    modules::{hooks::HookPermissions, pool_key::is_dynamic_fee},
+    params::Params,
+};
 
 use tycho_substreams::prelude::*;
 #[substreams::handlers::map]
@@ -15,9 +17,9 @@ pub fn map_pools_created(
     block: eth::Block,
 ) -> Result<BlockEntityChanges, substreams::errors::Error> {
     let mut new_pools: Vec<TransactionEntityChanges> = vec![];
-    let pool_manager = params.as_str();
+    let params = Params::parse(&params)?;
 
-    get_new_pools(&block, &mut new_pools, pool_manager);
+    get_new_pools(&block, &mut new_pools, &params);
 
     Ok(BlockEntityChanges { block: None, changes: new_pools })
 }
@@ -26,12 +28,22 @@ pub fn map_pools_created(
 fn get_new_pools(
     block: &eth::Block,
     new_pools: &mut Vec<TransactionEntityChanges>,
-    pool_manager_address: &str,
+    params: &Params,
 ) {
     // Extract new pools from Initialize events
     let mut on_pool_created = |event: Initialize, _tx: &eth::TransactionTrace, _log: &eth::Log| {
         let tycho_tx: Transaction = _tx.into();
 
+        // Dynamic fee pools are initialized with an LP fee of 0 until the hook sets one.
+        let lp_fee = if is_dynamic_fee(event.fee.to_u64() as u32) {
+            BigInt::zero()
+        } else {
+            event.fee.clone()
+        };
+        let hook_permissions = HookPermissions::from_address(&event.hooks);
+        let token0 = params.to_token(&event.currency0);
+        let token1 = params.to_token(&event.currency1);
+
         new_pools.push(TransactionEntityChanges {
             tx: Some(tycho_tx.clone()),
             entity_changes: vec![EntityChanges {
@@ -39,7 +51,7 @@ fn get_new_pools(
                 attributes: vec![
                     Attribute {
                         name: "balance_owner".to_string(),
-                        value: hex::decode(pool_manager_address).unwrap(),
+                        value: params.pool_manager.clone(),
                         change: ChangeType::Creation.into(),
                     },
                     Attribute {
@@ -59,6 +71,11 @@ fn get_new_pools(
                             .to_signed_bytes_be(),
                         change: ChangeType::Creation.into(),
                     },
+                    Attribute {
+                        name: "lp_fee".to_string(),
+                        value: lp_fee.to_signed_bytes_be(),
+                        change: ChangeType::Creation.into(),
+                    },
                     Attribute {
                         name: "protocol_fees/zero2one".to_string(),
                         value: BigInt::from(0).to_signed_bytes_be(),
@@ -73,7 +90,7 @@ fn get_new_pools(
             }],
             component_changes: vec![ProtocolComponent {
                 id: event.id.to_vec().to_hex(),
-                tokens: vec![event.currency0.clone(), event.currency1.clone()],
+                tokens: vec![token0.clone(), token1.clone()],
                 contracts: vec![],
                 static_att: vec![
                     Attribute {
@@ -91,11 +108,24 @@ fn get_new_pools(
                         value: event.hooks.to_vec(),
                         change: ChangeType::Creation.into(),
                     },
+                    // The hook callbacks enabled for this pool, decoded from the hook address.
+                    Attribute {
+                        name: "hook_permissions".to_string(),
+                        value: BigInt::from(hook_permissions.bits()).to_signed_bytes_be(),
+                        change: ChangeType::Creation.into(),
+                    },
+                    // Flags pools whose hook can alter the settled amounts. These pools can't be
+                    // simulated from the pool state alone and consumers may want to exclude them.
+                    Attribute {
+                        name: "hook_returns_delta".to_string(),
+                        value: vec![hook_permissions.returns_delta() as u8],
+                        change: ChangeType::Creation.into(),
+                    },
                     // Represents the pool's LP Fee. The fee is either static or dynamic. Static
                     // fees are represented in hundredths of a bip, can be set to a value between 0
                     // and 1000000 (100%) and are immutable. If the value is set to 0x800000 then
-                    // the pool is flagged as using dynamic fees. The dynamic fees changes should
-                    // be tracked in state attributes and the flag value set
+                    // the pool is flagged as using dynamic fees. The dynamic fees changes are
+                    // tracked in the `lp_fee` state attribute and the flag value set
                     // here should remain untouched (it is needed for
                     // generating the PoolKey for contract interactions).
                     Attribute {
@@ -114,7 +144,7 @@ fn get_new_pools(
             }],
             balance_changes: vec![
                 BalanceChange {
-                    token: event.currency0,
+                    token: token0,
                     balance: BigInt::from(0).to_signed_bytes_be(),
                     component_id: event
                         .id
@@ -124,7 +154,7 @@ fn get_new_pools(
                         .to_vec(),
                 },
                 BalanceChange {
-                    token: event.currency1,
+                    token: token1,
                     balance: BigInt::from(0).to_signed_bytes_be(),
                     component_id: event
                         .id
@@ -139,7 +169,7 @@ fn get_new_pools(
 
     let mut eh = EventHandler::new(block);
 
-    eh.filter_by_address(vec![Address::from_str(pool_manager_address).unwrap()]);
+    eh.filter_by_address(vec![Address::from_slice(&params.pool_manager)]);
 
     eh.on::<Initialize, _>(&mut on_pool_created);
     eh.handle_events();
@@ -1,5 +1,10 @@
 use crate::{
-    abi::pool_manager::events::{Initialize, ModifyLiquidity, ProtocolFeeUpdated, Swap},
+    abi::pool_manager::{
+        events::{Donate, Initialize, ModifyLiquidity, ProtocolFeeUpdated, Swap},
+        functions::UpdateDynamicLpFee,
+    },
+    modules::pool_key::pool_id,
+    params::Params,
     pb::uniswap::v4::{
         events::{pool_event, pool_event::Type, PoolEvent},
         Events, Pool,
@@ -8,16 +13,19 @@ use crate::{
 use anyhow::Ok;
 use substreams::store::{StoreGet, StoreGetProto};
 use substreams_ethereum::{
-    pb::eth::v2::{self as eth, Log, TransactionTrace},
-    Event,
+    pb::eth::v2::{self as eth, Call, Log, TransactionTrace},
+    Event, Function,
 };
 use substreams_helper::hex::Hexable;
 
 #[substreams::handlers::map]
 pub fn map_events(
+    params: String,
     block: eth::Block,
     pools_store: StoreGetProto<Pool>,
 ) -> Result<Events, anyhow::Error> {
+    let pool_manager = Params::parse(&params)?.pool_manager;
+
     let mut pool_manager_events = block
         .transaction_traces
         .into_iter()
@@ -28,10 +36,22 @@ pub fn map_events(
                 .as_ref()
                 .expect("all transaction traces have a receipt");
 
-            receipt
+            let log_events = receipt
                 .logs
                 .iter()
                 .filter_map(|log| log_to_event(log, &tx, &pools_store))
+                .collect::<Vec<_>>();
+
+            let call_events = tx
+                .calls
+                .iter()
+                .filter(|call| !call.state_reverted && call.address == pool_manager)
+                .filter_map(|call| call_to_event(call, &tx, &pools_store))
+                .collect::<Vec<_>>();
+
+            log_events
+                .into_iter()
+                .chain(call_events)
                 .collect::<Vec<_>>()
         })
         .collect::<Vec<_>>();
@@ -84,22 +104,21 @@ fn log_to_event(
                 fee: swap.fee.into(),
             })),
         })
-    // Skipped because Donate doesn't seem to affect pool liquidity?
-    // } else if let Some(flash) = Donate::match_and_decode(event) {
-    //     let pool_id = flash.id.to_vec().to_hex();
-    //     let pool = pools_store.get_last(format!("{}:{}", "pool", &pool_id))?;
-    //     Some(PoolEvent {
-    //         log_ordinal: event.ordinal,
-    //         pool_id,
-    //         currency0: pool.currency0.to_hex(),
-    //         currency1: pool.currency1.to_hex(),
-    //         transaction: Some(tx.into()),
-    //         r#type: Some(Type::Donate(pool_event::Donate {
-    //             sender: flash.sender.to_hex(),
-    //             amount0: flash.amount0.to_string(),
-    //             amount1: flash.amount1.to_string(),
-    //         })),
-    //     })
+    } else if let Some(donate) = Donate::match_and_decode(event) {
+        let pool_id = donate.id.to_vec().to_hex();
+        let pool = pools_store.get_last(format!("{}:{}", "pool", &pool_id))?;
+        Some(PoolEvent {
+            log_ordinal: event.ordinal,
+            pool_id,
+            currency0: pool.currency0.to_hex(),
+            currency1: pool.currency1.to_hex(),
+            transaction: Some(tx.into()),
+            r#type: Some(Type::Donate(pool_event::Donate {
+                sender: donate.sender.to_hex(),
+                amount0: donate.amount0.to_string(),
+                amount1: donate.amount1.to_string(),
+            })),
+        })
     } else if let Some(modify_liquidity) = ModifyLiquidity::match_and_decode(event) {
         let pool_id = modify_liquidity.id.to_vec().to_hex();
         let pool = pools_store.get_last(format!("{}:{}", "pool", &pool_id))?;
@@ -140,3 +159,27 @@ fn log_to_event(
         None
     }
 }
+
+fn call_to_event(
+    call: &Call,
+    tx: &TransactionTrace,
+    pools_store: &StoreGetProto<Pool>,
+) -> Option<PoolEvent> {
+    // The PoolManager emits no event when a hook updates the LP fee of a dynamic fee pool, so we
+    // decode the call instead. This is the only source of the `lp_fee` attribute: `Swap.fee` is
+    // the total fee of the swap, which includes the protocol fee and any fee override returned by
+    // the `beforeSwap` hook.
+    let update = UpdateDynamicLpFee::match_and_decode(call)?;
+    let pool_id = pool_id(&update.key).to_hex();
+    let pool = pools_store.get_last(format!("{}:{}", "pool", &pool_id))?;
+    Some(PoolEvent {
+        log_ordinal: call.end_ordinal,
+        pool_id,
+        currency0: pool.currency0.to_hex(),
+        currency1: pool.currency1.to_hex(),
+        transaction: Some(tx.into()),
+        r#type: Some(Type::DynamicLpFeeUpdated(pool_event::DynamicLpFeeUpdated {
+            lp_fee: update.new_dynamic_lp_fee.to_u64() as u32,
+        })),
+    })
+}
@@ -0,0 +1,109 @@
+use substreams::{
+    scalar::BigInt,
+    store::{StoreAdd, StoreAddBigInt, StoreNew},
+};
+use substreams_ethereum::{
+    pb::eth::v2::{self as eth, Log, TransactionTrace},
+    Event,
+};
+
+use anyhow::Ok;
+use substreams_helper::hex::Hexable;
+
+use crate::{
+    abi::pool_manager::events::Transfer,
+    params::Params,
+    pb::uniswap::v4::{ClaimDelta, ClaimDeltas},
+};
+
+const ZERO_ADDRESS: [u8; 20] = [0; 20];
+
+/// Extracts the ERC-6909 claim supply changes of the PoolManager.
+///
+/// Claims are minted when a user parks a currency owed to them in the PoolManager instead of
+/// taking it, and burned when they are redeemed. The backing tokens are held by the PoolManager
+/// but are not part of any pool's liquidity, so they are tracked separately from the component
+/// balances.
+#[substreams::handlers::map]
+pub fn map_claim_deltas(params: String, block: eth::Block) -> Result<ClaimDeltas, anyhow::Error> {
+    let params = Params::parse(&params)?;
+
+    let mut deltas = block
+        .transaction_traces
+        .iter()
+        .filter(|tx| tx.status == 1)
+        .flat_map(|tx| {
+            tx.receipt
+                .as_ref()
+                .expect("all transaction traces have a receipt")
+                .logs
+                .iter()
+                .filter(|log| log.address == params.pool_manager)
+                .filter_map(|log| log_to_claim_delta(log, tx, &params))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    deltas.sort_unstable_by_key(|delta| delta.ordinal);
+
+    Ok(ClaimDeltas { deltas })
+}
+
+#[substreams::handlers::store]
+pub fn store_claims_supply(claim_deltas: ClaimDeltas, store: StoreAddBigInt) {
+    claim_deltas
+        .deltas
+        .iter()
+        .for_each(|delta| {
+            store.add(
+                delta.ordinal,
+                format!("claims:{0}", &delta.currency.to_hex()),
+                BigInt::from_signed_bytes_be(&delta.delta),
+            );
+        });
+}
+
+fn log_to_claim_delta(log: &Log, tx: &TransactionTrace, params: &Params) -> Option<ClaimDelta> {
+    let transfer = Transfer::match_and_decode(log)?;
+
+    // Transfers between holders leave the claim supply untouched.
+    let delta = if transfer.from == ZERO_ADDRESS {
+        transfer.amount
+    } else if transfer.to == ZERO_ADDRESS {
+        transfer.amount.neg()
+    } else {
+        return None;
+    };
+
+    Some(ClaimDelta {
+        currency: params.to_token(&id_to_currency(&transfer.id)),
+        delta: delta.to_signed_bytes_be(),
+        ordinal: log.ordinal,
+        transaction: Some(tx.into()),
+    })
+}
+
+/// Claim ids are the currency address cast to `uint256`.
+fn id_to_currency(id: &BigInt) -> Vec<u8> {
+    let (_, bytes) = id.to_bytes_be();
+    let mut currency = vec![0; 20];
+    let len = bytes.len().min(20);
+    currency[20 - len..].copy_from_slice(&bytes[bytes.len() - len..]);
+    currency
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_id_to_currency() {
+        assert_eq!(id_to_currency(&BigInt::zero()), vec![0; 20]);
+        assert_eq!(
+            id_to_currency(&BigInt::from_unsigned_bytes_be(
+                &hex::decode("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap()
+            )),
+            hex::decode("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap()
+        );
+    }
+}
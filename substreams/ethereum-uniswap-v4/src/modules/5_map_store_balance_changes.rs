@@ -135,6 +135,35 @@ fn event_to_balance_deltas(
                 },
             ])
         }
+        pool_event::Type::Donate(e) => {
+            // Donated amounts are credited to the pool's in-range LPs and stay in the
+            // PoolManager, so they increase the component balances.
+            let delta0 = BigInt::from_str(&e.amount0).unwrap();
+            let delta1 = BigInt::from_str(&e.amount1).unwrap();
+
+            Some(vec![
+                BalanceDelta {
+                    token: hex::decode(event.currency0.trim_start_matches("0x")).unwrap(),
+                    delta: delta0.to_signed_bytes_be(),
+                    component_id: address.clone(),
+                    ord: event.log_ordinal,
+                    tx: event
+                        .transaction
+                        .as_ref()
+                        .map(Into::into),
+                },
+                BalanceDelta {
+                    token: hex::decode(event.currency1.trim_start_matches("0x")).unwrap(),
+                    delta: delta1.to_signed_bytes_be(),
+                    component_id: address,
+                    ord: event.log_ordinal,
+                    tx: event
+                        .transaction
+                        .as_ref()
+                        .map(Into::into),
+                },
+            ])
+        }
         _ => None,
     }
 }
@@ -143,7 +172,9 @@ impl PoolEvent {
     fn can_introduce_balance_changes(&self) -> bool {
         matches!(
             self.r#type.as_ref().unwrap(),
-            pool_event::Type::ModifyLiquidity(_) | pool_event::Type::Swap(_)
+            pool_event::Type::ModifyLiquidity(_) |
+                pool_event::Type::Swap(_) |
+                pool_event::Type::Donate(_)
         )
     }
 }
@@ -165,3 +196,43 @@ fn get_amount_delta(
             .expect("Failed to calculate token amounts from liquidity delta");
     (BigInt::from(amount0), BigInt::from(amount1))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::uniswap::v4::Transaction;
+
+    #[test]
+    fn test_donate_balance_deltas() {
+        let event = PoolEvent {
+            log_ordinal: 42,
+            pool_id: "0x21c67e77068de97969ba93d4aab21826d33ca12bb9f565d8496e8fda8a82ca27"
+                .to_string(),
+            currency0: "0x0000000000000000000000000000000000000000".to_string(),
+            currency1: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+            transaction: Some(Transaction { hash: vec![1], from: vec![2], to: vec![3], index: 7 }),
+            r#type: Some(pool_event::Type::Donate(pool_event::Donate {
+                sender: "0x0000000000000000000000000000000000000001".to_string(),
+                amount0: "1000000000000000000".to_string(),
+                amount1: "2500000000".to_string(),
+            })),
+        };
+
+        assert!(event.can_introduce_balance_changes());
+
+        let deltas = event_to_balance_deltas(BigInt::zero(), event).unwrap();
+
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].token, vec![0; 20]);
+        assert_eq!(
+            BigInt::from_signed_bytes_be(&deltas[0].delta),
+            BigInt::from_str("1000000000000000000").unwrap()
+        );
+        assert_eq!(
+            deltas[1].token,
+            hex::decode("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap()
+        );
+        assert_eq!(BigInt::from_signed_bytes_be(&deltas[1].delta), BigInt::from(2500000000u64));
+        assert!(deltas.iter().all(|d| d.ord == 42));
+    }
+}
@@ -1,6 +1,10 @@
-use crate::pb::uniswap::v4::{
-    events::{pool_event, PoolEvent},
-    Events, LiquidityChanges, TickDeltas,
+use crate::{
+    modules::hooks::swap_entrypoints,
+    params::Params,
+    pb::uniswap::v4::{
+        events::{pool_event, PoolEvent},
+        ClaimDeltas, Events, LiquidityChanges, TickDeltas,
+    },
 };
 use itertools::Itertools;
 use std::{collections::HashMap, str::FromStr, vec};
@@ -13,6 +17,7 @@ type PoolAddress = Vec<u8>;
 
 #[substreams::handlers::map]
 pub fn map_protocol_changes(
+    params: String,
     block: eth::Block,
     created_pools: BlockEntityChanges,
     events: Events,
@@ -22,11 +27,15 @@ pub fn map_protocol_changes(
     ticks_store_deltas: StoreDeltas,
     pool_liquidity_changes: LiquidityChanges,
     pool_liquidity_store_deltas: StoreDeltas,
+    claim_deltas: ClaimDeltas,
+    claims_store_deltas: StoreDeltas,
 ) -> Result<BlockChanges, substreams::errors::Error> {
     // We merge contract changes by transaction (identified by transaction index) making it easy to
     // sort them at the very end.
     let mut transaction_changes: HashMap<_, TransactionChangesBuilder> = HashMap::new();
 
+    let params = Params::parse(&params)?;
+
     // Add created pools to the tx_changes_map
     for change in created_pools.changes.into_iter() {
         let tx = change.tx.as_ref().unwrap();
@@ -37,6 +46,11 @@ pub fn map_protocol_changes(
             .component_changes
             .iter()
             .for_each(|c| {
+                // Hooked pools need their hook contract traced to be simulated.
+                for (entrypoint, entrypoint_params) in swap_entrypoints(c, &params) {
+                    builder.add_entrypoint(&entrypoint);
+                    builder.add_entrypoint_params(&entrypoint_params);
+                }
                 builder.add_protocol_component(c);
             });
         change
@@ -138,6 +152,22 @@ pub fn map_protocol_changes(
             });
         });
 
+    // Claim supplies are reported as `claims_supply/{currency}` attributes of the PoolManager,
+    // apart from its token balances: the tokens backing them are parked outside of any pool.
+    for (tx, currency, supply) in claims_supply_changes(claims_store_deltas, claim_deltas) {
+        let builder = transaction_changes
+            .entry(tx.index)
+            .or_insert_with(|| TransactionChangesBuilder::new(&tx));
+        builder.add_entity_change(&EntityChanges {
+            component_id: params.pool_manager.to_hex(),
+            attributes: vec![Attribute {
+                name: format!("claims_supply/{}", currency.to_hex()),
+                value: supply.to_signed_bytes_be(),
+                change: ChangeType::Update.into(),
+            }],
+        });
+    }
+
     // Insert others changes
     events
         .pool_events
@@ -164,6 +194,30 @@ pub fn map_protocol_changes(
     })
 }
 
+/// Pairs the claims store deltas with the claim deltas they were added from, returning the
+/// transaction, the currency and its new claim supply. Claims are only burnt from balances minted
+/// before, so the supply can't drop below zero.
+fn claims_supply_changes(
+    store_deltas: StoreDeltas,
+    claim_deltas: ClaimDeltas,
+) -> Vec<(Transaction, Vec<u8>, BigInt)> {
+    store_deltas
+        .deltas
+        .into_iter()
+        .zip(claim_deltas.deltas)
+        .map(|(store_delta, claim_delta)| {
+            let supply =
+                BigInt::from_str(&String::from_utf8(store_delta.new_value).unwrap()).unwrap();
+            assert!(
+                supply >= BigInt::zero(),
+                "claim supply of {} should not be negative",
+                claim_delta.currency.to_hex()
+            );
+            (claim_delta.transaction.unwrap().into(), claim_delta.currency, supply)
+        })
+        .collect()
+}
+
 fn event_to_attributes_updates(event: PoolEvent) -> Vec<(Transaction, PoolAddress, Attribute)> {
     match event.r#type.as_ref().unwrap() {
         pool_event::Type::Swap(swap) => vec![
@@ -224,6 +278,74 @@ fn event_to_attributes_updates(event: PoolEvent) -> Vec<(Transaction, PoolAddres
                 ),
             ]
         }
+        pool_event::Type::DynamicLpFeeUpdated(fee_update) => vec![(
+            event.transaction.unwrap().into(),
+            hex::decode(event.pool_id.trim_start_matches("0x")).unwrap(),
+            Attribute {
+                name: "lp_fee".to_string(),
+                value: BigInt::from(fee_update.lp_fee).to_signed_bytes_be(),
+                change: ChangeType::Update.into(),
+            },
+        )],
         _ => vec![],
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::uniswap::v4::{self as uni, ClaimDelta};
+    use substreams::pb::substreams::StoreDelta;
+
+    #[test]
+    fn test_claims_supply_changes() {
+        let usdc = hex::decode("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+        let claim_delta = |index: u64, delta: i32| ClaimDelta {
+            currency: usdc.clone(),
+            delta: BigInt::from(delta).to_signed_bytes_be(),
+            ordinal: index,
+            transaction: Some(uni::Transaction { index, ..Default::default() }),
+        };
+        let store_delta = |old: i32, new: i32| StoreDelta {
+            key: format!("claims:{}", usdc.to_hex()),
+            old_value: BigInt::from(old)
+                .to_string()
+                .into_bytes(),
+            new_value: BigInt::from(new)
+                .to_string()
+                .into_bytes(),
+            ..Default::default()
+        };
+
+        let changes = claims_supply_changes(
+            StoreDeltas { deltas: vec![store_delta(0, 1000), store_delta(1000, 400)] },
+            ClaimDeltas { deltas: vec![claim_delta(1, 1000), claim_delta(2, -600)] },
+        );
+
+        assert_eq!(
+            changes
+                .into_iter()
+                .map(|(tx, currency, supply)| (tx.index, currency, supply))
+                .collect::<Vec<_>>(),
+            vec![(1, usdc.clone(), BigInt::from(1000)), (2, usdc.clone(), BigInt::from(400))]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "should not be negative")]
+    fn test_negative_claims_supply() {
+        let store_delta = StoreDelta {
+            new_value: BigInt::from(-200)
+                .to_string()
+                .into_bytes(),
+            ..Default::default()
+        };
+        let claim_delta =
+            ClaimDelta { transaction: Some(uni::Transaction::default()), ..Default::default() };
+
+        claims_supply_changes(
+            StoreDeltas { deltas: vec![store_delta] },
+            ClaimDeltas { deltas: vec![claim_delta] },
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use ethabi::{ethereum_types::U256, Address, Token};
+use substreams::scalar::BigInt;
+use tycho_substreams::{
+    entrypoint::create_entrypoint,
+    models::{entry_point_params::TraceData, EntryPoint, EntryPointParams, RpcTraceData},
+    prelude::ProtocolComponent,
+};
+
+use crate::{
+    modules::pool_key::{to_twos_complement, PoolKey},
+    params::Params,
+};
+
+// Hook permission flags, see v4-core `Hooks.sol`. The initialize and liquidity callbacks (bits 8
+// to 13, above `BEFORE_SWAP_FLAG`) and the donate callbacks (bits 4 and 5, between
+// `AFTER_SWAP_FLAG` and `BEFORE_SWAP_RETURNS_DELTA_FLAG`) don't affect swaps and are only kept in
+// the raw bits.
+const BEFORE_SWAP_FLAG: u16 = 1 << 7;
+const AFTER_SWAP_FLAG: u16 = 1 << 6;
+const BEFORE_SWAP_RETURNS_DELTA_FLAG: u16 = 1 << 3;
+const AFTER_SWAP_RETURNS_DELTA_FLAG: u16 = 1 << 2;
+const AFTER_ADD_LIQUIDITY_RETURNS_DELTA_FLAG: u16 = 1 << 1;
+const AFTER_REMOVE_LIQUIDITY_RETURNS_DELTA_FLAG: u16 = 1;
+
+const ALL_HOOK_MASK: u16 = (1 << 14) - 1;
+
+const BEFORE_SWAP_SIGNATURE: &str =
+    "beforeSwap(address,(address,address,uint24,int24,address),(bool,int256,uint160),bytes)";
+const BEFORE_SWAP_SELECTOR: [u8; 4] = [0x57, 0x5e, 0x24, 0xb4];
+const AFTER_SWAP_SIGNATURE: &str =
+    "afterSwap(address,(address,address,uint24,int24,address),(bool,int256,uint160),int256,bytes)";
+const AFTER_SWAP_SELECTOR: [u8; 4] = [0xb4, 0x7b, 0x2f, 0xb1];
+
+/// `TickMath.MIN_SQRT_PRICE + 1`, the loosest price limit for a zero for one swap.
+const MIN_SQRT_PRICE_LIMIT: u64 = 4295128740;
+
+/// The hook callbacks a v4 pool calls, as encoded in the lowest 14 bits of the hook address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HookPermissions(u16);
+
+impl HookPermissions {
+    pub fn from_address(hooks: &[u8]) -> Self {
+        let len = hooks.len();
+        if len < 2 {
+            return Self(0);
+        }
+        Self(u16::from_be_bytes([hooks[len - 2], hooks[len - 1]]) & ALL_HOOK_MASK)
+    }
+
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    fn has(&self, flag: u16) -> bool {
+        self.0 & flag != 0
+    }
+
+    pub fn before_swap(&self) -> bool {
+        self.has(BEFORE_SWAP_FLAG)
+    }
+
+    pub fn after_swap(&self) -> bool {
+        self.has(AFTER_SWAP_FLAG)
+    }
+
+    /// Whether the hook can return deltas that alter the amounts settled by the caller. Pools
+    /// with such hooks can't be simulated by replaying the pool math alone.
+    pub fn returns_delta(&self) -> bool {
+        self.has(
+            BEFORE_SWAP_RETURNS_DELTA_FLAG |
+                AFTER_SWAP_RETURNS_DELTA_FLAG |
+                AFTER_ADD_LIQUIDITY_RETURNS_DELTA_FLAG |
+                AFTER_REMOVE_LIQUIDITY_RETURNS_DELTA_FLAG,
+        )
+    }
+}
+
+/// Creates the entrypoints of the swap callbacks a pool's hook implements, so the hook contract
+/// can be traced by the DCI.
+///
+/// The callbacks are traced with the PoolManager as caller, as hooks only accept calls from it.
+pub fn swap_entrypoints(
+    component: &ProtocolComponent,
+    params: &Params,
+) -> Vec<(EntryPoint, EntryPointParams)> {
+    let pool_manager = params.pool_manager.as_slice();
+    let Some(key) = pool_key(component, params) else {
+        return vec![];
+    };
+    let hooks = key.4.clone();
+    let permissions = HookPermissions::from_address(&hooks);
+
+    let mut entrypoints = vec![];
+    if permissions.before_swap() {
+        let calldata = [
+            BEFORE_SWAP_SELECTOR.as_slice(),
+            &ethabi::encode(&[
+                Token::Address(Address::from_slice(pool_manager)),
+                pool_key_token(&key),
+                swap_params_token(),
+                Token::Bytes(vec![]),
+            ]),
+        ]
+        .concat();
+        entrypoints.push(create_entrypoint(
+            hooks.clone(),
+            BEFORE_SWAP_SIGNATURE.to_string(),
+            component.id.clone(),
+            TraceData::Rpc(RpcTraceData { caller: Some(pool_manager.to_vec()), calldata }),
+        ));
+    }
+    if permissions.after_swap() {
+        let calldata = [
+            AFTER_SWAP_SELECTOR.as_slice(),
+            &ethabi::encode(&[
+                Token::Address(Address::from_slice(pool_manager)),
+                pool_key_token(&key),
+                swap_params_token(),
+                // The swap's BalanceDelta, left empty as it only feeds the hook's accounting.
+                Token::Int(U256::zero()),
+                Token::Bytes(vec![]),
+            ]),
+        ]
+        .concat();
+        entrypoints.push(create_entrypoint(
+            hooks,
+            AFTER_SWAP_SIGNATURE.to_string(),
+            component.id.clone(),
+            TraceData::Rpc(RpcTraceData { caller: Some(pool_manager.to_vec()), calldata }),
+        ));
+    }
+    entrypoints
+}
+
+fn pool_key(component: &ProtocolComponent, params: &Params) -> Option<PoolKey> {
+    let attribute = |name: &str| {
+        component
+            .static_att
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| attr.value.clone())
+    };
+    Some((
+        params.to_currency(component.tokens.first()?),
+        params.to_currency(component.tokens.get(1)?),
+        BigInt::from_signed_bytes_be(&attribute("key_lp_fee")?),
+        BigInt::from_signed_bytes_be(&attribute("tick_spacing")?),
+        attribute("hooks")?,
+    ))
+}
+
+fn pool_key_token(key: &PoolKey) -> Token {
+    let (currency0, currency1, fee, tick_spacing, hooks) = key;
+    Token::Tuple(vec![
+        Token::Address(Address::from_slice(currency0)),
+        Token::Address(Address::from_slice(currency1)),
+        Token::Uint(U256::from(fee.to_u64())),
+        Token::Int(to_twos_complement(tick_spacing)),
+        Token::Address(Address::from_slice(hooks)),
+    ])
+}
+
+/// A minimal exact-in zero for one swap, enough to reach the hook's swap logic.
+fn swap_params_token() -> Token {
+    Token::Tuple(vec![
+        Token::Bool(true),
+        Token::Int(to_twos_complement(&BigInt::from(-1))),
+        Token::Uint(U256::from(MIN_SQRT_PRICE_LIMIT)),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_permissions_from_address() {
+        // Hook with beforeAddLiquidity, afterAddLiquidity, beforeSwap, afterSwap and
+        // beforeSwapReturnsDelta enabled.
+        let hooks = hex::decode("0000000000000000000000000000000000000cc8").unwrap();
+        let permissions = HookPermissions::from_address(&hooks);
+
+        assert!(permissions.before_swap());
+        assert!(permissions.after_swap());
+        assert!(permissions.returns_delta());
+        assert_eq!(permissions.bits(), 0x0cc8);
+    }
+
+    #[test]
+    fn test_no_hooks() {
+        let permissions = HookPermissions::from_address(&[0; 20]);
+
+        assert_eq!(permissions.bits(), 0);
+        assert!(!permissions.returns_delta());
+    }
+}
@@ -11,6 +11,9 @@ mod store_pools;
 #[path = "3_map_events.rs"]
 mod map_events;
 
+#[path = "3_map_store_claims.rs"]
+mod map_store_claims;
+
 #[path = "4_store_current_tick.rs"]
 mod store_current_tick;
 
@@ -26,8 +29,10 @@ mod map_store_ticks;
 #[path = "5_map_store_liquidity.rs"]
 mod map_store_liquidity;
 
+mod hooks;
 #[path = "6_map_protocol_changes.rs"]
 mod map_protocol_changes;
+mod pool_key;
 mod uni_math;
 
 impl From<TransactionTrace> for Transaction {
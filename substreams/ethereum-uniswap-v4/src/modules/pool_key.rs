@@ -0,0 +1,70 @@
+use ethabi::{ethereum_types::U256, Address, Token};
+use substreams::scalar::BigInt;
+use tiny_keccak::{Hasher, Keccak};
+
+/// LP fee value used in the PoolKey to flag a pool as using dynamic fees.
+pub const DYNAMIC_FEE_FLAG: u32 = 0x800000;
+
+/// Whether the given PoolKey fee flags the pool as using dynamic LP fees.
+pub fn is_dynamic_fee(key_fee: u32) -> bool {
+    key_fee == DYNAMIC_FEE_FLAG
+}
+
+/// The PoolKey tuple as decoded by the PoolManager ABI:
+/// `(currency0, currency1, fee, tickSpacing, hooks)`.
+pub type PoolKey = (Vec<u8>, Vec<u8>, BigInt, BigInt, Vec<u8>);
+
+/// Computes the PoolId of a PoolKey, i.e. `keccak256(abi.encode(key))`.
+pub fn pool_id(key: &PoolKey) -> Vec<u8> {
+    let (currency0, currency1, fee, tick_spacing, hooks) = key;
+
+    let mut hasher = Keccak::v256();
+    hasher.update(&ethabi::encode(&[
+        Token::Address(Address::from_slice(currency0)),
+        Token::Address(Address::from_slice(currency1)),
+        Token::Uint(U256::from(fee.to_u64())),
+        Token::Int(to_twos_complement(tick_spacing)),
+        Token::Address(Address::from_slice(hooks)),
+    ]));
+
+    let mut output = vec![0; 32];
+    hasher.finalize(&mut output);
+    output
+}
+
+pub fn to_twos_complement(value: &BigInt) -> U256 {
+    let bytes = value.to_signed_bytes_be();
+    let fill = if value < &BigInt::zero() { 0xff } else { 0x00 };
+    let mut word = [fill; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    U256::from_big_endian(&word)
+}
+
+#[cfg(test)]
+mod tests {
+    use substreams_helper::hex::Hexable;
+
+    use super::*;
+
+    #[test]
+    fn test_pool_id_computation() {
+        let key: PoolKey = (
+            vec![0; 20],                                                      // ETH
+            hex::decode("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap(), // USDC
+            BigInt::from(500),
+            BigInt::from(10),
+            vec![0; 20],
+        );
+
+        assert_eq!(
+            pool_id(&key).to_hex(),
+            "0x21c67e77068de97969ba93d4aab21826d33ca12bb9f565d8496e8fda8a82ca27"
+        );
+    }
+
+    #[test]
+    fn test_negative_tick_spacing_encoding() {
+        assert_eq!(to_twos_complement(&BigInt::from(-1)), U256::MAX);
+        assert_eq!(to_twos_complement(&BigInt::from(60)), U256::from(60));
+    }
+}
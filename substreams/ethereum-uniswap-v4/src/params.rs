@@ -0,0 +1,71 @@
+use serde::Deserialize;
+
+const ZERO_ADDRESS: [u8; 20] = [0; 20];
+
+/// Module parameters, passed as a query string, e.g.
+/// `pool_manager=000000000004444c5dc75cB358380D2e3dE08A90&native_token=<address>`
+#[derive(Deserialize)]
+pub struct Params {
+    /// The PoolManager contract address.
+    #[serde(with = "hex::serde")]
+    pub pool_manager: Vec<u8>,
+    /// The address native ETH is reported as. The PoolManager represents it as `address(0)`;
+    /// if omitted, the zero address is kept as is. The shipped manifests omit it so the token ids
+    /// of existing deployments don't change; set it to e.g. the `0xEeee…` sentinel to opt in.
+    #[serde(with = "hex::serde", default)]
+    pub native_token: Vec<u8>,
+}
+
+impl Params {
+    pub fn parse(params: &str) -> Result<Self, anyhow::Error> {
+        serde_qs::from_str(params).map_err(|e| anyhow::anyhow!("Invalid params: {e}"))
+    }
+
+    /// Maps a PoolManager currency to the address it is reported as.
+    pub fn to_token(&self, currency: &[u8]) -> Vec<u8> {
+        if currency == ZERO_ADDRESS && !self.native_token.is_empty() {
+            self.native_token.clone()
+        } else {
+            currency.to_vec()
+        }
+    }
+
+    /// Maps a reported token back to the PoolManager currency, e.g. to rebuild a PoolKey.
+    pub fn to_currency(&self, token: &[u8]) -> Vec<u8> {
+        if !self.native_token.is_empty() && token == self.native_token {
+            ZERO_ADDRESS.to_vec()
+        } else {
+            token.to_vec()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ETH_ADDRESS: &str = "eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee";
+
+    #[test]
+    fn test_native_token_mapping() {
+        let params = Params::parse(&format!(
+            "pool_manager=000000000004444c5dc75cB358380D2e3dE08A90&native_token={ETH_ADDRESS}"
+        ))
+        .unwrap();
+        let eth = hex::decode(ETH_ADDRESS).unwrap();
+        let usdc = hex::decode("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+
+        assert_eq!(params.to_token(&ZERO_ADDRESS), eth);
+        assert_eq!(params.to_token(&usdc), usdc);
+        assert_eq!(params.to_currency(&eth), ZERO_ADDRESS.to_vec());
+        assert_eq!(params.to_currency(&usdc), usdc);
+    }
+
+    #[test]
+    fn test_native_token_defaults_to_zero_address() {
+        let params =
+            Params::parse("pool_manager=000000000004444c5dc75cB358380D2e3dE08A90").unwrap();
+
+        assert_eq!(params.to_token(&ZERO_ADDRESS), ZERO_ADDRESS.to_vec());
+    }
+}
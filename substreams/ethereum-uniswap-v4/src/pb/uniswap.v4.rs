@@ -84,6 +84,29 @@ pub struct LiquidityChanges {
     #[prost(message, repeated, tag="1")]
     pub changes: ::prost::alloc::vec::Vec<LiquidityChange>,
 }
+/// A change to the ERC-6909 claim supply of a currency held by the PoolManager.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClaimDelta {
+    /// The currency address backing the claim (the ERC-6909 id).
+    #[prost(bytes="vec", tag="1")]
+    pub currency: ::prost::alloc::vec::Vec<u8>,
+    /// The minted (positive) or burned (negative) amount. Bigint encoded as signed big endian bytes.
+    #[prost(bytes="vec", tag="2")]
+    pub delta: ::prost::alloc::vec::Vec<u8>,
+    /// Used to determine the order of the claim changes. Necessary for the claims store.
+    #[prost(uint64, tag="3")]
+    pub ordinal: u64,
+    #[prost(message, optional, tag="4")]
+    pub transaction: ::core::option::Option<Transaction>,
+}
+/// A group of ClaimDelta
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClaimDeltas {
+    #[prost(message, repeated, tag="1")]
+    pub deltas: ::prost::alloc::vec::Vec<ClaimDelta>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Events {
@@ -108,7 +131,7 @@ pub mod events {
         pub currency1: ::prost::alloc::string::String,
         #[prost(message, optional, tag="105")]
         pub transaction: ::core::option::Option<super::Transaction>,
-        #[prost(oneof="pool_event::Type", tags="1, 2, 3, 4, 5")]
+        #[prost(oneof="pool_event::Type", tags="1, 2, 3, 4, 5, 6")]
         pub r#type: ::core::option::Option<pool_event::Type>,
     }
     /// Nested message and enum types in `PoolEvent`.
@@ -183,6 +206,14 @@ pub mod events {
             #[prost(uint32, tag="2")]
             pub protocol_fee: u32,
         }
+        /// The LP fee of a dynamic fee pool changed.
+        #[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct DynamicLpFeeUpdated {
+            /// In hundredths of a bip
+            #[prost(uint32, tag="1")]
+            pub lp_fee: u32,
+        }
         #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Oneof)]
         pub enum Type {
@@ -196,6 +227,8 @@ pub mod events {
             Donate(Donate),
             #[prost(message, tag="5")]
             ProtocolFeeUpdated(ProtocolFeeUpdated),
+            #[prost(message, tag="6")]
+            DynamicLpFeeUpdated(DynamicLpFeeUpdated),
         }
     }
 }